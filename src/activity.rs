@@ -0,0 +1,296 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::webhooks::{optional_string_or_number, string_or_number};
+
+/// Kind of object recorded in the activity log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActivityObjectType {
+    Task,
+    Project,
+    Comment,
+}
+
+impl ActivityObjectType {
+    /// Name used by the API: `item`, `project` or `note`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityObjectType::Task => "item",
+            ActivityObjectType::Project => "project",
+            ActivityObjectType::Comment => "note",
+        }
+    }
+}
+
+/// What happened to an object in an activity event
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ActivityAction {
+    Added,
+    Updated,
+    Deleted,
+    Completed,
+    Uncompleted,
+    Archived,
+    Unarchived,
+    Shared,
+    Left,
+    /// An event type this crate does not model
+    Other(String),
+}
+
+impl ActivityAction {
+    /// Name used by the API, such as `completed`
+    pub fn as_str(&self) -> &str {
+        match self {
+            ActivityAction::Added => "added",
+            ActivityAction::Updated => "updated",
+            ActivityAction::Deleted => "deleted",
+            ActivityAction::Completed => "completed",
+            ActivityAction::Uncompleted => "uncompleted",
+            ActivityAction::Archived => "archived",
+            ActivityAction::Unarchived => "unarchived",
+            ActivityAction::Shared => "shared",
+            ActivityAction::Left => "left",
+            ActivityAction::Other(event_type) => event_type,
+        }
+    }
+
+    fn parse(event_type: &str) -> Self {
+        match event_type {
+            "added" => ActivityAction::Added,
+            "updated" => ActivityAction::Updated,
+            "deleted" => ActivityAction::Deleted,
+            "completed" => ActivityAction::Completed,
+            "uncompleted" => ActivityAction::Uncompleted,
+            "archived" => ActivityAction::Archived,
+            "unarchived" => ActivityAction::Unarchived,
+            "shared" => ActivityAction::Shared,
+            "left" => ActivityAction::Left,
+            other => ActivityAction::Other(other.to_string()),
+        }
+    }
+}
+
+/// Object an activity event is about, identified like the matching model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActivityObject {
+    /// A [`crate::Task`]
+    Task { id: String, project_id: Option<String> },
+    /// A [`crate::Project`]
+    Project { id: String },
+    /// A [`crate::Comment`] on a task or project
+    Comment {
+        id: String,
+        task_id: Option<String>,
+        project_id: Option<String>,
+    },
+    /// An object type this crate does not model
+    Other { object_type: String, id: String },
+}
+
+impl ActivityObject {
+    pub fn id(&self) -> &str {
+        match self {
+            ActivityObject::Task { id, .. }
+            | ActivityObject::Project { id }
+            | ActivityObject::Comment { id, .. }
+            | ActivityObject::Other { id, .. } => id,
+        }
+    }
+}
+
+/// A field changed by an event, named after the field of the matching model
+///
+/// `before` is `None` for fields set by an `added` event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// One entry of the activity log
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityEvent {
+    pub id: String,
+    pub object: ActivityObject,
+    pub action: ActivityAction,
+    pub event_date: String,
+    /// User who made the change, `None` for changes made by Todoist itself
+    pub initiator_id: Option<String>,
+    pub changes: Vec<FieldChange>,
+    /// Raw event details as sent by the API
+    pub extra_data: Value,
+}
+
+impl ActivityEvent {
+    /// Change of a single field, such as `content` or `due`
+    pub fn change(&self, field: &str) -> Option<&FieldChange> {
+        self.changes.iter().find(|change| change.field == field)
+    }
+}
+
+/// Activity log query arguments
+///
+/// `since` and `until` are datetimes such as `2024-05-01T00:00:00`.
+#[derive(Debug, Clone, Default)]
+pub struct ActivityArgs {
+    pub object_type: Option<ActivityObjectType>,
+    /// Only meaningful together with `object_type`
+    pub object_id: Option<String>,
+    pub event_type: Option<ActivityAction>,
+    pub initiator_id: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Page size, at most 100
+    pub limit: Option<i32>,
+    pub offset: Option<i32>,
+}
+
+impl ActivityArgs {
+    pub(crate) fn query_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(object_type) = self.object_type {
+            params.push(("object_type", object_type.as_str().to_string()));
+        }
+        if let Some(object_id) = &self.object_id {
+            params.push(("object_id", object_id.clone()));
+        }
+        if let Some(event_type) = &self.event_type {
+            params.push(("event_type", event_type.as_str().to_string()));
+        }
+        if let Some(initiator_id) = &self.initiator_id {
+            params.push(("initiator_id", initiator_id.clone()));
+        }
+        if let Some(since) = &self.since {
+            params.push(("since", since.clone()));
+        }
+        if let Some(until) = &self.until {
+            params.push(("until", until.clone()));
+        }
+        if let Some(limit) = self.limit {
+            params.push(("limit", limit.to_string()));
+        }
+        if let Some(offset) = self.offset {
+            params.push(("offset", offset.to_string()));
+        }
+        params
+    }
+}
+
+/// Response of the activity log endpoint
+#[derive(Debug, Deserialize)]
+pub(crate) struct ActivityResponse {
+    #[serde(default)]
+    pub events: Vec<RawActivityEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RawActivityEvent {
+    #[serde(deserialize_with = "string_or_number")]
+    id: String,
+    object_type: String,
+    #[serde(deserialize_with = "string_or_number")]
+    object_id: String,
+    event_type: String,
+    event_date: String,
+    #[serde(default, deserialize_with = "optional_string_or_number")]
+    parent_project_id: Option<String>,
+    #[serde(default, deserialize_with = "optional_string_or_number")]
+    parent_item_id: Option<String>,
+    #[serde(default, deserialize_with = "optional_string_or_number")]
+    initiator_id: Option<String>,
+    #[serde(default)]
+    extra_data: Value,
+}
+
+impl From<RawActivityEvent> for ActivityEvent {
+    fn from(raw: RawActivityEvent) -> Self {
+        let object = match raw.object_type.as_str() {
+            "item" => ActivityObject::Task {
+                id: raw.object_id,
+                project_id: raw.parent_project_id,
+            },
+            "project" => ActivityObject::Project { id: raw.object_id },
+            "note" => ActivityObject::Comment {
+                id: raw.object_id,
+                task_id: raw.parent_item_id,
+                project_id: raw.parent_project_id,
+            },
+            _ => ActivityObject::Other {
+                object_type: raw.object_type,
+                id: raw.object_id,
+            },
+        };
+        let action = ActivityAction::parse(&raw.event_type);
+        let changes = match raw.extra_data.as_object() {
+            Some(extra) => field_changes(&object, &action, extra),
+            None => Vec::new(),
+        };
+
+        ActivityEvent {
+            id: raw.id,
+            object,
+            action,
+            event_date: raw.event_date,
+            initiator_id: raw.initiator_id,
+            changes,
+            extra_data: raw.extra_data,
+        }
+    }
+}
+
+/// Extra data keys describing the object rather than a change to it
+const CONTEXT_KEYS: &[&str] = &[
+    "client",
+    "name_of_parent",
+    "parent_name",
+    "parent_item_content",
+    "note_count",
+];
+
+/// Build field changes from `X` / `last_X` pairs of the extra data
+///
+/// `added` events report every field they set, other events only fields with a previous value.
+fn field_changes(object: &ActivityObject, action: &ActivityAction, extra: &Map<String, Value>) -> Vec<FieldChange> {
+    let mut changes: Vec<FieldChange> = extra
+        .iter()
+        .filter(|(key, _)| !key.starts_with("last_") && !CONTEXT_KEYS.contains(&key.as_str()))
+        .filter_map(|(key, after)| {
+            let before = extra.get(&format!("last_{key}")).cloned();
+            if before.is_none() && *action != ActivityAction::Added {
+                return None;
+            }
+            Some(FieldChange {
+                field: model_field(object, key).to_string(),
+                before,
+                after: Some(after.clone()),
+            })
+        })
+        .collect();
+
+    // A field cleared by the update only has its previous value
+    for (key, before) in extra {
+        if let Some(key) = key.strip_prefix("last_") {
+            if !extra.contains_key(key) {
+                changes.push(FieldChange {
+                    field: model_field(object, key).to_string(),
+                    before: Some(before.clone()),
+                    after: None,
+                });
+            }
+        }
+    }
+    changes
+}
+
+/// Name of the model field matching an activity log key
+fn model_field<'a>(object: &ActivityObject, key: &'a str) -> &'a str {
+    match (object, key) {
+        (ActivityObject::Task { .. }, "due_date") => "due",
+        (ActivityObject::Task { .. }, "deadline_date") => "deadline",
+        (ActivityObject::Task { .. }, "responsible_uid") => "assignee_id",
+        (ActivityObject::Task { .. }, "checked") => "is_completed",
+        _ => key,
+    }
+}
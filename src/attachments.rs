@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Name of the manifest written next to archived attachments
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// How an attachment already present in an archive is recognized as complete
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExistingFileCheck {
+    /// The file on disk has the size recorded in the manifest
+    Size,
+    /// The file on disk has the size and SHA-256 recorded in the manifest
+    #[default]
+    Hash,
+}
+
+/// An archived attachment
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ManifestEntry {
+    pub comment_id: String,
+    pub task_id: Option<String>,
+    pub project_id: Option<String>,
+    pub file_name: String,
+    pub file_type: String,
+    pub file_url: String,
+    /// Path of the file, relative to the archive directory
+    pub path: String,
+    pub size: u64,
+    /// Hex-encoded SHA-256 of the file
+    pub sha256: String,
+    pub downloaded_at: String,
+}
+
+/// Index of the attachments stored in an archive directory
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AttachmentManifest {
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl AttachmentManifest {
+    /// Read the manifest of a directory, or an empty one if there is none yet
+    pub async fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE);
+        match tokio::fs::read(&path).await {
+            Ok(contents) => {
+                serde_json::from_slice(&contents).with_context(|| format!("invalid manifest {}", path.display()))
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    /// Write the manifest into a directory
+    pub async fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(MANIFEST_FILE);
+        tokio::fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Entry of the attachment of a comment
+    pub fn entry(&self, comment_id: &str) -> Option<&ManifestEntry> {
+        self.entries.iter().find(|entry| entry.comment_id == comment_id)
+    }
+
+    /// Add an entry, replacing any entry for the same comment
+    pub fn upsert(&mut self, entry: ManifestEntry) {
+        self.entries.retain(|existing| existing.comment_id != entry.comment_id);
+        self.entries.push(entry);
+    }
+}
+
+/// Outcome of archiving the attachments of a project
+#[derive(Debug, Default)]
+pub struct ArchiveReport {
+    /// Comment IDs whose attachment was downloaded
+    pub downloaded: Vec<String>,
+    /// Comment IDs whose attachment was already archived
+    pub skipped: Vec<String>,
+    /// Comment IDs whose attachment could not be downloaded, with the reason
+    pub failed: Vec<(String, anyhow::Error)>,
+}
+
+/// File name for an archived attachment, safe on every platform
+pub(crate) fn archive_file_name(comment_id: &str, file_name: &str) -> String {
+    let comment_id = sanitize_file_name(comment_id);
+    let file_name = sanitize_file_name(file_name);
+    format!(
+        "{}-{}",
+        if comment_id.is_empty() { "comment" } else { &comment_id },
+        if file_name.is_empty() { "attachment" } else { &file_name }
+    )
+}
+
+/// Replace path separators and characters some file systems reject, and drop leading dots
+fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    sanitized.trim_start_matches('.').to_string()
+}
+
+/// Size and hex-encoded SHA-256 of a file
+pub(crate) async fn hash_file(path: &Path) -> Result<(u64, String)> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    let sha256 = hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect();
+    Ok((size, sha256))
+}
+
+/// Whether a file matches its manifest entry
+pub(crate) async fn is_archived(path: &Path, entry: &ManifestEntry, check: ExistingFileCheck) -> bool {
+    match check {
+        ExistingFileCheck::Size => tokio::fs::metadata(path)
+            .await
+            .is_ok_and(|metadata| metadata.len() == entry.size),
+        ExistingFileCheck::Hash => hash_file(path)
+            .await
+            .is_ok_and(|(size, sha256)| size == entry.size && sha256 == entry.sha256),
+    }
+}
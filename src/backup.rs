@@ -0,0 +1,568 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+
+use crate::batch::{Batch, BatchResult, CommandResult};
+use crate::models::*;
+use crate::sync::{ProjectDataResponse, SyncResponse, FULL_SYNC_TOKEN};
+use crate::wrapper::TodoistWrapper;
+
+/// Value of [`Backup::format`], identifying a backup file
+pub const BACKUP_FORMAT: &str = "todoist-api-backup";
+
+/// Current version of the backup layout
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Name of the JSON document inside a zip backup
+const ZIP_ENTRY: &str = "backup.json";
+
+/// File format of a serialized backup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackupFormat {
+    /// A single JSON document
+    #[default]
+    Json,
+    /// A zip archive holding the JSON document
+    Zip,
+}
+
+impl BackupFormat {
+    /// Format matching the extension of a path, JSON unless it ends in `.zip`
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("zip") => BackupFormat::Zip,
+            _ => BackupFormat::Json,
+        }
+    }
+}
+
+/// Snapshot of an account
+///
+/// Attachments are kept as the metadata of their comments; the files themselves are not copied.
+/// Tasks of archived projects and sections are in `tasks` and `completed_tasks` with the others.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Backup {
+    /// Always [`BACKUP_FORMAT`]
+    pub format: String,
+    pub version: u32,
+    pub created_at: String,
+    pub projects: Vec<Project>,
+    /// Archived projects, archived again on restore
+    #[serde(default)]
+    pub archived_projects: Vec<Project>,
+    pub sections: Vec<Section>,
+    /// Archived sections, archived again on restore
+    #[serde(default)]
+    pub archived_sections: Vec<Section>,
+    pub tasks: Vec<Task>,
+    pub completed_tasks: Vec<CompletedTask>,
+    pub labels: Vec<Label>,
+    pub comments: Vec<Comment>,
+    pub filters: Vec<SavedFilter>,
+}
+
+impl Backup {
+    /// Build a backup from a full sync, the archived projects and the completion history
+    ///
+    /// The sync must also hold the tasks, sections and comments of the archived projects.
+    pub(crate) fn from_sync(
+        sync: SyncResponse,
+        archived_projects: Vec<Project>,
+        completed_tasks: Vec<CompletedTask>,
+    ) -> Self {
+        let (archived_sections, sections): (Vec<_>, Vec<_>) = sync
+            .sections
+            .into_iter()
+            .filter(|section| !section.is_deleted)
+            .partition(|section| section.is_archived);
+        let (synced_archived_projects, projects): (Vec<_>, Vec<_>) = sync
+            .projects
+            .into_iter()
+            .filter(|project| !project.is_deleted)
+            .partition(|project| project.is_archived);
+        let mut archived_projects = archived_projects;
+        for project in synced_archived_projects {
+            if !archived_projects.iter().any(|archived| archived.id == project.id) {
+                archived_projects.push(Project::from(project));
+            }
+        }
+
+        Backup {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            projects: projects.into_iter().map(Project::from).collect(),
+            archived_projects,
+            sections: sections.into_iter().map(Section::from).collect(),
+            archived_sections: archived_sections.into_iter().map(Section::from).collect(),
+            tasks: sync
+                .items
+                .into_iter()
+                .filter(|item| !item.is_deleted && !item.checked)
+                .map(Task::from)
+                .collect(),
+            completed_tasks,
+            labels: sync
+                .labels
+                .into_iter()
+                .filter(|label| !label.is_deleted)
+                .map(Label::from)
+                .collect(),
+            comments: sync
+                .notes
+                .into_iter()
+                .chain(sync.project_notes)
+                .filter(|note| !note.is_deleted)
+                .map(Comment::from)
+                .collect(),
+            filters: sync
+                .filters
+                .into_iter()
+                .filter(|filter| !filter.is_deleted)
+                .map(SavedFilter::from)
+                .collect(),
+        }
+    }
+
+    /// Serialize the backup
+    pub fn to_bytes(&self, format: BackupFormat) -> Result<Vec<u8>> {
+        let json = serde_json::to_vec_pretty(self)?;
+        match format {
+            BackupFormat::Json => Ok(json),
+            BackupFormat::Zip => {
+                let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+                let options =
+                    zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+                zip.start_file(ZIP_ENTRY, options)?;
+                zip.write_all(&json)?;
+                Ok(zip.finish()?.into_inner())
+            }
+        }
+    }
+
+    /// Read a JSON or zip backup, detecting the format from its content
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let backup: Backup = if bytes.starts_with(b"PK\x03\x04") {
+            let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).context("invalid zip backup")?;
+            let mut json = Vec::new();
+            zip.by_name(ZIP_ENTRY)
+                .with_context(|| format!("zip backup has no {ZIP_ENTRY}"))?
+                .read_to_end(&mut json)?;
+            serde_json::from_slice(&json)
+        } else {
+            serde_json::from_slice(bytes)
+        }
+        .context("invalid backup")?;
+
+        if backup.format != BACKUP_FORMAT {
+            bail!("not a backup: format is {:?}", backup.format);
+        }
+        if backup.version > BACKUP_VERSION {
+            bail!(
+                "backup version {} is newer than the supported version {BACKUP_VERSION}",
+                backup.version
+            );
+        }
+        Ok(backup)
+    }
+
+    /// Write the backup to a file, as a zip archive if the path ends in `.zip`
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        let bytes = self.to_bytes(BackupFormat::from_path(path))?;
+        tokio::fs::write(path, bytes)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Read a backup file written by [`Backup::save`]
+    pub async fn load(path: &Path) -> Result<Self> {
+        let bytes = tokio::fs::read(path)
+            .await
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl TodoistWrapper {
+    /// Snapshot the whole account, including archived projects and the completion history
+    ///
+    /// Costs one extra request per archived project, to read its tasks, sections and comments.
+    pub async fn backup(&self) -> Result<Backup> {
+        let mut sync = self
+            .sync(
+                FULL_SYNC_TOKEN,
+                &[
+                    "projects",
+                    "sections",
+                    "items",
+                    "labels",
+                    "notes",
+                    "project_notes",
+                    "filters",
+                ],
+            )
+            .await?;
+        let archived_projects = self.get_all_archived_projects().await?;
+        for project in &archived_projects {
+            let data = self.get_project_data(&project.id).await?;
+            sync.items.extend(data.items);
+            for section in data.sections {
+                if !sync.sections.iter().any(|synced| synced.id == section.id) {
+                    sync.sections.push(section);
+                }
+            }
+            sync.project_notes.extend(data.project_notes);
+        }
+        let completed_tasks = self.get_all_completed_tasks(&CompletedTaskArgs::default()).await?;
+        Ok(Backup::from_sync(sync, archived_projects, completed_tasks))
+    }
+
+    /// Tasks, sections and comments of a project, archived or not
+    async fn get_project_data(&self, project_id: &str) -> Result<ProjectDataResponse> {
+        let url = format!("{}/projects/get_data", self.sync_base_url);
+        let response = self
+            .send(self.client.get(&url).query(&[("project_id", project_id)]))
+            .await?
+            .error_for_status()?;
+
+        let data: ProjectDataResponse = response.json().await?;
+        Ok(data)
+    }
+
+    /// Recreate a backup in this account, remapping IDs
+    ///
+    /// With `dry_run`, nothing is sent and the report tells what would be created. Labels and
+    /// saved filters that already exist are reused, as is the inbox.
+    pub async fn restore_backup(&self, backup: &Backup, dry_run: bool) -> Result<RestoreReport> {
+        let target = self.sync(FULL_SYNC_TOKEN, &["projects", "labels", "filters"]).await?;
+        let plan = plan_restore(backup, &target);
+        if dry_run {
+            return Ok(plan.report);
+        }
+
+        let result = self.execute_batch(&plan.batch).await?;
+        Ok(plan.finish(result))
+    }
+}
+
+/// What a restore created, or would create in a dry run
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    pub dry_run: bool,
+    pub projects: usize,
+    pub sections: usize,
+    pub tasks: usize,
+    pub completed_tasks: usize,
+    pub comments: usize,
+    pub labels: usize,
+    pub filters: usize,
+    /// Projects and sections archived after being created, also counted in `projects` and `sections`
+    pub archived: usize,
+    /// Objects of the backup that are not recreated, with the reason
+    pub skipped: Vec<String>,
+    /// Backup ID to ID in the target account
+    ///
+    /// A dry run only maps objects reused from the target account, such as the inbox.
+    pub id_mapping: HashMap<String, String>,
+    /// Commands rejected by the server
+    pub failed: Vec<CommandResult>,
+}
+
+/// Commands recreating a backup in a target account
+struct RestorePlan {
+    pub batch: Batch,
+    pub report: RestoreReport,
+    /// Backup ID to temporary ID of the command creating the object
+    created: HashMap<String, String>,
+}
+
+impl RestorePlan {
+    /// Report of an executed plan, with the real IDs of created objects
+    pub(crate) fn finish(mut self, result: BatchResult) -> RestoreReport {
+        for (backup_id, temp_id) in &self.created {
+            if let Some(id) = result.real_id(temp_id) {
+                self.report.id_mapping.insert(backup_id.clone(), id.to_string());
+            }
+        }
+        self.report.dry_run = false;
+        self.report.failed = result.errors().into_iter().cloned().collect();
+        self.report
+    }
+}
+
+/// Plan the restore of a backup into the account described by `target`
+///
+/// The inbox, labels and saved filters that already exist in the target, matched by name, are
+/// reused instead of created. Everything else is created, so restoring twice duplicates it.
+fn plan_restore(backup: &Backup, target: &SyncResponse) -> RestorePlan {
+    let mut batch = Batch::new();
+    let mut report = RestoreReport {
+        dry_run: true,
+        ..Default::default()
+    };
+    // Backup ID to the ID commands should use: a temporary ID or an existing ID
+    let mut ids: HashMap<String, String> = HashMap::new();
+    let mut created: HashMap<String, String> = HashMap::new();
+
+    for label in &backup.labels {
+        if let Some(existing) = target
+            .labels
+            .iter()
+            .find(|existing| !existing.is_deleted && existing.name.eq_ignore_ascii_case(&label.name))
+        {
+            report.id_mapping.insert(label.id.clone(), existing.id.clone());
+            report.skipped.push(format!("label {} already exists", label.name));
+            continue;
+        }
+        let temp_id = batch.add_label(&CreateLabelArgs {
+            name: label.name.clone(),
+            color: Some(label.color.clone()),
+            order: Some(label.order),
+            is_favorite: Some(label.is_favorite),
+        });
+        created.insert(label.id.clone(), temp_id);
+        report.labels += 1;
+    }
+
+    let inbox = target
+        .projects
+        .iter()
+        .find(|project| project.inbox_project && !project.is_deleted);
+    let all_projects: Vec<Project> = backup
+        .projects
+        .iter()
+        .chain(&backup.archived_projects)
+        .cloned()
+        .collect();
+    let projects = parents_first(
+        &all_projects,
+        |project| &project.id,
+        |project| project.parent_id.as_deref(),
+        |project| project.order,
+    );
+    for project in projects {
+        if project.is_inbox_project {
+            if let Some(inbox) = inbox {
+                ids.insert(project.id.clone(), inbox.id.clone());
+                report.id_mapping.insert(project.id.clone(), inbox.id.clone());
+                continue;
+            }
+        }
+        let temp_id = batch.add_project(&CreateProjectArgs {
+            name: project.name.clone(),
+            color: Some(project.color.clone()),
+            parent_id: project.parent_id.as_ref().and_then(|id| ids.get(id)).cloned(),
+            is_favorite: Some(project.is_favorite),
+            view_style: Some(project.view_style.clone()).filter(|style| !style.is_empty()),
+        });
+        ids.insert(project.id.clone(), temp_id.clone());
+        created.insert(project.id.clone(), temp_id);
+        report.projects += 1;
+    }
+
+    let mut sections: Vec<&Section> = backup.sections.iter().chain(&backup.archived_sections).collect();
+    sections.sort_by_key(|section| section.order);
+    for section in sections {
+        let Some(project_id) = ids.get(&section.project_id).cloned() else {
+            report.skipped.push(format!(
+                "section {}: project {} is not in the backup",
+                section.id, section.project_id
+            ));
+            continue;
+        };
+        let temp_id = batch.add_section(&CreateSectionArgs {
+            name: section.name.clone(),
+            project_id,
+            order: Some(section.order),
+        });
+        ids.insert(section.id.clone(), temp_id.clone());
+        created.insert(section.id.clone(), temp_id);
+        report.sections += 1;
+    }
+
+    let tasks = parents_first(
+        &backup.tasks,
+        |task| &task.id,
+        |task| task.parent_id.as_deref(),
+        |task| task.order,
+    );
+    for task in tasks {
+        let Some(args) = task_args(task, &ids) else {
+            report.skipped.push(format!(
+                "task {}: project {} is not in the backup",
+                task.id, task.project_id
+            ));
+            continue;
+        };
+        let temp_id = batch.add_task(&args);
+        ids.insert(task.id.clone(), temp_id.clone());
+        created.insert(task.id.clone(), temp_id);
+        report.tasks += 1;
+    }
+
+    for completed in &backup.completed_tasks {
+        let snapshot = completed.task.clone().unwrap_or_else(|| Task {
+            id: completed.task_id.clone(),
+            content: completed.content.clone(),
+            description: String::new(),
+            project_id: completed.project_id.clone(),
+            section_id: completed.section_id.clone(),
+            parent_id: None,
+            order: 0,
+            priority: 1,
+            is_completed: true,
+            labels: Vec::new(),
+            created_at: String::new(),
+            due: None,
+            deadline: None,
+            duration: None,
+            assignee_id: None,
+            url: String::new(),
+            comment_count: 0,
+        });
+        // Closing a recurring task would reschedule it instead of completing it
+        let snapshot = Task { due: None, ..snapshot };
+        let Some(args) = task_args(&snapshot, &ids) else {
+            report.skipped.push(format!(
+                "completed task {}: project {} is not in the backup",
+                completed.task_id, completed.project_id
+            ));
+            continue;
+        };
+        let temp_id = batch.add_task(&args);
+        batch.complete_task(&temp_id);
+        ids.insert(completed.task_id.clone(), temp_id.clone());
+        created.insert(completed.task_id.clone(), temp_id);
+        report.completed_tasks += 1;
+    }
+
+    for comment in &backup.comments {
+        let task_id = comment.task_id.as_ref().and_then(|id| ids.get(id)).cloned();
+        let project_id = comment.project_id.as_ref().and_then(|id| ids.get(id)).cloned();
+        if task_id.is_none() && project_id.is_none() {
+            report.skipped.push(format!(
+                "comment {}: its task or project is not in the backup",
+                comment.id
+            ));
+            continue;
+        }
+        let temp_id = batch.add_comment(&CreateCommentArgs {
+            content: comment.content.clone(),
+            project_id: if task_id.is_none() { project_id } else { None },
+            task_id,
+            attachment: comment.attachment.clone(),
+        });
+        created.insert(comment.id.clone(), temp_id);
+        report.comments += 1;
+    }
+
+    for filter in &backup.filters {
+        if let Some(existing) = target
+            .filters
+            .iter()
+            .find(|existing| !existing.is_deleted && existing.name.eq_ignore_ascii_case(&filter.name))
+        {
+            report.id_mapping.insert(filter.id.clone(), existing.id.clone());
+            report.skipped.push(format!("filter {} already exists", filter.name));
+            continue;
+        }
+        let temp_id = batch.add_filter(&CreateSavedFilterArgs {
+            name: filter.name.clone(),
+            query: filter.query.clone(),
+            color: Some(filter.color.clone()),
+            order: Some(filter.order),
+            is_favorite: Some(filter.is_favorite),
+        });
+        created.insert(filter.id.clone(), temp_id);
+        report.filters += 1;
+    }
+
+    // Archived last, once their tasks and comments exist. Archiving a project archives its
+    // sub-projects, so only the topmost archived projects are archived explicitly.
+    for section in &backup.archived_sections {
+        if let Some(temp_id) = created.get(&section.id) {
+            batch.archive_section(temp_id);
+            report.archived += 1;
+        }
+    }
+    let archived_ids: HashSet<&str> = backup
+        .archived_projects
+        .iter()
+        .map(|project| project.id.as_str())
+        .collect();
+    for project in &backup.archived_projects {
+        if project
+            .parent_id
+            .as_deref()
+            .is_some_and(|parent_id| archived_ids.contains(parent_id))
+        {
+            continue;
+        }
+        if let Some(temp_id) = created.get(&project.id) {
+            batch.archive_project(temp_id);
+            report.archived += 1;
+        }
+    }
+
+    RestorePlan { batch, report, created }
+}
+
+/// Creation arguments recreating a task, or `None` if its project is unknown
+///
+/// The assignee is dropped, as users differ between accounts.
+fn task_args(task: &Task, ids: &HashMap<String, String>) -> Option<CreateTaskArgs> {
+    let project_id = ids.get(&task.project_id)?.clone();
+    let mut args = CreateTaskArgs {
+        content: task.content.clone(),
+        description: Some(task.description.clone()).filter(|description| !description.is_empty()),
+        project_id: Some(project_id),
+        section_id: task.section_id.as_ref().and_then(|id| ids.get(id)).cloned(),
+        parent_id: task.parent_id.as_ref().and_then(|id| ids.get(id)).cloned(),
+        order: Some(task.order),
+        priority: Some(task.priority),
+        labels: Some(task.labels.clone()).filter(|labels| !labels.is_empty()),
+        deadline_date: task.deadline.as_ref().map(|deadline| deadline.date.clone()),
+        duration: task.duration.as_ref().map(|duration| duration.amount),
+        duration_unit: task.duration.as_ref().map(|duration| duration.unit.clone()),
+        ..Default::default()
+    };
+    if let Some(due) = &task.due {
+        // Only the due string carries the recurrence
+        if due.is_recurring {
+            args.due_string = Some(due.string.clone());
+        } else if due.datetime.is_some() {
+            args.due_datetime = due.datetime.clone();
+        } else {
+            args.due_date = Some(due.date.clone());
+        }
+    }
+    Some(args)
+}
+
+/// Items sorted so that every parent comes before its children, then by order
+fn parents_first<T>(
+    items: &[T],
+    id: impl Fn(&T) -> &String,
+    parent: impl Fn(&T) -> Option<&str>,
+    order: impl Fn(&T) -> i32,
+) -> Vec<&T> {
+    let by_id: HashMap<&str, &T> = items.iter().map(|item| (id(item).as_str(), item)).collect();
+    let depth = |item: &T| {
+        let mut depth = 0;
+        let mut current = parent(item);
+        // Bounded by the number of items in case of a cycle
+        while let Some(parent_id) = current.filter(|_| depth < items.len()) {
+            let Some(next) = by_id.get(parent_id) else {
+                break;
+            };
+            depth += 1;
+            current = parent(next);
+        }
+        depth
+    };
+    let mut sorted: Vec<&T> = items.iter().collect();
+    sorted.sort_by_key(|item| (depth(item), order(item)));
+    sorted
+}
@@ -0,0 +1,603 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::models::*;
+
+/// Maximum number of commands the Sync API accepts in a single request
+pub const SYNC_COMMAND_LIMIT: usize = 100;
+
+/// A single Sync API command queued in a [`Batch`]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchCommand {
+    #[serde(rename = "type")]
+    pub command_type: String,
+    pub uuid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp_id: Option<String>,
+    pub args: Value,
+}
+
+/// A list of commands submitted together through the Sync API
+///
+/// Commands that create objects return a temporary ID which can be used in place of a real ID
+/// by any later command of the same batch:
+///
+/// ```rust
+/// use todoist_api::*;
+///
+/// let mut batch = Batch::new();
+/// let project_id = batch.add_project(&CreateProjectArgs {
+///     name: "Client onboarding".to_string(),
+///     ..Default::default()
+/// });
+/// let section_id = batch.add_section(&CreateSectionArgs {
+///     name: "Week 1".to_string(),
+///     project_id: project_id.clone(),
+///     order: None,
+/// });
+/// batch.add_task(&CreateTaskArgs {
+///     content: "Kick-off meeting".to_string(),
+///     project_id: Some(project_id),
+///     section_id: Some(section_id),
+///     ..Default::default()
+/// });
+/// assert_eq!(batch.len(), 3);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Batch {
+    commands: Vec<BatchCommand>,
+    serialization_error: Option<String>,
+}
+
+impl Batch {
+    /// Create an empty batch
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of queued commands
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Whether no command has been queued yet
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Queued commands, in submission order
+    pub fn commands(&self) -> &[BatchCommand] {
+        &self.commands
+    }
+
+    /// First command argument that could not be serialized, which keeps the batch from being sent
+    pub fn serialization_error(&self) -> Option<&str> {
+        self.serialization_error.as_deref()
+    }
+
+    /// Queue a raw command and return its UUID
+    pub fn push(&mut self, command_type: &str, args: Value) -> String {
+        let uuid = new_id();
+        self.commands.push(BatchCommand {
+            command_type: command_type.to_string(),
+            uuid: uuid.clone(),
+            temp_id: None,
+            args,
+        });
+        uuid
+    }
+
+    /// Queue a raw object-creating command and return its temporary ID
+    pub fn push_with_temp_id(&mut self, command_type: &str, args: Value) -> String {
+        let temp_id = new_id();
+        self.commands.push(BatchCommand {
+            command_type: command_type.to_string(),
+            uuid: new_id(),
+            temp_id: Some(temp_id.clone()),
+            args,
+        });
+        temp_id
+    }
+
+    // ===== PROJECT COMMANDS =====
+
+    /// Queue a project creation and return its temporary ID
+    pub fn add_project(&mut self, args: &CreateProjectArgs) -> String {
+        let mut body = Map::new();
+        body.insert("name".to_string(), Value::from(args.name.as_str()));
+        self.insert_opt(&mut body, "color", &args.color);
+        self.insert_opt(&mut body, "parent_id", &args.parent_id);
+        self.insert_opt(&mut body, "is_favorite", &args.is_favorite);
+        self.insert_opt(&mut body, "view_style", &args.view_style);
+        self.push_with_temp_id("project_add", Value::Object(body))
+    }
+
+    /// Queue a project update and return the command UUID
+    pub fn update_project(&mut self, project_id: &str, args: &UpdateProjectArgs) -> String {
+        let mut body = id_args(project_id);
+        self.insert_opt(&mut body, "name", &args.name);
+        self.insert_opt(&mut body, "color", &args.color);
+        self.insert_opt(&mut body, "is_favorite", &args.is_favorite);
+        self.insert_opt(&mut body, "view_style", &args.view_style);
+        self.push("project_update", Value::Object(body))
+    }
+
+    /// Queue a project deletion and return the command UUID
+    pub fn delete_project(&mut self, project_id: &str) -> String {
+        self.push("project_delete", Value::Object(id_args(project_id)))
+    }
+
+    /// Queue a project archival and return the command UUID
+    pub fn archive_project(&mut self, project_id: &str) -> String {
+        self.push("project_archive", Value::Object(id_args(project_id)))
+    }
+
+    /// Queue a project unarchival and return the command UUID
+    pub fn unarchive_project(&mut self, project_id: &str) -> String {
+        self.push("project_unarchive", Value::Object(id_args(project_id)))
+    }
+
+    /// Queue an invitation to a project by email and return the command UUID
+    pub fn share_project(&mut self, project_id: &str, email: &str) -> String {
+        let mut body = Map::new();
+        body.insert("project_id".to_string(), Value::from(project_id));
+        body.insert("email".to_string(), Value::from(email));
+        self.push("share_project", Value::Object(body))
+    }
+
+    /// Queue the removal of a project collaborator and return the command UUID
+    pub fn delete_collaborator(&mut self, project_id: &str, email: &str) -> String {
+        let mut body = Map::new();
+        body.insert("project_id".to_string(), Value::from(project_id));
+        body.insert("email".to_string(), Value::from(email));
+        self.push("delete_collaborator", Value::Object(body))
+    }
+
+    // ===== TASK COMMANDS =====
+
+    /// Queue a task creation and return its temporary ID
+    pub fn add_task(&mut self, args: &CreateTaskArgs) -> String {
+        let mut body = Map::new();
+        body.insert("content".to_string(), Value::from(args.content.as_str()));
+        self.insert_opt(&mut body, "description", &args.description);
+        self.insert_opt(&mut body, "project_id", &args.project_id);
+        self.insert_opt(&mut body, "section_id", &args.section_id);
+        self.insert_opt(&mut body, "parent_id", &args.parent_id);
+        self.insert_opt(&mut body, "child_order", &args.order);
+        self.insert_opt(&mut body, "priority", &args.priority);
+        self.insert_opt(&mut body, "labels", &args.labels);
+        self.insert_opt(&mut body, "responsible_uid", &args.assignee_id);
+        self.insert_due(
+            &mut body,
+            &args.due_string,
+            &args.due_date,
+            &args.due_datetime,
+            &args.due_lang,
+        );
+        self.insert_deadline(&mut body, &args.deadline_date, &args.deadline_lang);
+        insert_duration(&mut body, &args.duration, &args.duration_unit);
+        self.push_with_temp_id("item_add", Value::Object(body))
+    }
+
+    /// Queue a task update and return the command UUID
+    pub fn update_task(&mut self, task_id: &str, args: &UpdateTaskArgs) -> String {
+        let mut body = id_args(task_id);
+        self.insert_opt(&mut body, "content", &args.content);
+        self.insert_opt(&mut body, "description", &args.description);
+        self.insert_opt(&mut body, "priority", &args.priority);
+        self.insert_opt(&mut body, "labels", &args.labels);
+        self.insert_opt(&mut body, "responsible_uid", &args.assignee_id);
+        self.insert_due(
+            &mut body,
+            &args.due_string,
+            &args.due_date,
+            &args.due_datetime,
+            &args.due_lang,
+        );
+        self.insert_deadline(&mut body, &args.deadline_date, &args.deadline_lang);
+        insert_duration(&mut body, &args.duration, &args.duration_unit);
+        self.push("item_update", Value::Object(body))
+    }
+
+    /// Queue a task completion and return the command UUID
+    pub fn complete_task(&mut self, task_id: &str) -> String {
+        self.push("item_close", Value::Object(id_args(task_id)))
+    }
+
+    /// Queue the reopening of a completed task and return the command UUID
+    pub fn reopen_task(&mut self, task_id: &str) -> String {
+        self.push("item_uncomplete", Value::Object(id_args(task_id)))
+    }
+
+    /// Queue a task deletion and return the command UUID
+    pub fn delete_task(&mut self, task_id: &str) -> String {
+        self.push("item_delete", Value::Object(id_args(task_id)))
+    }
+
+    // ===== LABEL COMMANDS =====
+
+    /// Queue a label creation and return its temporary ID
+    pub fn add_label(&mut self, args: &CreateLabelArgs) -> String {
+        let mut body = Map::new();
+        body.insert("name".to_string(), Value::from(args.name.as_str()));
+        self.insert_opt(&mut body, "color", &args.color);
+        self.insert_opt(&mut body, "item_order", &args.order);
+        self.insert_opt(&mut body, "is_favorite", &args.is_favorite);
+        self.push_with_temp_id("label_add", Value::Object(body))
+    }
+
+    /// Queue a label update and return the command UUID
+    pub fn update_label(&mut self, label_id: &str, args: &UpdateLabelArgs) -> String {
+        let mut body = id_args(label_id);
+        self.insert_opt(&mut body, "name", &args.name);
+        self.insert_opt(&mut body, "color", &args.color);
+        self.insert_opt(&mut body, "item_order", &args.order);
+        self.insert_opt(&mut body, "is_favorite", &args.is_favorite);
+        self.push("label_update", Value::Object(body))
+    }
+
+    /// Queue a label deletion and return the command UUID
+    pub fn delete_label(&mut self, label_id: &str) -> String {
+        self.push("label_delete", Value::Object(id_args(label_id)))
+    }
+
+    // ===== SECTION COMMANDS =====
+
+    /// Queue a section creation and return its temporary ID
+    pub fn add_section(&mut self, args: &CreateSectionArgs) -> String {
+        let mut body = Map::new();
+        body.insert("name".to_string(), Value::from(args.name.as_str()));
+        body.insert("project_id".to_string(), Value::from(args.project_id.as_str()));
+        self.insert_opt(&mut body, "section_order", &args.order);
+        self.push_with_temp_id("section_add", Value::Object(body))
+    }
+
+    /// Queue a section update and return the command UUID
+    pub fn update_section(&mut self, section_id: &str, args: &UpdateSectionArgs) -> String {
+        let mut body = id_args(section_id);
+        body.insert("name".to_string(), Value::from(args.name.as_str()));
+        self.push("section_update", Value::Object(body))
+    }
+
+    /// Queue a section deletion and return the command UUID
+    pub fn delete_section(&mut self, section_id: &str) -> String {
+        self.push("section_delete", Value::Object(id_args(section_id)))
+    }
+
+    /// Queue a section archival and return the command UUID
+    pub fn archive_section(&mut self, section_id: &str) -> String {
+        self.push("section_archive", Value::Object(id_args(section_id)))
+    }
+
+    /// Queue a section unarchival and return the command UUID
+    pub fn unarchive_section(&mut self, section_id: &str) -> String {
+        self.push("section_unarchive", Value::Object(id_args(section_id)))
+    }
+
+    // ===== COMMENT COMMANDS =====
+
+    /// Queue a comment creation and return its temporary ID
+    ///
+    /// Comments on a project (no `task_id`) are sent as `project_note_add`.
+    pub fn add_comment(&mut self, args: &CreateCommentArgs) -> String {
+        let mut body = Map::new();
+        body.insert("content".to_string(), Value::from(args.content.as_str()));
+        self.insert_opt(&mut body, "item_id", &args.task_id);
+        self.insert_opt(&mut body, "project_id", &args.project_id);
+        self.insert_opt(&mut body, "file_attachment", &args.attachment);
+        let command_type = if args.task_id.is_some() {
+            "note_add"
+        } else {
+            "project_note_add"
+        };
+        self.push_with_temp_id(command_type, Value::Object(body))
+    }
+
+    /// Queue a comment update and return the command UUID
+    pub fn update_comment(&mut self, comment_id: &str, args: &UpdateCommentArgs) -> String {
+        let mut body = id_args(comment_id);
+        body.insert("content".to_string(), Value::from(args.content.as_str()));
+        self.push("note_update", Value::Object(body))
+    }
+
+    /// Queue a comment deletion and return the command UUID
+    pub fn delete_comment(&mut self, comment_id: &str) -> String {
+        self.push("note_delete", Value::Object(id_args(comment_id)))
+    }
+
+    // ===== FILTER COMMANDS =====
+
+    /// Queue a saved filter creation and return its temporary ID
+    pub fn add_filter(&mut self, args: &CreateSavedFilterArgs) -> String {
+        let mut body = Map::new();
+        body.insert("name".to_string(), Value::from(args.name.as_str()));
+        body.insert("query".to_string(), Value::from(args.query.as_str()));
+        self.insert_opt(&mut body, "color", &args.color);
+        self.insert_opt(&mut body, "item_order", &args.order);
+        self.insert_opt(&mut body, "is_favorite", &args.is_favorite);
+        self.push_with_temp_id("filter_add", Value::Object(body))
+    }
+
+    /// Queue a saved filter update and return the command UUID
+    pub fn update_filter(&mut self, filter_id: &str, args: &UpdateSavedFilterArgs) -> String {
+        let mut body = id_args(filter_id);
+        self.insert_opt(&mut body, "name", &args.name);
+        self.insert_opt(&mut body, "query", &args.query);
+        self.insert_opt(&mut body, "color", &args.color);
+        self.insert_opt(&mut body, "item_order", &args.order);
+        self.insert_opt(&mut body, "is_favorite", &args.is_favorite);
+        self.push("filter_update", Value::Object(body))
+    }
+
+    /// Queue a saved filter deletion and return the command UUID
+    pub fn delete_filter(&mut self, filter_id: &str) -> String {
+        self.push("filter_delete", Value::Object(id_args(filter_id)))
+    }
+
+    /// Queue a reordering of saved filters and return the command UUID
+    ///
+    /// Filters are given their position in `filter_ids`, starting at 1.
+    pub fn reorder_filters(&mut self, filter_ids: &[&str]) -> String {
+        let orders: Map<String, Value> = filter_ids
+            .iter()
+            .zip(1..)
+            .map(|(id, order)| (id.to_string(), Value::from(order)))
+            .collect();
+        let mut body = Map::new();
+        body.insert("id_order_mapping".to_string(), Value::Object(orders));
+        self.push("filter_update_orders", Value::Object(body))
+    }
+
+    // ===== REMINDER COMMANDS =====
+
+    /// Queue a reminder creation and return its temporary ID
+    pub fn add_reminder(&mut self, args: &CreateReminderArgs) -> String {
+        let mut body = Map::new();
+        body.insert("item_id".to_string(), Value::from(args.task_id.as_str()));
+        self.insert_opt(&mut body, "notify_uid", &args.notify_uid);
+        self.insert_trigger(&mut body, &args.trigger);
+        self.push_with_temp_id("reminder_add", Value::Object(body))
+    }
+
+    /// Queue a reminder update and return the command UUID
+    pub fn update_reminder(&mut self, reminder_id: &str, args: &UpdateReminderArgs) -> String {
+        let mut body = id_args(reminder_id);
+        self.insert_opt(&mut body, "notify_uid", &args.notify_uid);
+        if let Some(trigger) = &args.trigger {
+            self.insert_trigger(&mut body, trigger);
+        }
+        self.push("reminder_update", Value::Object(body))
+    }
+
+    /// Queue a reminder deletion and return the command UUID
+    pub fn delete_reminder(&mut self, reminder_id: &str) -> String {
+        self.push("reminder_delete", Value::Object(id_args(reminder_id)))
+    }
+}
+
+/// Outcome of a single batch command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandStatus {
+    Ok,
+    Error { code: i64, message: String },
+}
+
+/// Per-command report returned by [`crate::TodoistWrapper::execute_batch`]
+#[derive(Debug, Clone)]
+pub struct CommandResult {
+    pub uuid: String,
+    pub command_type: String,
+    pub temp_id: Option<String>,
+    /// Real ID of the object created by this command, if any
+    pub id: Option<String>,
+    pub status: CommandStatus,
+}
+
+impl CommandResult {
+    /// Whether the command was applied
+    pub fn is_ok(&self) -> bool {
+        self.status == CommandStatus::Ok
+    }
+}
+
+/// Result of executing a [`Batch`]
+#[derive(Debug, Clone, Default)]
+pub struct BatchResult {
+    /// One entry per command, in submission order
+    pub results: Vec<CommandResult>,
+    /// Temporary ID to real ID mapping for every created object
+    pub temp_id_mapping: HashMap<String, String>,
+}
+
+impl BatchResult {
+    /// Whether every command was applied
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(CommandResult::is_ok)
+    }
+
+    /// Commands that were rejected by the server
+    pub fn errors(&self) -> Vec<&CommandResult> {
+        self.results.iter().filter(|r| !r.is_ok()).collect()
+    }
+
+    /// Resolve a temporary ID to the real ID assigned by Todoist
+    pub fn real_id(&self, temp_id: &str) -> Option<&str> {
+        self.temp_id_mapping.get(temp_id).map(String::as_str)
+    }
+
+    pub(crate) fn record_chunk(&mut self, commands: &[BatchCommand], response: SyncCommandResponse) {
+        self.temp_id_mapping.extend(response.temp_id_mapping);
+        for command in commands {
+            let status = match response.sync_status.get(&command.uuid) {
+                Some(Value::String(s)) if s == "ok" => CommandStatus::Ok,
+                Some(Value::Object(error)) => CommandStatus::Error {
+                    code: error.get("error_code").and_then(Value::as_i64).unwrap_or_default(),
+                    message: error
+                        .get("error")
+                        .and_then(Value::as_str)
+                        .unwrap_or("unknown error")
+                        .to_string(),
+                },
+                Some(other) => CommandStatus::Error {
+                    code: 0,
+                    message: format!("unexpected sync status: {other}"),
+                },
+                None => CommandStatus::Error {
+                    code: 0,
+                    message: "no status returned for command".to_string(),
+                },
+            };
+            let id = command
+                .temp_id
+                .as_ref()
+                .and_then(|temp_id| self.temp_id_mapping.get(temp_id).cloned());
+            self.results.push(CommandResult {
+                uuid: command.uuid.clone(),
+                command_type: command.command_type.clone(),
+                temp_id: command.temp_id.clone(),
+                id,
+                status,
+            });
+        }
+    }
+
+    /// Copy commands, replacing temporary IDs resolved by earlier chunks with real IDs
+    pub(crate) fn resolve_temp_ids(&self, commands: &[BatchCommand]) -> Vec<BatchCommand> {
+        commands
+            .iter()
+            .map(|command| {
+                let mut command = command.clone();
+                replace_ids(&mut command.args, &self.temp_id_mapping);
+                command
+            })
+            .collect()
+    }
+}
+
+/// Raw Sync API response to a `commands` request
+#[derive(Debug, Deserialize)]
+pub(crate) struct SyncCommandResponse {
+    #[serde(default)]
+    pub sync_status: HashMap<String, Value>,
+    #[serde(default)]
+    pub temp_id_mapping: HashMap<String, String>,
+}
+
+fn new_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+fn id_args(id: &str) -> Map<String, Value> {
+    let mut body = Map::new();
+    body.insert("id".to_string(), Value::from(id));
+    body
+}
+
+impl Batch {
+    /// Insert a field when it is set
+    ///
+    /// A value that fails to serialize is left out and the error is kept for `execute_batch`.
+    fn insert_opt<T: Serialize>(&mut self, body: &mut Map<String, Value>, key: &str, value: &Option<T>) {
+        if let Some(value) = value {
+            match serde_json::to_value(value) {
+                Ok(value) => {
+                    body.insert(key.to_string(), value);
+                }
+                Err(err) => {
+                    self.serialization_error
+                        .get_or_insert_with(|| format!("argument `{key}` could not be serialized: {err}"));
+                }
+            }
+        }
+    }
+
+    fn insert_due(
+        &mut self,
+        body: &mut Map<String, Value>,
+        string: &Option<String>,
+        date: &Option<String>,
+        datetime: &Option<String>,
+        lang: &Option<String>,
+    ) {
+        let mut due = Map::new();
+        self.insert_opt(&mut due, "string", string);
+        // The Sync API takes both full-day dates and datetimes in the `date` field
+        self.insert_opt(&mut due, "date", &datetime.clone().or_else(|| date.clone()));
+        if due.is_empty() {
+            return;
+        }
+        self.insert_opt(&mut due, "lang", lang);
+        body.insert("due".to_string(), Value::Object(due));
+    }
+
+    fn insert_trigger(&mut self, body: &mut Map<String, Value>, trigger: &ReminderTrigger) {
+        match trigger {
+            ReminderTrigger::Relative { minute_offset } => {
+                body.insert("type".to_string(), Value::from("relative"));
+                body.insert("minute_offset".to_string(), Value::from(*minute_offset));
+            }
+            ReminderTrigger::Absolute { datetime } => {
+                let mut due = Map::new();
+                due.insert("date".to_string(), Value::from(datetime.as_str()));
+                body.insert("type".to_string(), Value::from("absolute"));
+                body.insert("due".to_string(), Value::Object(due));
+            }
+            ReminderTrigger::Location {
+                name,
+                latitude,
+                longitude,
+                trigger,
+                radius,
+            } => {
+                body.insert("type".to_string(), Value::from("location"));
+                body.insert("name".to_string(), Value::from(name.as_str()));
+                body.insert("loc_lat".to_string(), Value::from(latitude.as_str()));
+                body.insert("loc_long".to_string(), Value::from(longitude.as_str()));
+                self.insert_opt(body, "loc_trigger", &Some(trigger));
+                body.insert("radius".to_string(), Value::from(*radius));
+            }
+        }
+    }
+
+    fn insert_deadline(&mut self, body: &mut Map<String, Value>, date: &Option<String>, lang: &Option<String>) {
+        if date.is_none() {
+            return;
+        }
+        let mut deadline = Map::new();
+        self.insert_opt(&mut deadline, "date", date);
+        self.insert_opt(&mut deadline, "lang", lang);
+        body.insert("deadline".to_string(), Value::Object(deadline));
+    }
+}
+
+fn insert_duration(body: &mut Map<String, Value>, amount: &Option<i32>, unit: &Option<String>) {
+    if let Some(amount) = amount {
+        let mut duration = Map::new();
+        duration.insert("amount".to_string(), Value::from(*amount));
+        duration.insert(
+            "unit".to_string(),
+            Value::from(unit.clone().unwrap_or_else(|| "minute".to_string())),
+        );
+        body.insert("duration".to_string(), Value::Object(duration));
+    }
+}
+
+fn replace_ids(value: &mut Value, mapping: &HashMap<String, String>) {
+    match value {
+        Value::String(s) => {
+            if let Some(real_id) = mapping.get(s.as_str()) {
+                *s = real_id.clone();
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(|v| replace_ids(v, mapping)),
+        Value::Object(map) => map.values_mut().for_each(|v| replace_ids(v, mapping)),
+        _ => {}
+    }
+}
+
+/// Split a batch into Sync API sized chunks
+pub(crate) fn chunks(batch: &Batch) -> impl Iterator<Item = &[BatchCommand]> {
+    batch.commands.chunks(SYNC_COMMAND_LIMIT)
+}
@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Future returned by [`CredentialProvider`] methods
+pub type CredentialFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Source of the API token, consulted before each request
+///
+/// Implementations must not print tokens in their `Debug` output.
+pub trait CredentialProvider: fmt::Debug + Send + Sync {
+    /// Token to send with the next request
+    fn token(&self) -> CredentialFuture<'_, String>;
+
+    /// Called when the API rejected `rejected_token` with a 401
+    ///
+    /// Returning a different token retries the request once with it. The default gives up.
+    fn on_unauthorized<'a>(&'a self, rejected_token: &'a str) -> CredentialFuture<'a, Option<String>> {
+        let _ = rejected_token;
+        Box::pin(async { Ok(None) })
+    }
+}
+
+/// A fixed token, as used by [`crate::TodoistWrapper::new`]
+#[derive(Clone)]
+pub struct StaticToken {
+    token: String,
+}
+
+impl StaticToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self { token: token.into() }
+    }
+}
+
+impl fmt::Debug for StaticToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticToken").field("token", &"[redacted]").finish()
+    }
+}
+
+impl CredentialProvider for StaticToken {
+    fn token(&self) -> CredentialFuture<'_, String> {
+        Box::pin(async { Ok(self.token.clone()) })
+    }
+}
+
+/// A token read from an environment variable on each request
+#[derive(Debug, Clone)]
+pub struct EnvToken {
+    variable: String,
+}
+
+impl EnvToken {
+    pub fn new(variable: impl Into<String>) -> Self {
+        Self {
+            variable: variable.into(),
+        }
+    }
+}
+
+impl CredentialProvider for EnvToken {
+    fn token(&self) -> CredentialFuture<'_, String> {
+        Box::pin(async {
+            std::env::var(&self.variable).with_context(|| format!("environment variable {} is not set", self.variable))
+        })
+    }
+
+    fn on_unauthorized<'a>(&'a self, rejected_token: &'a str) -> CredentialFuture<'a, Option<String>> {
+        Box::pin(async move { Ok(self.token().await.ok().filter(|token| token != rejected_token)) })
+    }
+}
+
+/// A token read from a file on each request, such as a mounted secret
+///
+/// Surrounding whitespace is ignored.
+#[derive(Debug, Clone)]
+pub struct FileToken {
+    path: PathBuf,
+}
+
+impl FileToken {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CredentialProvider for FileToken {
+    fn token(&self) -> CredentialFuture<'_, String> {
+        Box::pin(async {
+            let contents = tokio::fs::read_to_string(&self.path)
+                .await
+                .with_context(|| format!("failed to read token from {}", self.path.display()))?;
+            Ok(contents.trim().to_string())
+        })
+    }
+
+    fn on_unauthorized<'a>(&'a self, rejected_token: &'a str) -> CredentialFuture<'a, Option<String>> {
+        Box::pin(async move { Ok(self.token().await.ok().filter(|token| token != rejected_token)) })
+    }
+}
+
+type Fetch = Arc<dyn Fn() -> CredentialFuture<'static, String> + Send + Sync>;
+
+/// A token fetched from a slower source, such as a secret store, and cached for a while
+///
+/// The token is fetched again once it is older than the time to live, or right away when the
+/// API rejects it.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use todoist_api::credentials::CachedToken;
+/// use todoist_api::TodoistWrapper;
+///
+/// let credentials = CachedToken::new(Duration::from_secs(300), || async {
+///     // Ask the secret store here
+///     Ok("token".to_string())
+/// });
+/// let todoist = TodoistWrapper::with_credentials(credentials);
+/// ```
+#[derive(Clone)]
+pub struct CachedToken {
+    fetch: Fetch,
+    ttl: Duration,
+    cached: Arc<Mutex<Option<(String, Instant)>>>,
+}
+
+impl CachedToken {
+    pub fn new<F, Fut>(ttl: Duration, fetch: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        Self {
+            fetch: Arc::new(move || Box::pin(fetch()) as CredentialFuture<'static, String>),
+            ttl,
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Drop the cached token so the next request fetches a new one
+    pub async fn invalidate(&self) {
+        *self.cached.lock().await = None;
+    }
+}
+
+impl fmt::Debug for CachedToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedToken")
+            .field("ttl", &self.ttl)
+            .field("token", &"[redacted]")
+            .finish_non_exhaustive()
+    }
+}
+
+impl CredentialProvider for CachedToken {
+    fn token(&self) -> CredentialFuture<'_, String> {
+        Box::pin(async {
+            // Holding the lock while fetching keeps concurrent requests from fetching in parallel
+            let mut cached = self.cached.lock().await;
+            if let Some((token, fetched_at)) = cached.as_ref() {
+                if fetched_at.elapsed() < self.ttl {
+                    return Ok(token.clone());
+                }
+            }
+            let token = (self.fetch)().await?;
+            *cached = Some((token.clone(), Instant::now()));
+            Ok(token)
+        })
+    }
+
+    fn on_unauthorized<'a>(&'a self, rejected_token: &'a str) -> CredentialFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let mut cached = self.cached.lock().await;
+            // Another request may already have replaced the rejected token
+            if let Some((token, _)) = cached.as_ref() {
+                if token != rejected_token {
+                    return Ok(Some(token.clone()));
+                }
+            }
+            let token = (self.fetch)().await?;
+            *cached = Some((token.clone(), Instant::now()));
+            Ok(Some(token).filter(|token| token != rejected_token))
+        })
+    }
+}
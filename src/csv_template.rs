@@ -0,0 +1,255 @@
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+
+use crate::batch::{Batch, BatchResult};
+use crate::models::*;
+use crate::wrapper::TodoistWrapper;
+
+/// Columns of a Todoist CSV template, in the order Todoist writes them
+pub const CSV_COLUMNS: [&str; 14] = [
+    "TYPE",
+    "CONTENT",
+    "DESCRIPTION",
+    "PRIORITY",
+    "INDENT",
+    "AUTHOR",
+    "RESPONSIBLE",
+    "DATE",
+    "DATE_LANG",
+    "TIMEZONE",
+    "DURATION",
+    "DURATION_UNIT",
+    "DEADLINE",
+    "DEADLINE_LANG",
+];
+
+/// Deepest subtask level Todoist accepts in a template
+const MAX_INDENT: usize = 5;
+
+impl TodoistWrapper {
+    /// Export a project as a Todoist CSV template
+    pub async fn export_project_csv(&self, project_id: &str) -> Result<String> {
+        let sections_args = SectionFilterArgs {
+            project_id: Some(project_id.to_string()),
+            limit: None,
+            cursor: None,
+        };
+        let (project, sections, tasks) = futures::try_join!(
+            self.get_project(project_id),
+            self.get_sections_filtered(&sections_args),
+            self.get_tasks_for_project(project_id)
+        )?;
+        export_csv(&project, &sections, &tasks)
+    }
+
+    /// Import a Todoist CSV template into a project
+    ///
+    /// The file is parsed before anything is sent; commands the server rejects are reported in the
+    /// returned [`BatchResult`].
+    pub async fn import_project_csv(&self, project_id: &str, text: &str) -> Result<BatchResult> {
+        let batch = import_csv(text, project_id)?;
+        self.execute_batch(&batch).await
+    }
+}
+
+/// Write the sections and tasks of a project as a Todoist CSV template
+///
+/// Tasks outside any section come first, then each section followed by its tasks. Subtasks follow
+/// their parent with a deeper `INDENT`. Sections and tasks of other projects are ignored.
+pub fn export_csv(project: &Project, sections: &[Section], tasks: &[Task]) -> Result<String> {
+    let tasks: Vec<&Task> = tasks.iter().filter(|task| task.project_id == project.id).collect();
+    let mut sections: Vec<&Section> = sections
+        .iter()
+        .filter(|section| section.project_id == project.id)
+        .collect();
+    sections.sort_by_key(|section| section.order);
+
+    let mut children: HashMap<Option<&str>, Vec<&Task>> = HashMap::new();
+    for task in &tasks {
+        // Subtasks of a task outside the project are exported at the top level
+        let parent = task
+            .parent_id
+            .as_deref()
+            .filter(|parent_id| tasks.iter().any(|task| task.id == *parent_id));
+        children.entry(parent).or_default().push(task);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|task| task.order);
+    }
+    let roots = children.get(&None).cloned().unwrap_or_default();
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(CSV_COLUMNS)?;
+    let in_known_section = |task: &&Task| {
+        task.section_id
+            .as_ref()
+            .is_some_and(|section_id| sections.iter().any(|section| &section.id == section_id))
+    };
+    for task in roots.iter().filter(|task| !in_known_section(task)) {
+        write_task(&mut writer, task, 1, &children)?;
+    }
+    for section in &sections {
+        let mut row = vec![String::new(); CSV_COLUMNS.len()];
+        row[0] = "section".to_string();
+        row[1] = section.name.clone();
+        writer.write_record(&row)?;
+        for task in roots
+            .iter()
+            .filter(|task| task.section_id.as_deref() == Some(section.id.as_str()))
+        {
+            write_task(&mut writer, task, 1, &children)?;
+        }
+    }
+
+    let bytes = writer.into_inner().context("failed to write CSV")?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn write_task(
+    writer: &mut csv::Writer<Vec<u8>>,
+    task: &Task,
+    indent: usize,
+    children: &HashMap<Option<&str>, Vec<&Task>>,
+) -> Result<()> {
+    let due = task.due.as_ref();
+    writer.write_record([
+        "task".to_string(),
+        task.content.clone(),
+        task.description.clone(),
+        // Templates use the priorities shown in the app, where 1 is the most urgent
+        (5 - task.priority.clamp(1, 4)).to_string(),
+        indent.min(MAX_INDENT).to_string(),
+        String::new(),
+        String::new(),
+        due.map(|due| due.string.clone()).unwrap_or_default(),
+        String::new(),
+        due.and_then(|due| due.timezone.clone()).unwrap_or_default(),
+        task.duration
+            .as_ref()
+            .map(|duration| duration.amount.to_string())
+            .unwrap_or_default(),
+        task.duration
+            .as_ref()
+            .map(|duration| duration.unit.clone())
+            .unwrap_or_default(),
+        task.deadline
+            .as_ref()
+            .map(|deadline| deadline.date.clone())
+            .unwrap_or_default(),
+        String::new(),
+    ])?;
+    for child in children.get(&Some(task.id.as_str())).into_iter().flatten() {
+        write_task(writer, child, indent + 1, children)?;
+    }
+    Ok(())
+}
+
+/// Parse a Todoist CSV template into commands recreating it in a project
+///
+/// Sections, tasks and notes are queued in file order. Tasks are placed in the section above them
+/// and under the closest preceding task with a smaller `INDENT`; notes become comments of the task
+/// above them, or of the project when no task precedes them. `meta` rows are ignored.
+pub fn import_csv(text: &str, project_id: &str) -> Result<Batch> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(text.as_bytes());
+    let columns: HashMap<String, usize> = reader
+        .headers()?
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (name.trim().to_ascii_uppercase(), index))
+        .collect();
+    for required in ["TYPE", "CONTENT"] {
+        if !columns.contains_key(required) {
+            bail!("CSV template has no {required} column");
+        }
+    }
+
+    let mut batch = Batch::new();
+    let mut section_id: Option<String> = None;
+    // Temporary ID of the last task at each indent level
+    let mut parents: Vec<String> = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |position| position.line());
+        let field = |column: &str| {
+            columns
+                .get(column)
+                .and_then(|&index| record.get(index))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+        let Some(row_type) = field("TYPE") else {
+            continue;
+        };
+        let content = field("CONTENT").unwrap_or_default().to_string();
+
+        match row_type.to_ascii_lowercase().as_str() {
+            "section" => {
+                section_id = Some(batch.add_section(&CreateSectionArgs {
+                    name: content,
+                    project_id: project_id.to_string(),
+                    order: None,
+                }));
+                parents.clear();
+            }
+            "task" => {
+                let indent = match field("INDENT") {
+                    Some(indent) => indent
+                        .parse::<usize>()
+                        .with_context(|| format!("line {line}: invalid INDENT {indent:?}"))?,
+                    None => 1,
+                };
+                if indent == 0 || indent > MAX_INDENT {
+                    bail!("line {line}: INDENT must be between 1 and {MAX_INDENT}");
+                }
+                if indent > parents.len() + 1 {
+                    bail!(
+                        "line {line}: INDENT {indent} has no parent task at level {}",
+                        indent - 1
+                    );
+                }
+                let priority = field("PRIORITY")
+                    .map(|priority| match priority.parse::<i32>() {
+                        Ok(priority @ 1..=4) => Ok(5 - priority),
+                        _ => Err(anyhow!("line {line}: invalid PRIORITY {priority:?}")),
+                    })
+                    .transpose()?;
+                let duration = field("DURATION")
+                    .map(|duration| {
+                        duration
+                            .parse::<i32>()
+                            .with_context(|| format!("line {line}: invalid DURATION {duration:?}"))
+                    })
+                    .transpose()?;
+                let args = CreateTaskArgs {
+                    content,
+                    description: field("DESCRIPTION").map(str::to_string),
+                    project_id: Some(project_id.to_string()),
+                    section_id: section_id.clone(),
+                    parent_id: indent.checked_sub(2).map(|level| parents[level].clone()),
+                    priority,
+                    due_string: field("DATE").map(str::to_string),
+                    due_lang: field("DATE_LANG").map(str::to_string),
+                    deadline_date: field("DEADLINE").map(str::to_string),
+                    deadline_lang: field("DEADLINE_LANG").map(str::to_string),
+                    duration,
+                    duration_unit: duration.and(field("DURATION_UNIT").map(str::to_string)),
+                    ..Default::default()
+                };
+                parents.truncate(indent - 1);
+                parents.push(batch.add_task(&args));
+            }
+            "note" => {
+                let task_id = parents.last().cloned();
+                batch.add_comment(&CreateCommentArgs {
+                    content,
+                    project_id: task_id.is_none().then(|| project_id.to_string()),
+                    task_id,
+                    attachment: None,
+                });
+            }
+            "meta" => {}
+            other => bail!("line {line}: unknown TYPE {other:?}"),
+        }
+    }
+    Ok(batch)
+}
@@ -0,0 +1,888 @@
+use chrono::{Duration as ChronoDuration, NaiveDate};
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::models::*;
+
+/// Error returned when a filter query cannot be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterParseError {
+    pub message: String,
+    /// Byte offset in the query where the error was detected
+    pub position: usize,
+}
+
+impl FilterParseError {
+    fn new(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+impl fmt::Display for FilterParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for FilterParseError {}
+
+/// A date used in a filter term
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterDate {
+    Today,
+    Tomorrow,
+    Yesterday,
+    Date(NaiveDate),
+}
+
+impl FilterDate {
+    fn resolve(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            FilterDate::Today => today,
+            FilterDate::Tomorrow => today + ChronoDuration::days(1),
+            FilterDate::Yesterday => today - ChronoDuration::days(1),
+            FilterDate::Date(date) => *date,
+        }
+    }
+}
+
+impl From<NaiveDate> for FilterDate {
+    fn from(date: NaiveDate) -> Self {
+        FilterDate::Date(date)
+    }
+}
+
+/// Who a task is assigned to, in an `assigned to:` term
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Assignee {
+    Me,
+    Others,
+    /// A collaborator name or email, possibly with `*` wildcards
+    Name(String),
+}
+
+/// A single filter condition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterTerm {
+    /// `today`, `tomorrow`, `yesterday` or a `YYYY-MM-DD` date
+    DueOn(FilterDate),
+    /// `overdue` or `od`
+    Overdue,
+    /// `no date`
+    NoDate,
+    /// `recurring`
+    Recurring,
+    /// `no labels`
+    NoLabels,
+    /// `p1` to `p4`, where `p1` is the highest priority
+    Priority(u8),
+    /// `#Project`, or `##Project` to include sub-projects
+    Project { name: String, include_subprojects: bool },
+    /// `/Section`
+    Section(String),
+    /// `@label`
+    Label(String),
+    /// `assigned`
+    Assigned,
+    /// `assigned to: name`
+    AssignedTo(Assignee),
+    /// `due before: date`
+    DueBefore(FilterDate),
+    /// `due after: date`
+    DueAfter(FilterDate),
+    /// `search: text`
+    Search(String),
+}
+
+/// A parsed filter expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterExpr {
+    Term(FilterTerm),
+    Not(Box<FilterExpr>),
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+}
+
+/// Data needed to evaluate filters that refer to other resources or to the current date
+#[derive(Debug, Clone)]
+pub struct FilterContext {
+    pub today: NaiveDate,
+    pub projects: Vec<Project>,
+    pub sections: Vec<Section>,
+    pub users: Vec<User>,
+    /// ID of the user running the query, used by `assigned to: me` and `assigned to: others`
+    pub current_user_id: Option<String>,
+}
+
+impl FilterContext {
+    /// Create a context with no projects, sections or users
+    #[must_use]
+    pub fn new(today: NaiveDate) -> Self {
+        Self {
+            today,
+            projects: Vec::new(),
+            sections: Vec::new(),
+            users: Vec::new(),
+            current_user_id: None,
+        }
+    }
+}
+
+/// A Todoist filter query, possibly made of several `,`-separated queries
+///
+/// Queries are evaluated locally with the same semantics as the server, so cached or offline
+/// tasks can be filtered without a round-trip:
+///
+/// ```rust
+/// use chrono::NaiveDate;
+/// use todoist_api::filter::{FilterContext, FilterQuery};
+///
+/// let query = FilterQuery::parse("(today | overdue) & @work & !p4").unwrap();
+/// let context = FilterContext::new(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap());
+/// # let tasks: Vec<todoist_api::Task> = Vec::new();
+/// let matching = query.filter(&tasks, &context);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterQuery {
+    pub queries: Vec<FilterExpr>,
+}
+
+impl FilterQuery {
+    /// Parse a filter query
+    pub fn parse(input: &str) -> Result<Self, FilterParseError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            input_len: input.len(),
+        };
+        let mut queries = vec![parser.parse_or()?];
+        while parser.eat(&TokenKind::Comma) {
+            queries.push(parser.parse_or()?);
+        }
+        if let Some(token) = parser.peek() {
+            let message = match token.kind {
+                TokenKind::RParen => "unexpected ')' without matching '('".to_string(),
+                _ => format!("unexpected {}", token.kind),
+            };
+            return Err(FilterParseError::new(message, token.position));
+        }
+        Ok(Self { queries })
+    }
+
+    /// Whether a task matches any of the queries
+    pub fn matches(&self, task: &Task, context: &FilterContext) -> bool {
+        self.queries.iter().any(|q| q.matches(task, context))
+    }
+
+    /// Tasks matching any of the queries, in their original order
+    pub fn filter<'t>(&self, tasks: &'t [Task], context: &FilterContext) -> Vec<&'t Task> {
+        tasks.iter().filter(|t| self.matches(t, context)).collect()
+    }
+
+    /// Tasks matching each query, one list per `,`-separated query
+    pub fn evaluate<'t>(&self, tasks: &'t [Task], context: &FilterContext) -> Vec<Vec<&'t Task>> {
+        self.queries
+            .iter()
+            .map(|q| tasks.iter().filter(|t| q.matches(t, context)).collect())
+            .collect()
+    }
+}
+
+impl FilterExpr {
+    /// Whether a task matches this expression
+    pub fn matches(&self, task: &Task, context: &FilterContext) -> bool {
+        match self {
+            FilterExpr::Term(term) => term.matches(task, context),
+            FilterExpr::Not(expr) => !expr.matches(task, context),
+            FilterExpr::And(left, right) => left.matches(task, context) && right.matches(task, context),
+            FilterExpr::Or(left, right) => left.matches(task, context) || right.matches(task, context),
+        }
+    }
+}
+
+impl FilterTerm {
+    /// Whether a task matches this term
+    pub fn matches(&self, task: &Task, context: &FilterContext) -> bool {
+        let due_date = task
+            .due
+            .as_ref()
+            .and_then(|due| NaiveDate::parse_from_str(&due.date, "%Y-%m-%d").ok());
+
+        match self {
+            FilterTerm::DueOn(date) => due_date == Some(date.resolve(context.today)),
+            FilterTerm::Overdue => due_date.is_some_and(|d| d < context.today),
+            FilterTerm::NoDate => task.due.is_none(),
+            FilterTerm::Recurring => task.due.as_ref().is_some_and(|due| due.is_recurring),
+            FilterTerm::NoLabels => task.labels.is_empty(),
+            // The API counts priorities the other way around: p1 is priority 4
+            FilterTerm::Priority(p) => task.priority == 5 - i32::from(*p),
+            FilterTerm::Project {
+                name,
+                include_subprojects,
+            } => project_ids(name, *include_subprojects, context).contains(task.project_id.as_str()),
+            FilterTerm::Section(name) => task.section_id.as_ref().is_some_and(|section_id| {
+                context
+                    .sections
+                    .iter()
+                    .any(|s| &s.id == section_id && wildcard_match(name, &s.name))
+            }),
+            FilterTerm::Label(name) => task.labels.iter().any(|label| wildcard_match(name, label)),
+            FilterTerm::Assigned => task.assignee_id.is_some(),
+            FilterTerm::AssignedTo(assignee) => match (&task.assignee_id, assignee) {
+                (None, _) => false,
+                (Some(id), Assignee::Me) => context.current_user_id.as_ref() == Some(id),
+                (Some(id), Assignee::Others) => context.current_user_id.as_ref() != Some(id),
+                (Some(id), Assignee::Name(name)) => context
+                    .users
+                    .iter()
+                    .any(|u| &u.id == id && (wildcard_match(name, &u.name) || wildcard_match(name, &u.email))),
+            },
+            FilterTerm::DueBefore(date) => due_date.is_some_and(|d| d < date.resolve(context.today)),
+            FilterTerm::DueAfter(date) => due_date.is_some_and(|d| d > date.resolve(context.today)),
+            FilterTerm::Search(text) => {
+                let pattern = if text.contains('*') {
+                    text.clone()
+                } else {
+                    format!("*{text}*")
+                };
+                wildcard_match(&pattern, &task.content)
+            }
+        }
+    }
+}
+
+/// IDs of the projects matching a name, with their descendants if requested
+fn project_ids<'c>(name: &str, include_subprojects: bool, context: &'c FilterContext) -> HashSet<&'c str> {
+    let mut ids: HashSet<&str> = context
+        .projects
+        .iter()
+        .filter(|p| wildcard_match(name, &p.name))
+        .map(|p| p.id.as_str())
+        .collect();
+    if include_subprojects {
+        loop {
+            let children: Vec<&str> = context
+                .projects
+                .iter()
+                .filter(|p| !ids.contains(p.id.as_str()))
+                .filter(|p| p.parent_id.as_deref().is_some_and(|parent| ids.contains(parent)))
+                .map(|p| p.id.as_str())
+                .collect();
+            if children.is_empty() {
+                break;
+            }
+            ids.extend(children);
+        }
+    }
+    ids
+}
+
+/// Case-insensitive match where `*` stands for any sequence of characters
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let value: Vec<char> = value.to_lowercase().chars().collect();
+
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while v < value.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == value[v] {
+            p += 1;
+            v += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            v = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// ===== BUILDER =====
+
+/// Task priority as shown in the Todoist apps, where `P1` is the highest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    P1,
+    P2,
+    P3,
+    P4,
+}
+
+/// Typed builder for filter queries
+///
+/// Filters render to the Todoist filter syntax with [`ToString`], escaping names as needed:
+///
+/// ```rust
+/// use todoist_api::filter::{Filter, Priority};
+///
+/// let filter = Filter::today()
+///     .or(Filter::overdue())
+///     .and(Filter::label("work"))
+///     .not(Filter::project("Q3 & Q4"));
+/// assert_eq!(filter.to_string(), r"(today | overdue) & @work & !#Q3 \& Q4");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    expr: FilterExpr,
+}
+
+impl Filter {
+    fn term(term: FilterTerm) -> Self {
+        Self {
+            expr: FilterExpr::Term(term),
+        }
+    }
+
+    /// Tasks due today
+    pub fn today() -> Self {
+        Self::due_on(FilterDate::Today)
+    }
+
+    /// Tasks due tomorrow
+    pub fn tomorrow() -> Self {
+        Self::due_on(FilterDate::Tomorrow)
+    }
+
+    /// Tasks due on a date
+    pub fn due_on(date: impl Into<FilterDate>) -> Self {
+        Self::term(FilterTerm::DueOn(date.into()))
+    }
+
+    /// Tasks due before a date
+    pub fn due_before(date: impl Into<FilterDate>) -> Self {
+        Self::term(FilterTerm::DueBefore(date.into()))
+    }
+
+    /// Tasks due after a date
+    pub fn due_after(date: impl Into<FilterDate>) -> Self {
+        Self::term(FilterTerm::DueAfter(date.into()))
+    }
+
+    /// Overdue tasks
+    pub fn overdue() -> Self {
+        Self::term(FilterTerm::Overdue)
+    }
+
+    /// Tasks without a due date
+    pub fn no_date() -> Self {
+        Self::term(FilterTerm::NoDate)
+    }
+
+    /// Recurring tasks
+    pub fn recurring() -> Self {
+        Self::term(FilterTerm::Recurring)
+    }
+
+    /// Tasks without labels
+    pub fn no_labels() -> Self {
+        Self::term(FilterTerm::NoLabels)
+    }
+
+    /// Tasks with a priority
+    pub fn priority(priority: Priority) -> Self {
+        Self::term(FilterTerm::Priority(match priority {
+            Priority::P1 => 1,
+            Priority::P2 => 2,
+            Priority::P3 => 3,
+            Priority::P4 => 4,
+        }))
+    }
+
+    /// Tasks in a project, which may contain `*` wildcards
+    pub fn project(name: impl Into<String>) -> Self {
+        Self::term(FilterTerm::Project {
+            name: name.into(),
+            include_subprojects: false,
+        })
+    }
+
+    /// Tasks in a project or any of its sub-projects
+    pub fn project_with_subprojects(name: impl Into<String>) -> Self {
+        Self::term(FilterTerm::Project {
+            name: name.into(),
+            include_subprojects: true,
+        })
+    }
+
+    /// Tasks in a section
+    pub fn section(name: impl Into<String>) -> Self {
+        Self::term(FilterTerm::Section(name.into()))
+    }
+
+    /// Tasks with a label
+    pub fn label(name: impl Into<String>) -> Self {
+        Self::term(FilterTerm::Label(name.into()))
+    }
+
+    /// Tasks assigned to anyone
+    pub fn assigned() -> Self {
+        Self::term(FilterTerm::Assigned)
+    }
+
+    /// Tasks assigned to the current user
+    pub fn assigned_to_me() -> Self {
+        Self::term(FilterTerm::AssignedTo(Assignee::Me))
+    }
+
+    /// Tasks assigned to other users
+    pub fn assigned_to_others() -> Self {
+        Self::term(FilterTerm::AssignedTo(Assignee::Others))
+    }
+
+    /// Tasks assigned to a collaborator, by name or email
+    pub fn assigned_to(name: impl Into<String>) -> Self {
+        Self::term(FilterTerm::AssignedTo(Assignee::Name(name.into())))
+    }
+
+    /// Tasks whose content contains a text
+    pub fn search(text: impl Into<String>) -> Self {
+        Self::term(FilterTerm::Search(text.into()))
+    }
+
+    /// Tasks matching both filters
+    pub fn and(self, other: Filter) -> Self {
+        Self {
+            expr: FilterExpr::And(Box::new(self.expr), Box::new(other.expr)),
+        }
+    }
+
+    /// Tasks matching either filter
+    pub fn or(self, other: Filter) -> Self {
+        Self {
+            expr: FilterExpr::Or(Box::new(self.expr), Box::new(other.expr)),
+        }
+    }
+
+    /// Tasks matching this filter but not the other one
+    ///
+    /// Use `!filter` to negate a single filter.
+    pub fn not(self, other: Filter) -> Self {
+        self.and(!other)
+    }
+
+    /// The expression built so far
+    pub fn expr(&self) -> &FilterExpr {
+        &self.expr
+    }
+
+    /// Convert into a query that can be evaluated locally
+    pub fn into_query(self) -> FilterQuery {
+        FilterQuery {
+            queries: vec![self.expr],
+        }
+    }
+}
+
+impl std::ops::Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Filter {
+            expr: FilterExpr::Not(Box::new(self.expr)),
+        }
+    }
+}
+
+impl From<Filter> for FilterExpr {
+    fn from(filter: Filter) -> Self {
+        filter.expr
+    }
+}
+
+impl From<Filter> for FilterQuery {
+    fn from(filter: Filter) -> Self {
+        filter.into_query()
+    }
+}
+
+impl From<Filter> for TaskFilterArgs {
+    fn from(filter: Filter) -> Self {
+        TaskFilterArgs {
+            query: filter.to_string(),
+            lang: None,
+            limit: None,
+            cursor: None,
+        }
+    }
+}
+
+// ===== RENDERING =====
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.expr.fmt(f)
+    }
+}
+
+impl fmt::Display for FilterQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, query) in self.queries.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            query.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FilterExpr {
+    /// Binding strength, used to add only the parentheses the parser needs
+    fn precedence(&self) -> u8 {
+        match self {
+            FilterExpr::Or(..) => 1,
+            FilterExpr::And(..) => 2,
+            FilterExpr::Not(_) => 3,
+            FilterExpr::Term(_) => 4,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, min_precedence: u8) -> fmt::Result {
+        if self.precedence() < min_precedence {
+            write!(f, "({self})")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+}
+
+impl fmt::Display for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Operators are left-associative, so a right operand of the same kind keeps its parentheses
+        match self {
+            FilterExpr::Term(term) => term.fmt(f),
+            FilterExpr::Not(expr) => {
+                write!(f, "!")?;
+                expr.fmt_operand(f, 3)
+            }
+            FilterExpr::And(left, right) => {
+                left.fmt_operand(f, 2)?;
+                write!(f, " & ")?;
+                right.fmt_operand(f, 3)
+            }
+            FilterExpr::Or(left, right) => {
+                left.fmt_operand(f, 1)?;
+                write!(f, " | ")?;
+                right.fmt_operand(f, 2)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FilterTerm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterTerm::DueOn(date) => write!(f, "{date}"),
+            FilterTerm::Overdue => write!(f, "overdue"),
+            FilterTerm::NoDate => write!(f, "no date"),
+            FilterTerm::Recurring => write!(f, "recurring"),
+            FilterTerm::NoLabels => write!(f, "no labels"),
+            FilterTerm::Priority(p) => write!(f, "p{p}"),
+            FilterTerm::Project {
+                name,
+                include_subprojects,
+            } => {
+                let sigil = if *include_subprojects { "##" } else { "#" };
+                write!(f, "{sigil}{}", escape(name))
+            }
+            FilterTerm::Section(name) => write!(f, "/{}", escape(name)),
+            FilterTerm::Label(name) => write!(f, "@{}", escape(name)),
+            FilterTerm::Assigned => write!(f, "assigned"),
+            FilterTerm::AssignedTo(Assignee::Me) => write!(f, "assigned to: me"),
+            FilterTerm::AssignedTo(Assignee::Others) => write!(f, "assigned to: others"),
+            FilterTerm::AssignedTo(Assignee::Name(name)) => write!(f, "assigned to: {}", escape(name)),
+            FilterTerm::DueBefore(date) => write!(f, "due before: {date}"),
+            FilterTerm::DueAfter(date) => write!(f, "due after: {date}"),
+            FilterTerm::Search(text) => write!(f, "search: {}", escape(text)),
+        }
+    }
+}
+
+impl fmt::Display for FilterDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterDate::Today => write!(f, "today"),
+            FilterDate::Tomorrow => write!(f, "tomorrow"),
+            FilterDate::Yesterday => write!(f, "yesterday"),
+            FilterDate::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+        }
+    }
+}
+
+/// Escape the characters the tokenizer would otherwise treat as operators
+fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if is_delimiter(c) || c == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// ===== TOKENIZER =====
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Comma,
+    Term(FilterTerm),
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::And => write!(f, "'&'"),
+            TokenKind::Or => write!(f, "'|'"),
+            TokenKind::Not => write!(f, "'!'"),
+            TokenKind::LParen => write!(f, "'('"),
+            TokenKind::RParen => write!(f, "')'"),
+            TokenKind::Comma => write!(f, "','"),
+            TokenKind::Term(_) => write!(f, "filter term"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+/// Characters ending a term unless escaped with a backslash
+fn is_delimiter(c: char) -> bool {
+    matches!(c, '&' | '|' | '(' | ')' | ',')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, FilterParseError> {
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (position, c) = chars[i];
+        let kind = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '&' => TokenKind::And,
+            '|' => TokenKind::Or,
+            '!' => TokenKind::Not,
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            ',' => TokenKind::Comma,
+            _ => {
+                let (raw, next) = read_term(&chars, i);
+                tokens.push(Token {
+                    kind: TokenKind::Term(parse_term(&raw, position)?),
+                    position,
+                });
+                i = next;
+                continue;
+            }
+        };
+        tokens.push(Token { kind, position });
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+/// Read a term up to the next unescaped delimiter, resolving escapes
+fn read_term(chars: &[(usize, char)], start: usize) -> (String, usize) {
+    let mut raw = String::new();
+    let mut i = start;
+    while i < chars.len() {
+        let c = chars[i].1;
+        if c == '\\' && i + 1 < chars.len() {
+            raw.push(chars[i + 1].1);
+            i += 2;
+            continue;
+        }
+        if is_delimiter(c) {
+            break;
+        }
+        raw.push(c);
+        i += 1;
+    }
+    (raw.trim_end().to_string(), i)
+}
+
+fn parse_term(raw: &str, position: usize) -> Result<FilterTerm, FilterParseError> {
+    let sigil_name = |sigil: &str, what: &str| -> Result<String, FilterParseError> {
+        let name = raw[sigil.len()..].trim();
+        if name.is_empty() {
+            Err(FilterParseError::new(
+                format!("expected a {what} name after '{sigil}'"),
+                position,
+            ))
+        } else {
+            Ok(name.to_string())
+        }
+    };
+
+    if raw.starts_with("##") {
+        return Ok(FilterTerm::Project {
+            name: sigil_name("##", "project")?,
+            include_subprojects: true,
+        });
+    }
+    if raw.starts_with('#') {
+        return Ok(FilterTerm::Project {
+            name: sigil_name("#", "project")?,
+            include_subprojects: false,
+        });
+    }
+    if raw.starts_with('/') {
+        return Ok(FilterTerm::Section(sigil_name("/", "section")?));
+    }
+    if raw.starts_with('@') {
+        return Ok(FilterTerm::Label(sigil_name("@", "label")?));
+    }
+
+    if let Some((keyword, argument)) = raw.split_once(':') {
+        let keyword = keyword.trim().to_lowercase();
+        let argument = argument.trim();
+        let argument_position = position + raw.find(':').unwrap_or_default() + 1;
+        if argument.is_empty() {
+            return Err(FilterParseError::new(
+                format!("expected a value after '{keyword}:'"),
+                argument_position,
+            ));
+        }
+        return match keyword.as_str() {
+            "assigned to" => Ok(FilterTerm::AssignedTo(match argument.to_lowercase().as_str() {
+                "me" => Assignee::Me,
+                "others" => Assignee::Others,
+                _ => Assignee::Name(argument.to_string()),
+            })),
+            "due before" => Ok(FilterTerm::DueBefore(parse_date(argument, argument_position)?)),
+            "due after" => Ok(FilterTerm::DueAfter(parse_date(argument, argument_position)?)),
+            "search" => Ok(FilterTerm::Search(argument.to_string())),
+            _ => Err(FilterParseError::new(
+                format!(
+                    "unknown filter '{keyword}:' (expected 'assigned to:', 'due before:', 'due after:' or 'search:')"
+                ),
+                position,
+            )),
+        };
+    }
+
+    let normalized = raw.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    match normalized.as_str() {
+        "overdue" | "od" => Ok(FilterTerm::Overdue),
+        "no date" | "no due date" => Ok(FilterTerm::NoDate),
+        "recurring" => Ok(FilterTerm::Recurring),
+        "no labels" => Ok(FilterTerm::NoLabels),
+        "assigned" => Ok(FilterTerm::Assigned),
+        "p1" => Ok(FilterTerm::Priority(1)),
+        "p2" => Ok(FilterTerm::Priority(2)),
+        "p3" => Ok(FilterTerm::Priority(3)),
+        "p4" => Ok(FilterTerm::Priority(4)),
+        _ => match parse_date(raw, position) {
+            Ok(date) => Ok(FilterTerm::DueOn(date)),
+            Err(_) if normalized.starts_with('p') && normalized[1..].chars().all(|c| c.is_ascii_digit()) => Err(
+                FilterParseError::new(format!("unknown priority '{raw}' (expected p1 to p4)"), position),
+            ),
+            Err(_) => Err(FilterParseError::new(format!("unknown filter term '{raw}'"), position)),
+        },
+    }
+}
+
+fn parse_date(raw: &str, position: usize) -> Result<FilterDate, FilterParseError> {
+    match raw.trim().to_lowercase().as_str() {
+        "today" => Ok(FilterDate::Today),
+        "tomorrow" => Ok(FilterDate::Tomorrow),
+        "yesterday" => Ok(FilterDate::Yesterday),
+        other => NaiveDate::parse_from_str(other, "%Y-%m-%d")
+            .map(FilterDate::Date)
+            .map_err(|_| {
+                FilterParseError::new(
+                    format!(
+                        "invalid date '{}' (expected today, tomorrow, yesterday or YYYY-MM-DD)",
+                        raw.trim()
+                    ),
+                    position,
+                )
+            }),
+    }
+}
+
+// ===== PARSER =====
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    input_len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.peek().is_some_and(|t| &t.kind == kind) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, FilterParseError> {
+        let mut expr = self.parse_and()?;
+        while self.eat(&TokenKind::Or) {
+            expr = FilterExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, FilterParseError> {
+        let mut expr = self.parse_unary()?;
+        while self.eat(&TokenKind::And) {
+            expr = FilterExpr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterExpr, FilterParseError> {
+        if self.eat(&TokenKind::Not) {
+            return Ok(FilterExpr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        let Some(token) = self.tokens.get(self.position).cloned() else {
+            return Err(FilterParseError::new("expected a filter term", self.input_len));
+        };
+        self.position += 1;
+        match token.kind {
+            TokenKind::Term(term) => Ok(FilterExpr::Term(term)),
+            TokenKind::LParen => {
+                let expr = self.parse_or()?;
+                if !self.eat(&TokenKind::RParen) {
+                    return Err(FilterParseError::new("missing ')' to close '('", token.position));
+                }
+                Ok(expr)
+            }
+            kind => Err(FilterParseError::new(
+                format!("expected a filter term but found {kind}"),
+                token.position,
+            )),
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::models::{Due, Task};
+use crate::wrapper::TodoistWrapper;
+
+/// Product identifier written in every calendar
+pub const PRODUCT_ID: &str = "-//todoist-api//iCalendar export//EN";
+
+/// Longest line, in octets, before it is folded
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Calendar component tasks are rendered as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IcsComponent {
+    /// `VTODO`, shown as tasks; tasks without a date are included
+    #[default]
+    Todo,
+    /// `VEVENT`, shown on the calendar; tasks without a date or deadline are left out
+    Event,
+}
+
+/// Options for [`render_calendar`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IcsOptions {
+    pub component: IcsComponent,
+    /// Calendar name shown by calendar apps
+    pub name: Option<String>,
+}
+
+/// Render tasks as an RFC 5545 calendar
+///
+/// Each task keeps the UID `{task_id}@todoist.com`, so re-importing or re-fetching a feed updates
+/// entries instead of duplicating them. Dates without a time become all-day entries, times with a
+/// timezone are written in UTC unless they repeat, and floating times stay floating. Recurring dues get an
+/// `RRULE` when [`recurrence_rule`] understands them, and keep only the next occurrence otherwise.
+pub fn render_calendar(tasks: &[Task], options: &IcsOptions) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{PRODUCT_ID}"),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+    ];
+    if let Some(name) = &options.name {
+        lines.push(format!("X-WR-CALNAME:{}", escape_text(name)));
+    }
+    for task in tasks {
+        lines.extend(render_task(task, options.component));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut calendar = String::new();
+    for line in lines {
+        calendar.push_str(&fold_line(&line));
+        calendar.push_str("\r\n");
+    }
+    calendar
+}
+
+impl TodoistWrapper {
+    /// Render the open tasks of a project as an iCalendar file
+    ///
+    /// The calendar is named after the project unless `options` sets a name.
+    pub async fn export_project_ics(&self, project_id: &str, options: &IcsOptions) -> Result<String> {
+        let (project, tasks) =
+            futures::try_join!(self.get_project(project_id), self.get_tasks_for_project(project_id))?;
+        let options = IcsOptions {
+            name: options.name.clone().or(Some(project.name)),
+            ..options.clone()
+        };
+        Ok(render_calendar(&tasks, &options))
+    }
+}
+
+/// Translate a Todoist recurring due string into an `RRULE` value
+///
+/// Understands daily, weekly, monthly and yearly repeats with an optional interval (`every 3 days`,
+/// `every other week`), weekday lists (`every mon, fri`), `every weekday` and `every weekend`.
+/// Times such as `at 9am` are ignored since they are part of the start time. Returns `None` for
+/// anything else, such as `every 3rd friday` or `every day until june`.
+pub fn recurrence_rule(due_string: &str) -> Option<String> {
+    let text = due_string.to_lowercase().replace("every!", "every").replace(',', " ");
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "at" | "and") && !is_time(word))
+        .collect();
+
+    let (interval, unit) = match words.as_slice() {
+        ["daily"] => (1, "day"),
+        ["weekly"] => (1, "week"),
+        ["monthly"] => (1, "month"),
+        ["yearly" | "annually"] => (1, "year"),
+        ["every", "weekday" | "workday"] => return Some("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR".to_string()),
+        ["every", "weekend"] => return Some("FREQ=WEEKLY;BYDAY=SA,SU".to_string()),
+        ["every", unit] if frequency(unit).is_some() => (1, *unit),
+        ["every", "other", unit] => (2, *unit),
+        ["every", count, unit] if count.chars().all(|c| c.is_ascii_digit()) => {
+            (count.parse::<u32>().ok().filter(|count| *count > 0)?, *unit)
+        }
+        ["every", days @ ..] if !days.is_empty() => {
+            let days = days.iter().map(|day| weekday(day)).collect::<Option<Vec<_>>>()?;
+            return Some(format!("FREQ=WEEKLY;BYDAY={}", days.join(",")));
+        }
+        _ => return None,
+    };
+
+    let frequency = frequency(unit)?;
+    Some(match interval {
+        1 => format!("FREQ={frequency}"),
+        _ => format!("FREQ={frequency};INTERVAL={interval}"),
+    })
+}
+
+fn frequency(unit: &str) -> Option<&'static str> {
+    match unit.trim_end_matches('s') {
+        "day" => Some("DAILY"),
+        "week" => Some("WEEKLY"),
+        "month" => Some("MONTHLY"),
+        "year" => Some("YEARLY"),
+        _ => None,
+    }
+}
+
+fn weekday(word: &str) -> Option<&'static str> {
+    let day = match word {
+        "mon" | "monday" => "MO",
+        "tue" | "tues" | "tuesday" => "TU",
+        "wed" | "wednesday" => "WE",
+        "thu" | "thurs" | "thursday" => "TH",
+        "fri" | "friday" => "FR",
+        "sat" | "saturday" => "SA",
+        "sun" | "sunday" => "SU",
+        _ => return None,
+    };
+    Some(day)
+}
+
+/// Whether a word is a time of day, such as `9am`, `5:30pm` or `14:00`
+fn is_time(word: &str) -> bool {
+    let digits = word.trim_end_matches("am").trim_end_matches("pm");
+    word.starts_with(|c: char| c.is_ascii_digit())
+        && (digits.len() < word.len() || word.contains(':'))
+        && digits.chars().all(|c| c.is_ascii_digit() || c == ':')
+}
+
+/// A point in time in one of the forms iCalendar distinguishes
+///
+/// Times in a timezone are written in UTC, since a `TZID` would need a `VTIMEZONE` definition.
+/// Repeating times stay floating instead, so their occurrences keep the same wall-clock time
+/// across daylight saving changes.
+#[derive(Debug, Clone, Copy)]
+enum Moment {
+    Date(NaiveDate),
+    Floating(NaiveDateTime),
+    Utc(DateTime<Utc>),
+}
+
+impl Moment {
+    fn from_due(due: &Due) -> Option<Self> {
+        let Some(datetime) = &due.datetime else {
+            return parse_date(&due.date).map(Moment::Date);
+        };
+        let timezone = due.timezone.as_deref().and_then(|timezone| timezone.parse::<Tz>().ok());
+        if let Ok(instant) = DateTime::parse_from_rfc3339(datetime) {
+            return Some(match timezone.filter(|_| due.is_recurring) {
+                Some(timezone) => Moment::Floating(instant.with_timezone(&timezone).naive_local()),
+                None => Moment::Utc(instant.with_timezone(&Utc)),
+            });
+        }
+        let local = NaiveDateTime::parse_from_str(datetime, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+        if due.is_recurring {
+            return Some(Moment::Floating(local));
+        }
+        let instant = timezone.and_then(|timezone| timezone.from_local_datetime(&local).earliest());
+        Some(match instant {
+            Some(instant) => Moment::Utc(instant.with_timezone(&Utc)),
+            None => Moment::Floating(local),
+        })
+    }
+
+    fn is_date(&self) -> bool {
+        matches!(self, Moment::Date(_))
+    }
+
+    fn add(self, duration: chrono::Duration) -> Self {
+        match self {
+            Moment::Date(date) => Moment::Date(date + duration),
+            Moment::Floating(local) => Moment::Floating(local + duration),
+            Moment::Utc(instant) => Moment::Utc(instant + duration),
+        }
+    }
+
+    /// Content line for a date or date-time property, such as `DTSTART;VALUE=DATE:20240506`
+    fn property(&self, name: &str) -> String {
+        match self {
+            Moment::Date(date) => format!("{name};VALUE=DATE:{}", date.format("%Y%m%d")),
+            Moment::Floating(local) => format!("{name}:{}", local.format("%Y%m%dT%H%M%S")),
+            Moment::Utc(instant) => format!("{name}:{}", instant.format("%Y%m%dT%H%M%SZ")),
+        }
+    }
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Length of a task, with day durations also applying to timed tasks
+fn task_duration(task: &Task) -> Option<chrono::Duration> {
+    let duration = task.duration.as_ref()?;
+    let amount = i64::from(duration.amount);
+    match duration.unit.as_str() {
+        "minute" => Some(chrono::Duration::minutes(amount)),
+        "hour" => Some(chrono::Duration::hours(amount)),
+        "day" => Some(chrono::Duration::days(amount)),
+        _ => None,
+    }
+    .filter(|duration| *duration > chrono::Duration::zero())
+}
+
+fn render_task(task: &Task, component: IcsComponent) -> Vec<String> {
+    let start = task.due.as_ref().and_then(Moment::from_due);
+    let deadline = task
+        .deadline
+        .as_ref()
+        .and_then(|deadline| parse_date(&deadline.date))
+        .map(Moment::Date);
+    let rule = task
+        .due
+        .as_ref()
+        .filter(|due| due.is_recurring)
+        .and_then(|due| recurrence_rule(&due.string));
+    let duration = task_duration(task);
+
+    let mut notes = Vec::new();
+    if let Some(due) = task.due.as_ref().filter(|due| due.is_recurring && rule.is_none()) {
+        notes.push(format!("Repeats: {}", due.string));
+    }
+
+    let mut dates = Vec::new();
+    let name = match component {
+        IcsComponent::Todo => {
+            match start {
+                Some(start) if rule.is_none() && duration.is_none() => {
+                    dates.push(start.property("DUE"));
+                    if let Some(deadline) = &task.deadline {
+                        notes.push(format!("Deadline: {}", deadline.date));
+                    }
+                }
+                Some(start) => {
+                    // DUE has to be later than DTSTART, so like DTEND it is exclusive
+                    dates.push(start.property("DTSTART"));
+                    let end = if start.is_date() {
+                        let days = duration.map_or(1, |duration| duration.num_days().max(1));
+                        Some(start.add(chrono::Duration::days(days)))
+                    } else {
+                        // A repeating time without a length only has a start
+                        duration.map(|duration| start.add(duration))
+                    };
+                    dates.extend(end.map(|end| end.property("DUE")));
+                    if let Some(deadline) = &task.deadline {
+                        notes.push(format!("Deadline: {}", deadline.date));
+                    }
+                }
+                None => dates.extend(deadline.map(|deadline| deadline.property("DUE"))),
+            }
+            "VTODO"
+        }
+        IcsComponent::Event => {
+            let Some(start) = start.or(deadline) else {
+                return Vec::new();
+            };
+            dates.push(start.property("DTSTART"));
+            if start.is_date() {
+                // All-day events end on the day after their last day
+                let days = duration.map_or(1, |duration| duration.num_days().max(1));
+                dates.push(start.add(chrono::Duration::days(days)).property("DTEND"));
+            } else if let Some(duration) = duration {
+                dates.push(start.add(duration).property("DTEND"));
+            }
+            if let Some(deadline) = task.deadline.as_ref().filter(|_| task.due.is_some()) {
+                notes.push(format!("Deadline: {}", deadline.date));
+            }
+            "VEVENT"
+        }
+    };
+
+    let stamp = DateTime::parse_from_rfc3339(&task.created_at)
+        .map(|created_at| created_at.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+    let mut lines = vec![
+        format!("BEGIN:{name}"),
+        format!("UID:{}@todoist.com", task.id),
+        format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")),
+        format!("SUMMARY:{}", escape_text(&task.content)),
+    ];
+    lines.extend(dates);
+    lines.extend(rule.map(|rule| format!("RRULE:{rule}")));
+
+    let mut description = task.description.clone();
+    for note in notes {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str(&note);
+    }
+    if !description.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape_text(&description)));
+    }
+    if !task.labels.is_empty() {
+        let labels: Vec<String> = task.labels.iter().map(|label| escape_text(label)).collect();
+        lines.push(format!("CATEGORIES:{}", labels.join(",")));
+    }
+    // iCalendar priorities go from 1 (highest) to 9, with 0 meaning undefined
+    let priority = match task.priority {
+        4 => 1,
+        3 => 5,
+        2 => 9,
+        _ => 0,
+    };
+    if priority > 0 {
+        lines.push(format!("PRIORITY:{priority}"));
+    }
+    if component == IcsComponent::Todo {
+        let status = if task.is_completed { "COMPLETED" } else { "NEEDS-ACTION" };
+        lines.push(format!("STATUS:{status}"));
+    }
+    if !task.url.is_empty() {
+        lines.push(format!("URL:{}", task.url));
+    }
+    lines.push(format!("END:{name}"));
+    lines
+}
+
+/// Escape a TEXT value
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fold a content line into 75-octet lines, never splitting a character
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
@@ -0,0 +1,136 @@
+use anyhow::Result;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use hmac::digest::CtOutput;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+use crate::ics::IcsOptions;
+use crate::wrapper::TodoistWrapper;
+
+/// Route prefix used when none is configured; feeds are served at `{prefix}/{project_id}.ics`
+pub const DEFAULT_ROUTE: &str = "/calendars";
+
+/// Serves the tasks of each project as an iCalendar feed
+///
+/// Calendar apps subscribe to `{route}/{project_id}.ics` and get the project's open tasks rendered
+/// with [`crate::ics::render_calendar`], fetched from Todoist on every request. Since calendar apps
+/// cannot send headers, feeds can be protected with a token passed as the `token` query parameter.
+///
+/// ```rust,no_run
+/// use todoist_api::ics::{IcsComponent, IcsOptions};
+/// use todoist_api::ics_server::CalendarServer;
+/// use todoist_api::TodoistWrapper;
+///
+/// # async fn run() -> anyhow::Result<()> {
+/// let todoist = TodoistWrapper::new("your-api-token".to_string());
+/// let server = CalendarServer::new(todoist)
+///     .with_access_token("long-random-string")
+///     .with_options(IcsOptions {
+///         component: IcsComponent::Event,
+///         name: None,
+///     });
+///
+/// // http://localhost:8080/calendars/2203306141.ics?token=long-random-string
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
+/// server.serve(listener).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct CalendarServer {
+    todoist: TodoistWrapper,
+    route: String,
+    options: IcsOptions,
+    access_token: Option<String>,
+}
+
+impl CalendarServer {
+    /// Create a server reading projects through a client
+    pub fn new(todoist: TodoistWrapper) -> Self {
+        Self {
+            todoist,
+            route: DEFAULT_ROUTE.to_string(),
+            options: IcsOptions::default(),
+            access_token: None,
+        }
+    }
+
+    /// Path prefix feeds are served under
+    pub fn with_route(mut self, route: impl Into<String>) -> Self {
+        self.route = route.into();
+        self
+    }
+
+    /// Rendering options; the calendar name defaults to the project name
+    pub fn with_options(mut self, options: IcsOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Require a `token` query parameter matching this value
+    pub fn with_access_token(mut self, token: impl Into<String>) -> Self {
+        self.access_token = Some(token.into());
+        self
+    }
+
+    /// Build a router to merge into an existing axum application
+    pub fn router(self) -> Router {
+        let route = format!("{}/:file", self.route.trim_end_matches('/'));
+        Router::new().route(&route, get(feed)).with_state(Arc::new(self))
+    }
+
+    /// Serve feeds on a listener until the process stops
+    pub async fn serve(self, listener: TcpListener) -> Result<()> {
+        axum::serve(listener, self.router()).await?;
+        Ok(())
+    }
+}
+
+async fn feed(
+    State(server): State<Arc<CalendarServer>>,
+    Path(file): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    if let Some(token) = &server.access_token {
+        if !query
+            .get("token")
+            .is_some_and(|presented| token_matches(token, presented))
+        {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+    }
+    let Some(project_id) = file.strip_suffix(".ics").filter(|id| !id.is_empty()) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    // Only ID characters reach the Todoist URL
+    if !project_id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    match server.todoist.export_project_ics(project_id, &server.options).await {
+        Ok(calendar) => ([(header::CONTENT_TYPE, "text/calendar; charset=utf-8")], calendar).into_response(),
+        Err(error) if is_not_found(&error) => StatusCode::NOT_FOUND.into_response(),
+        Err(_) => StatusCode::BAD_GATEWAY.into_response(),
+    }
+}
+
+/// Compare tokens in constant time; hashing first hides their lengths too
+fn token_matches(expected: &str, presented: &str) -> bool {
+    CtOutput::<Sha256>::new(Sha256::digest(expected)) == CtOutput::new(Sha256::digest(presented))
+}
+
+/// Whether Todoist answered 404, as it does for unknown projects
+fn is_not_found(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|error| error.status().is_some_and(|status| status.as_u16() == 404))
+}
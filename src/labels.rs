@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use crate::models::{Label, Task};
+
+/// A label name as seen across personal labels, shared labels and tasks
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelUsage {
+    pub name: String,
+    /// Personal label with this name, if there is one
+    pub label: Option<Label>,
+    /// Whether the name is used as a shared label
+    pub is_shared: bool,
+    /// Number of tasks carrying the label
+    pub task_count: usize,
+}
+
+/// Reconcile personal labels with shared label names and the names found on tasks
+///
+/// Names are matched case-insensitively, as Todoist does. Personal labels come first in their
+/// own order, followed by other names alphabetically.
+pub fn merge_labels(labels: &[Label], shared_labels: &[String], tasks: &[Task]) -> Vec<LabelUsage> {
+    let mut usages: Vec<LabelUsage> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    let mut personal: Vec<&Label> = labels.iter().collect();
+    personal.sort_by_key(|label| label.order);
+    for label in personal {
+        index.insert(label.name.to_lowercase(), usages.len());
+        usages.push(LabelUsage {
+            name: label.name.clone(),
+            label: Some(label.clone()),
+            is_shared: false,
+            task_count: 0,
+        });
+    }
+    let personal_count = usages.len();
+
+    let mut entry = |name: &str| -> usize {
+        *index.entry(name.to_lowercase()).or_insert_with(|| {
+            usages.push(LabelUsage {
+                name: name.to_string(),
+                label: None,
+                is_shared: false,
+                task_count: 0,
+            });
+            usages.len() - 1
+        })
+    };
+    let shared: Vec<usize> = shared_labels.iter().map(|name| entry(name)).collect();
+    let used: Vec<usize> = tasks
+        .iter()
+        .flat_map(|task| &task.labels)
+        .map(|name| entry(name))
+        .collect();
+
+    for position in shared {
+        usages[position].is_shared = true;
+    }
+    for position in used {
+        usages[position].task_count += 1;
+    }
+
+    usages[personal_count..].sort_by_key(|usage| usage.name.to_lowercase());
+    usages
+}
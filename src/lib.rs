@@ -6,7 +6,25 @@
 //!
 //! - Async/await support
 //! - Full CRUD operations for tasks
+//! - Natural-language quick add reporting the interpreted project, labels, priority and due date
 //! - Project and label management
+//! - Batched commands through the Sync API with temporary ID chaining
+//! - Local replica of an account with incremental sync and change events
+//! - Typed activity log with before/after field changes
+//! - Export and import of Todoist CSV templates with subtask indentation (`csv` feature)
+//! - iCalendar export of tasks with recurrence rules and timezones (`ics` feature)
+//! - Optional per-project `.ics` feed server (`ics-server` feature)
+//! - Account backup to JSON or zip, with dry-run restore into any account (`backup` feature)
+//! - Streaming file uploads and downloads for comment attachments, with project archiving
+//! - Declarative TOML or YAML project templates with idempotent apply and plan mode (`templates` feature)
+//! - Local evaluation of Todoist filter queries
+//! - Typed builder for Todoist filter strings
+//! - Webhook signature verification and typed events
+//! - Optional embeddable webhook server (`webhook-server` feature)
+//! - OAuth2 authorization-code flow with a loopback redirect listener
+//! - Pluggable credential providers for rotating tokens
+//! - Multi-account client pool with per-account rate limits
+//! - Optional SQLite persistence with an offline outbox (`sqlite` feature)
 //! - Comprehensive error handling with anyhow
 //! - Serde serialization/deserialization
 //!
@@ -31,10 +49,57 @@
 //! }
 //! ```
 
+pub mod activity;
+pub mod attachments;
+#[cfg(feature = "backup")]
+pub mod backup;
+pub mod batch;
+pub mod credentials;
+#[cfg(feature = "csv")]
+pub mod csv_template;
+pub mod filter;
+#[cfg(feature = "ics")]
+pub mod ics;
+#[cfg(feature = "ics-server")]
+pub mod ics_server;
+pub mod labels;
 pub mod models;
+pub mod oauth;
+#[cfg(feature = "sqlite")]
+pub mod offline;
+pub mod pool;
+pub mod quick_add;
+pub mod rate_limit;
+pub mod store;
+pub mod sync;
+#[cfg(feature = "templates")]
+pub mod templates;
+pub mod uploads;
+#[cfg(feature = "webhook-server")]
+pub mod webhook_server;
+pub mod webhooks;
 pub mod wrapper;
 
+pub use activity::{ActivityAction, ActivityArgs, ActivityEvent, ActivityObject, ActivityObjectType, FieldChange};
+pub use attachments::{ArchiveReport, AttachmentManifest, ExistingFileCheck, ManifestEntry};
+#[cfg(feature = "backup")]
+pub use backup::{Backup, BackupFormat, RestoreReport};
+pub use batch::{Batch, BatchCommand, BatchResult, CommandResult, CommandStatus};
+pub use credentials::CredentialProvider;
+pub use filter::{Filter, FilterContext, FilterParseError, FilterQuery};
+#[cfg(feature = "ics")]
+pub use ics::{IcsComponent, IcsOptions};
+pub use labels::{merge_labels, LabelUsage};
 pub use models::*;
+pub use pool::TodoistPool;
+pub use quick_add::{QuickAddOptions, QuickAddResult};
+pub use rate_limit::RateLimit;
+pub use store::{Change, StoreEvent, StoreSnapshot, TodoistStore};
+pub use sync::SyncResponse;
+#[cfg(feature = "templates")]
+pub use templates::{PlanAction, PlanStep, Template, TemplateError, TemplateObject, TemplatePlan};
+pub use uploads::UploadSource;
+pub use webhooks::{Webhook, WebhookError, WebhookEvent, WebhookVerifier};
 pub use wrapper::TodoistWrapper;
 
 // Re-export commonly used types
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Todoist Task model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Task {
     pub id: String,
     pub content: String,
@@ -23,7 +23,7 @@ pub struct Task {
 }
 
 /// Todoist Project model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Project {
     pub id: String,
     pub name: String,
@@ -40,7 +40,7 @@ pub struct Project {
 }
 
 /// Todoist Label model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Label {
     pub id: String,
     pub name: String,
@@ -50,7 +50,7 @@ pub struct Label {
 }
 
 /// Todoist Section model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Section {
     pub id: String,
     pub name: String,
@@ -60,7 +60,7 @@ pub struct Section {
 }
 
 /// Todoist Comment model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Comment {
     pub id: String,
     pub content: String,
@@ -71,7 +71,7 @@ pub struct Comment {
 }
 
 /// Todoist Attachment model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Attachment {
     pub file_name: String,
     pub file_type: String,
@@ -80,18 +80,146 @@ pub struct Attachment {
 }
 
 /// Todoist User model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct User {
     pub id: String,
     pub name: String,
     pub email: String,
     pub avatar_url: Option<String>,
+    #[serde(default)]
     pub is_premium: bool,
+    #[serde(default)]
     pub is_business_account: bool,
 }
 
+/// Profile and preferences of the authenticated user
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UserProfile {
+    pub user: User,
+    /// IANA timezone name, such as `Europe/Berlin`
+    pub timezone: String,
+    /// Offset from UTC, such as `+02:00`
+    pub utc_offset: String,
+    /// First day of the week, from 1 (Monday) to 7 (Sunday)
+    pub start_day: i32,
+    pub lang: String,
+    pub daily_goal: i32,
+    pub weekly_goal: i32,
+    pub karma: f64,
+}
+
+/// Karma, goals and completion counts of the authenticated user
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProductivityStats {
+    pub karma: f64,
+    /// `up`, `down` or `-`
+    pub karma_trend: String,
+    /// Number of tasks completed since the account was created
+    pub completed_count: i64,
+    pub daily_goal: i32,
+    pub weekly_goal: i32,
+    pub current_daily_streak: Streak,
+    pub max_daily_streak: Streak,
+    pub current_weekly_streak: Streak,
+    pub max_weekly_streak: Streak,
+    /// Completions of the last days, most recent first
+    pub days: Vec<DayCompletions>,
+    /// Completions of the last weeks, most recent first
+    pub weeks: Vec<WeekCompletions>,
+}
+
+/// A run of days or weeks in which the goal was met
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Streak {
+    #[serde(default)]
+    pub count: i32,
+    #[serde(default)]
+    pub start: String,
+    #[serde(default)]
+    pub end: String,
+}
+
+/// Number of tasks completed on a day
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DayCompletions {
+    pub date: String,
+    pub total_completed: i32,
+}
+
+/// Number of tasks completed in a week
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WeekCompletions {
+    pub from: String,
+    pub to: String,
+    pub total_completed: i32,
+}
+
+/// A completed task from the completion history
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CompletedTask {
+    /// ID of the completion record
+    pub id: String,
+    pub task_id: String,
+    pub content: String,
+    pub project_id: String,
+    pub section_id: Option<String>,
+    pub completed_at: String,
+    pub note_count: i32,
+    /// The task as it was when completed
+    pub task: Option<Task>,
+    pub project: Option<Project>,
+}
+
+/// Todoist Reminder model
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Reminder {
+    pub id: String,
+    pub task_id: String,
+    /// User to notify, the task's assignee or owner when not set
+    pub notify_uid: Option<String>,
+    pub trigger: ReminderTrigger,
+}
+
+/// When a reminder fires
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReminderTrigger {
+    /// A number of minutes before the task is due
+    Relative { minute_offset: i32 },
+    /// At a fixed datetime, such as `2024-05-01T09:00:00Z`
+    Absolute { datetime: String },
+    /// When entering or leaving a place
+    Location {
+        name: String,
+        latitude: String,
+        longitude: String,
+        trigger: LocationTrigger,
+        /// Radius around the location, in meters
+        radius: i32,
+    },
+}
+
+/// Whether a location reminder fires when arriving or leaving
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LocationTrigger {
+    OnEnter,
+    OnLeave,
+}
+
+/// Todoist saved filter model, a named view over a filter query
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SavedFilter {
+    pub id: String,
+    pub name: String,
+    pub query: String,
+    pub color: String,
+    pub order: i32,
+    pub is_favorite: bool,
+}
+
 /// Todoist Due date model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Due {
     pub string: String,
     pub date: String,
@@ -101,20 +229,20 @@ pub struct Due {
 }
 
 /// Todoist Deadline model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Deadline {
     pub date: String,
 }
 
 /// Todoist Duration model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Duration {
     pub amount: i32,
     pub unit: String, // "minute", "hour", "day"
 }
 
 /// Task creation arguments
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CreateTaskArgs {
     pub content: String,
     pub description: Option<String>,
@@ -136,7 +264,7 @@ pub struct CreateTaskArgs {
 }
 
 /// Task update arguments
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct UpdateTaskArgs {
     pub content: Option<String>,
     pub description: Option<String>,
@@ -154,7 +282,7 @@ pub struct UpdateTaskArgs {
 }
 
 /// Project creation arguments
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CreateProjectArgs {
     pub name: String,
     pub color: Option<String>,
@@ -164,7 +292,7 @@ pub struct CreateProjectArgs {
 }
 
 /// Project update arguments
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct UpdateProjectArgs {
     pub name: Option<String>,
     pub color: Option<String>,
@@ -173,7 +301,7 @@ pub struct UpdateProjectArgs {
 }
 
 /// Label creation arguments
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CreateLabelArgs {
     pub name: String,
     pub color: Option<String>,
@@ -182,7 +310,7 @@ pub struct CreateLabelArgs {
 }
 
 /// Label update arguments
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct UpdateLabelArgs {
     pub name: Option<String>,
     pub color: Option<String>,
@@ -191,7 +319,7 @@ pub struct UpdateLabelArgs {
 }
 
 /// Section creation arguments
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CreateSectionArgs {
     pub name: String,
     pub project_id: String,
@@ -199,13 +327,13 @@ pub struct CreateSectionArgs {
 }
 
 /// Section update arguments
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct UpdateSectionArgs {
     pub name: String,
 }
 
 /// Comment creation arguments
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CreateCommentArgs {
     pub content: String,
     pub task_id: Option<String>,
@@ -214,13 +342,48 @@ pub struct CreateCommentArgs {
 }
 
 /// Comment update arguments
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct UpdateCommentArgs {
     pub content: String,
 }
 
+/// Reminder creation arguments
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateReminderArgs {
+    pub task_id: String,
+    pub trigger: ReminderTrigger,
+    pub notify_uid: Option<String>,
+}
+
+/// Reminder update arguments
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UpdateReminderArgs {
+    pub trigger: Option<ReminderTrigger>,
+    pub notify_uid: Option<String>,
+}
+
+/// Saved filter creation arguments
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CreateSavedFilterArgs {
+    pub name: String,
+    pub query: String,
+    pub color: Option<String>,
+    pub order: Option<i32>,
+    pub is_favorite: Option<bool>,
+}
+
+/// Saved filter update arguments
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UpdateSavedFilterArgs {
+    pub name: Option<String>,
+    pub query: Option<String>,
+    pub color: Option<String>,
+    pub order: Option<i32>,
+    pub is_favorite: Option<bool>,
+}
+
 /// Task filter arguments
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Clone)]
 pub struct TaskFilterArgs {
     pub query: String,
     pub lang: Option<String>,
@@ -228,6 +391,19 @@ pub struct TaskFilterArgs {
     pub cursor: Option<String>,
 }
 
+/// Completed task history arguments
+///
+/// `since` and `until` are datetimes such as `2024-05-01T00:00:00`.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct CompletedTaskArgs {
+    pub project_id: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Page size, at most 200
+    pub limit: Option<i32>,
+    pub offset: Option<i32>,
+}
+
 /// Project filter arguments
 #[derive(Debug, Serialize)]
 pub struct ProjectFilterArgs {
@@ -235,6 +411,13 @@ pub struct ProjectFilterArgs {
     pub cursor: Option<String>,
 }
 
+/// Archived project listing arguments
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ArchivedProjectArgs {
+    pub limit: Option<i32>,
+    pub offset: Option<i32>,
+}
+
 /// Label filter arguments
 #[derive(Debug, Serialize)]
 pub struct LabelFilterArgs {
@@ -250,6 +433,22 @@ pub struct SectionFilterArgs {
     pub cursor: Option<String>,
 }
 
+/// Archived section listing arguments
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ArchivedSectionArgs {
+    pub project_id: String,
+    pub limit: Option<i32>,
+    pub cursor: Option<String>,
+}
+
+/// A page of archived sections
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArchivedSections {
+    pub sections: Vec<Section>,
+    /// Cursor of the next page, if there is one
+    pub next_cursor: Option<String>,
+}
+
 /// Comment filter arguments
 #[derive(Debug, Serialize)]
 pub struct CommentFilterArgs {
@@ -0,0 +1,305 @@
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+const TODOIST_AUTHORIZE_URL: &str = "https://todoist.com/oauth/authorize";
+const TODOIST_TOKEN_URL: &str = "https://todoist.com/oauth/access_token";
+const TODOIST_REVOKE_URL: &str = "https://api.todoist.com/sync/v9/access_tokens/revoke";
+
+/// Permission requested from the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Add tasks, without reading anything
+    TaskAdd,
+    /// Read all data
+    DataRead,
+    /// Read and modify all data, except deleting
+    DataReadWrite,
+    /// Delete tasks, labels and filters
+    DataDelete,
+    /// Delete projects
+    ProjectDelete,
+}
+
+impl Scope {
+    /// Name of the scope in the authorize URL
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::TaskAdd => "task:add",
+            Scope::DataRead => "data:read",
+            Scope::DataReadWrite => "data:read_write",
+            Scope::DataDelete => "data:delete",
+            Scope::ProjectDelete => "project:delete",
+        }
+    }
+}
+
+/// URLs of the OAuth endpoints, which can point to a local stand-in in tests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthEndpoints {
+    pub authorize_url: String,
+    pub token_url: String,
+    pub revoke_url: String,
+}
+
+impl Default for OAuthEndpoints {
+    fn default() -> Self {
+        Self {
+            authorize_url: TODOIST_AUTHORIZE_URL.to_string(),
+            token_url: TODOIST_TOKEN_URL.to_string(),
+            revoke_url: TODOIST_REVOKE_URL.to_string(),
+        }
+    }
+}
+
+/// Access token obtained from the authorization-code flow
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AccessToken {
+    pub access_token: String,
+    #[serde(default)]
+    pub token_type: String,
+}
+
+impl fmt::Debug for AccessToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessToken")
+            .field("access_token", &"[redacted]")
+            .field("token_type", &self.token_type)
+            .finish()
+    }
+}
+
+/// An authorize URL together with the CSRF state it carries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationRequest {
+    pub url: String,
+    pub state: String,
+}
+
+/// Client for the Todoist OAuth2 authorization-code flow
+///
+/// ```rust,no_run
+/// use todoist_api::oauth::{LoopbackListener, OAuthClient, Scope};
+/// use std::time::Duration;
+/// use todoist_api::TodoistWrapper;
+///
+/// # async fn run() -> anyhow::Result<()> {
+/// let listener = LoopbackListener::bind(8765).await?;
+/// let oauth = OAuthClient::new("client-id", "client-secret").with_redirect_uri(listener.redirect_uri());
+///
+/// let request = oauth.authorize_url(&[Scope::DataReadWrite])?;
+/// println!("Open {} in your browser", request.url);
+///
+/// let code = listener.wait_for_code(&request.state, Duration::from_secs(300)).await?;
+/// let token = oauth.exchange_code(&code).await?;
+/// let todoist = TodoistWrapper::new(token.access_token);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct OAuthClient {
+    client: Client,
+    client_id: String,
+    client_secret: String,
+    redirect_uri: Option<String>,
+    endpoints: OAuthEndpoints,
+}
+
+impl fmt::Debug for OAuthClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuthClient")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"[redacted]")
+            .field("redirect_uri", &self.redirect_uri)
+            .field("endpoints", &self.endpoints)
+            .finish()
+    }
+}
+
+impl OAuthClient {
+    /// Create a client for a Todoist app registered in the App Management Console
+    pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            redirect_uri: None,
+            endpoints: OAuthEndpoints::default(),
+        }
+    }
+
+    /// Redirect URI sent with the authorize request, instead of the one registered for the app
+    pub fn with_redirect_uri(mut self, redirect_uri: impl Into<String>) -> Self {
+        self.redirect_uri = Some(redirect_uri.into());
+        self
+    }
+
+    /// Use custom OAuth endpoints
+    pub fn with_endpoints(mut self, endpoints: OAuthEndpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Build the authorize URL with a fresh random CSRF state
+    pub fn authorize_url(&self, scopes: &[Scope]) -> Result<AuthorizationRequest> {
+        let state = uuid::Uuid::new_v4().simple().to_string();
+        let url = self.authorize_url_with_state(scopes, &state)?;
+        Ok(AuthorizationRequest { url, state })
+    }
+
+    /// Build the authorize URL with a given CSRF state
+    pub fn authorize_url_with_state(&self, scopes: &[Scope], state: &str) -> Result<String> {
+        let scope = scopes.iter().map(Scope::as_str).collect::<Vec<_>>().join(",");
+        let mut params = vec![
+            ("client_id", self.client_id.as_str()),
+            ("scope", scope.as_str()),
+            ("state", state),
+        ];
+        if let Some(redirect_uri) = &self.redirect_uri {
+            params.push(("redirect_uri", redirect_uri));
+        }
+        let url = Url::parse_with_params(&self.endpoints.authorize_url, &params)
+            .with_context(|| format!("invalid authorize URL {}", self.endpoints.authorize_url))?;
+        Ok(url.to_string())
+    }
+
+    /// Exchange the code received on the redirect URI for an access token
+    pub async fn exchange_code(&self, code: &str) -> Result<AccessToken> {
+        let mut form = vec![
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("code", code),
+        ];
+        if let Some(redirect_uri) = &self.redirect_uri {
+            form.push(("redirect_uri", redirect_uri));
+        }
+
+        let response = self.client.post(&self.endpoints.token_url).form(&form).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            bail!("token exchange failed with {status}: {body}");
+        }
+
+        let body: serde_json::Value = response.json().await?;
+        if let Some(error) = body.get("error").and_then(|e| e.as_str()) {
+            bail!("token exchange failed: {error}");
+        }
+        serde_json::from_value(body).context("invalid access token response")
+    }
+
+    /// Revoke an access token
+    pub async fn revoke(&self, access_token: &str) -> Result<()> {
+        let form = [
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("access_token", access_token),
+        ];
+        self.client
+            .post(&self.endpoints.revoke_url)
+            .form(&form)
+            .send()
+            .await?
+            .error_for_status()
+            .context("token revocation failed")?;
+        Ok(())
+    }
+}
+
+/// Local HTTP listener receiving the OAuth redirect, for CLI apps
+///
+/// The app registered in Todoist must use [`LoopbackListener::redirect_uri`] as its redirect URI.
+#[derive(Debug)]
+pub struct LoopbackListener {
+    listener: TcpListener,
+    port: u16,
+}
+
+impl LoopbackListener {
+    /// Listen on `127.0.0.1`, or on a random free port when `port` is 0
+    pub async fn bind(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .with_context(|| format!("failed to listen on port {port}"))?;
+        let port = listener.local_addr()?.port();
+        Ok(Self { listener, port })
+    }
+
+    /// URI the browser is redirected to, on the same `127.0.0.1` address the listener is bound to
+    pub fn redirect_uri(&self) -> String {
+        format!("http://127.0.0.1:{}/", self.port)
+    }
+
+    /// Wait up to `timeout` for the redirect and return the authorization code
+    ///
+    /// Fails if the user denied access, if the state does not match the one sent in the
+    /// authorize URL, or if no redirect arrives in time.
+    pub async fn wait_for_code(self, expected_state: &str, timeout: Duration) -> Result<String> {
+        tokio::time::timeout(timeout, self.accept_code(expected_state))
+            .await
+            .map_err(|_| anyhow!("no OAuth redirect received within {timeout:?}"))?
+    }
+
+    async fn accept_code(self, expected_state: &str) -> Result<String> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+            let mut request_line = String::new();
+            BufReader::new(&mut stream).read_line(&mut request_line).await?;
+
+            // Browsers also ask for things like /favicon.ico
+            let Some(params) = callback_params(&request_line) else {
+                stream
+                    .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .await?;
+                continue;
+            };
+
+            let result = check_callback(&params, expected_state);
+            let message = match &result {
+                Ok(_) => "Authorization complete, you can close this window.",
+                Err(_) => "Authorization failed, you can close this window.",
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{message}",
+                message.len()
+            );
+            stream.write_all(response.as_bytes()).await?;
+            stream.shutdown().await.ok();
+            return result;
+        }
+    }
+}
+
+/// Query parameters of a redirect request line, if it carries a code or an error
+fn callback_params(request_line: &str) -> Option<Vec<(String, String)>> {
+    let target = request_line.split_whitespace().nth(1)?;
+    let url = Url::parse(&format!("http://localhost{target}")).ok()?;
+    let params: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    params
+        .iter()
+        .any(|(key, _)| key == "code" || key == "error")
+        .then_some(params)
+}
+
+fn check_callback(params: &[(String, String)], expected_state: &str) -> Result<String> {
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    if let Some(error) = param("error") {
+        bail!("authorization denied: {error}");
+    }
+    if param("state") != Some(expected_state) {
+        bail!("state mismatch in OAuth redirect, the request may have been forged");
+    }
+    param("code")
+        .map(str::to_string)
+        .context("OAuth redirect without a code")
+}
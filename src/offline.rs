@@ -0,0 +1,790 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::models::*;
+use crate::store::{Change, StoreEvent, StoreSnapshot, TodoistStore};
+use crate::sync::FULL_SYNC_TOKEN;
+use crate::wrapper::TodoistWrapper;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS projects (id TEXT PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS sections (id TEXT PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS tasks (id TEXT PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS labels (id TEXT PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS comments (id TEXT PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS outbox (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        mutation TEXT NOT NULL,
+        base TEXT,
+        queued_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE TABLE IF NOT EXISTS id_map (local_id TEXT PRIMARY KEY, real_id TEXT NOT NULL);
+";
+
+/// A write made while offline, replayed later through [`TodoistWrapper`]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Mutation {
+    CreateTask {
+        args: CreateTaskArgs,
+    },
+    UpdateTask {
+        task_id: String,
+        args: UpdateTaskArgs,
+    },
+    CompleteTask {
+        task_id: String,
+    },
+    ReopenTask {
+        task_id: String,
+    },
+    DeleteTask {
+        task_id: String,
+    },
+    CreateProject {
+        args: CreateProjectArgs,
+    },
+    UpdateProject {
+        project_id: String,
+        args: UpdateProjectArgs,
+    },
+    DeleteProject {
+        project_id: String,
+    },
+    CreateSection {
+        args: CreateSectionArgs,
+    },
+    UpdateSection {
+        section_id: String,
+        args: UpdateSectionArgs,
+    },
+    DeleteSection {
+        section_id: String,
+    },
+    CreateLabel {
+        args: CreateLabelArgs,
+    },
+    UpdateLabel {
+        label_id: String,
+        args: UpdateLabelArgs,
+    },
+    DeleteLabel {
+        label_id: String,
+    },
+    CreateComment {
+        args: CreateCommentArgs,
+    },
+    UpdateComment {
+        comment_id: String,
+        args: UpdateCommentArgs,
+    },
+    DeleteComment {
+        comment_id: String,
+    },
+}
+
+impl Mutation {
+    /// Existing resource modified by this mutation, if any
+    ///
+    /// Creations and task reopening have no target in the replica, so they never conflict.
+    pub fn target(&self) -> Option<(ResourceKind, &str)> {
+        match self {
+            Mutation::UpdateTask { task_id, .. }
+            | Mutation::CompleteTask { task_id }
+            | Mutation::DeleteTask { task_id } => Some((ResourceKind::Task, task_id)),
+            Mutation::UpdateProject { project_id, .. } | Mutation::DeleteProject { project_id } => {
+                Some((ResourceKind::Project, project_id))
+            }
+            Mutation::UpdateSection { section_id, .. } | Mutation::DeleteSection { section_id } => {
+                Some((ResourceKind::Section, section_id))
+            }
+            Mutation::UpdateLabel { label_id, .. } | Mutation::DeleteLabel { label_id } => {
+                Some((ResourceKind::Label, label_id))
+            }
+            Mutation::UpdateComment { comment_id, .. } | Mutation::DeleteComment { comment_id } => {
+                Some((ResourceKind::Comment, comment_id))
+            }
+            Mutation::CreateTask { .. }
+            | Mutation::ReopenTask { .. }
+            | Mutation::CreateProject { .. }
+            | Mutation::CreateSection { .. }
+            | Mutation::CreateLabel { .. }
+            | Mutation::CreateComment { .. } => None,
+        }
+    }
+
+    /// Send the mutation and return the ID of the object it created, if any
+    async fn apply(&self, wrapper: &TodoistWrapper) -> Result<Option<String>> {
+        match self {
+            Mutation::CreateTask { args } => wrapper.create_task(args).await.map(|task| Some(task.id)),
+            Mutation::UpdateTask { task_id, args } => wrapper.update_task(task_id, args).await.map(|_| None),
+            Mutation::CompleteTask { task_id } => wrapper.complete_task(task_id).await.map(|_| None),
+            Mutation::ReopenTask { task_id } => wrapper.reopen_task(task_id).await.map(|_| None),
+            Mutation::DeleteTask { task_id } => wrapper.delete_task(task_id).await.map(|_| None),
+            Mutation::CreateProject { args } => wrapper.create_project(args).await.map(|project| Some(project.id)),
+            Mutation::UpdateProject { project_id, args } => {
+                wrapper.update_project(project_id, args).await.map(|_| None)
+            }
+            Mutation::DeleteProject { project_id } => wrapper.delete_project(project_id).await.map(|_| None),
+            Mutation::CreateSection { args } => wrapper.create_section(args).await.map(|section| Some(section.id)),
+            Mutation::UpdateSection { section_id, args } => {
+                wrapper.update_section(section_id, args).await.map(|_| None)
+            }
+            Mutation::DeleteSection { section_id } => wrapper.delete_section(section_id).await.map(|_| None),
+            Mutation::CreateLabel { args } => wrapper.create_label(args).await.map(|label| Some(label.id)),
+            Mutation::UpdateLabel { label_id, args } => wrapper.update_label(label_id, args).await.map(|_| None),
+            Mutation::DeleteLabel { label_id } => wrapper.delete_label(label_id).await.map(|_| None),
+            Mutation::CreateComment { args } => wrapper.create_comment(args).await.map(|comment| Some(comment.id)),
+            Mutation::UpdateComment { comment_id, args } => {
+                wrapper.update_comment(comment_id, args).await.map(|_| None)
+            }
+            Mutation::DeleteComment { comment_id } => wrapper.delete_comment(comment_id).await.map(|_| None),
+        }
+    }
+
+    /// Replace the local IDs of objects already created on the server with their real IDs
+    fn with_real_ids(&self, real_ids: &HashMap<String, String>) -> Result<Mutation> {
+        if real_ids.is_empty() {
+            return Ok(self.clone());
+        }
+        let mut value = serde_json::to_value(self)?;
+        replace_local_ids(&mut value, real_ids);
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Apply the mutation to a replica, giving created objects the ID `local_id`
+    ///
+    /// Reopened tasks are not in the replica, so reopening only shows once replayed.
+    fn apply_locally(&self, snapshot: &mut StoreSnapshot, local_id: &str, queued_at: &str) {
+        match self {
+            Mutation::CreateTask { args } => {
+                let inbox = snapshot.projects.iter().find(|project| project.is_inbox_project);
+                let project_id = args
+                    .project_id
+                    .clone()
+                    .or_else(|| inbox.map(|project| project.id.clone()))
+                    .unwrap_or_default();
+                snapshot.tasks.push(Task {
+                    id: local_id.to_string(),
+                    content: args.content.clone(),
+                    description: args.description.clone().unwrap_or_default(),
+                    project_id,
+                    section_id: args.section_id.clone(),
+                    parent_id: args.parent_id.clone(),
+                    order: args.order.unwrap_or_default(),
+                    priority: args.priority.unwrap_or(1),
+                    is_completed: false,
+                    labels: args.labels.clone().unwrap_or_default(),
+                    created_at: queued_at.to_string(),
+                    due: local_due(&args.due_string, &args.due_date, &args.due_datetime),
+                    deadline: args.deadline_date.clone().map(|date| Deadline { date }),
+                    duration: local_duration(args.duration, &args.duration_unit),
+                    assignee_id: args.assignee_id.clone(),
+                    url: String::new(),
+                    comment_count: 0,
+                });
+            }
+            Mutation::UpdateTask { task_id, args } => {
+                if let Some(task) = snapshot.tasks.iter_mut().find(|task| &task.id == task_id) {
+                    set(&mut task.content, &args.content);
+                    set(&mut task.description, &args.description);
+                    set(&mut task.priority, &args.priority);
+                    set(&mut task.labels, &args.labels);
+                    if let Some(due) = local_due(&args.due_string, &args.due_date, &args.due_datetime) {
+                        task.due = Some(due);
+                    }
+                    if let Some(date) = &args.deadline_date {
+                        task.deadline = Some(Deadline { date: date.clone() });
+                    }
+                    if let Some(duration) = local_duration(args.duration, &args.duration_unit) {
+                        task.duration = Some(duration);
+                    }
+                    if let Some(assignee_id) = &args.assignee_id {
+                        task.assignee_id = Some(assignee_id.clone());
+                    }
+                }
+            }
+            // Completing or deleting a task does the same to its subtasks
+            Mutation::CompleteTask { task_id } | Mutation::DeleteTask { task_id } => {
+                remove_tasks(snapshot, |task| &task.id == task_id);
+            }
+            Mutation::ReopenTask { .. } => {}
+            Mutation::CreateProject { args } => snapshot.projects.push(Project {
+                id: local_id.to_string(),
+                name: args.name.clone(),
+                comment_count: 0,
+                order: snapshot
+                    .projects
+                    .iter()
+                    .map(|project| project.order + 1)
+                    .max()
+                    .unwrap_or(1),
+                color: args.color.clone().unwrap_or_else(|| "charcoal".to_string()),
+                is_shared: false,
+                is_favorite: args.is_favorite.unwrap_or(false),
+                is_inbox_project: false,
+                is_team_inbox: false,
+                view_style: args.view_style.clone().unwrap_or_else(|| "list".to_string()),
+                url: String::new(),
+                parent_id: args.parent_id.clone(),
+            }),
+            Mutation::UpdateProject { project_id, args } => {
+                if let Some(project) = snapshot.projects.iter_mut().find(|project| &project.id == project_id) {
+                    set(&mut project.name, &args.name);
+                    set(&mut project.color, &args.color);
+                    set(&mut project.is_favorite, &args.is_favorite);
+                    set(&mut project.view_style, &args.view_style);
+                }
+            }
+            Mutation::DeleteProject { project_id } => {
+                snapshot.projects.retain(|project| &project.id != project_id);
+                snapshot.sections.retain(|section| &section.project_id != project_id);
+                snapshot
+                    .comments
+                    .retain(|comment| comment.project_id.as_ref() != Some(project_id));
+                remove_tasks(snapshot, |task| &task.project_id == project_id);
+            }
+            Mutation::CreateSection { args } => snapshot.sections.push(Section {
+                id: local_id.to_string(),
+                name: args.name.clone(),
+                project_id: args.project_id.clone(),
+                order: args.order.unwrap_or_default(),
+                url: String::new(),
+            }),
+            Mutation::UpdateSection { section_id, args } => {
+                if let Some(section) = snapshot.sections.iter_mut().find(|section| &section.id == section_id) {
+                    section.name = args.name.clone();
+                }
+            }
+            Mutation::DeleteSection { section_id } => {
+                snapshot.sections.retain(|section| &section.id != section_id);
+                remove_tasks(snapshot, |task| task.section_id.as_ref() == Some(section_id));
+            }
+            Mutation::CreateLabel { args } => snapshot.labels.push(Label {
+                id: local_id.to_string(),
+                name: args.name.clone(),
+                color: args.color.clone().unwrap_or_else(|| "charcoal".to_string()),
+                order: args.order.unwrap_or_default(),
+                is_favorite: args.is_favorite.unwrap_or(false),
+            }),
+            Mutation::UpdateLabel { label_id, args } => {
+                if let Some(label) = snapshot.labels.iter_mut().find(|label| &label.id == label_id) {
+                    set(&mut label.name, &args.name);
+                    set(&mut label.color, &args.color);
+                    set(&mut label.order, &args.order);
+                    set(&mut label.is_favorite, &args.is_favorite);
+                }
+            }
+            Mutation::DeleteLabel { label_id } => snapshot.labels.retain(|label| &label.id != label_id),
+            Mutation::CreateComment { args } => snapshot.comments.push(Comment {
+                id: local_id.to_string(),
+                content: args.content.clone(),
+                posted_at: queued_at.to_string(),
+                attachment: args.attachment.clone(),
+                project_id: args.project_id.clone(),
+                task_id: args.task_id.clone(),
+            }),
+            Mutation::UpdateComment { comment_id, args } => {
+                if let Some(comment) = snapshot.comments.iter_mut().find(|comment| &comment.id == comment_id) {
+                    comment.content = args.content.clone();
+                }
+            }
+            Mutation::DeleteComment { comment_id } => snapshot.comments.retain(|comment| &comment.id != comment_id),
+        }
+    }
+}
+
+fn set<T: Clone>(field: &mut T, value: &Option<T>) {
+    if let Some(value) = value {
+        *field = value.clone();
+    }
+}
+
+/// Due date of a queued write; natural-language dates are only known once replayed
+fn local_due(string: &Option<String>, date: &Option<String>, datetime: &Option<String>) -> Option<Due> {
+    let date = date
+        .clone()
+        .or_else(|| datetime.as_ref().map(|datetime| datetime.chars().take(10).collect()))?;
+    Some(Due {
+        string: string.clone().unwrap_or_else(|| date.clone()),
+        date,
+        is_recurring: false,
+        datetime: datetime.clone(),
+        timezone: None,
+    })
+}
+
+fn local_duration(amount: Option<i32>, unit: &Option<String>) -> Option<Duration> {
+    Some(Duration {
+        amount: amount?,
+        unit: unit.clone().unwrap_or_else(|| "minute".to_string()),
+    })
+}
+
+/// Replace local IDs in every `*_id` field, however deeply nested
+fn replace_local_ids(value: &mut Value, real_ids: &HashMap<String, String>) {
+    if let Value::Object(fields) = value {
+        for (key, field) in fields.iter_mut() {
+            match field {
+                Value::String(id) if key.ends_with("_id") => {
+                    if let Some(real_id) = real_ids.get(id.as_str()) {
+                        *id = real_id.clone();
+                    }
+                }
+                _ => replace_local_ids(field, real_ids),
+            }
+        }
+    }
+}
+
+/// Remove the matching tasks with their subtasks and comments
+fn remove_tasks(snapshot: &mut StoreSnapshot, matches: impl Fn(&Task) -> bool) {
+    let mut removed: HashSet<String> = snapshot
+        .tasks
+        .iter()
+        .filter(|task| matches(task))
+        .map(|task| task.id.clone())
+        .collect();
+    loop {
+        let subtasks: Vec<String> = snapshot
+            .tasks
+            .iter()
+            .filter(|task| !removed.contains(&task.id))
+            .filter(|task| {
+                task.parent_id
+                    .as_ref()
+                    .is_some_and(|parent_id| removed.contains(parent_id))
+            })
+            .map(|task| task.id.clone())
+            .collect();
+        if subtasks.is_empty() {
+            break;
+        }
+        removed.extend(subtasks);
+    }
+    snapshot.tasks.retain(|task| !removed.contains(&task.id));
+    snapshot.comments.retain(|comment| {
+        !comment
+            .task_id
+            .as_ref()
+            .is_some_and(|task_id| removed.contains(task_id))
+    });
+}
+
+/// Kind of resource kept in the offline store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Task,
+    Project,
+    Section,
+    Label,
+    Comment,
+}
+
+/// A resource of any kind, as stored in the replica
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum Resource {
+    Task(Task),
+    Project(Project),
+    Section(Section),
+    Label(Label),
+    Comment(Comment),
+}
+
+/// A queued mutation, with the state of its target when it was queued
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub mutation: Mutation,
+    pub base: Option<Resource>,
+    pub queued_at: String,
+}
+
+/// A queued mutation whose target changed remotely after it was queued, or no longer exists
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub entry: OutboxEntry,
+    /// Current remote state, `None` if the target was deleted or completed remotely
+    pub remote: Option<Resource>,
+}
+
+/// How to handle a [`Conflict`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Replay the local mutation, overwriting the remote changes
+    ///
+    /// A mutation whose target no longer exists cannot be replayed and is dropped instead, as with
+    /// [`Resolution::KeepRemote`].
+    ApplyLocal,
+    /// Drop the local mutation and keep the remote state
+    KeepRemote,
+    /// Leave the mutation in the outbox for manual handling
+    Defer,
+}
+
+/// Strategy deciding how conflicts are resolved during [`OfflineStore::sync_outbox`]
+pub trait ConflictResolver: Send + Sync {
+    fn resolve(&self, conflict: &Conflict) -> Resolution;
+}
+
+impl<F> ConflictResolver for F
+where
+    F: Fn(&Conflict) -> Resolution + Send + Sync,
+{
+    fn resolve(&self, conflict: &Conflict) -> Resolution {
+        self(conflict)
+    }
+}
+
+/// Always replay local mutations
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalWins;
+
+impl ConflictResolver for LocalWins {
+    fn resolve(&self, _conflict: &Conflict) -> Resolution {
+        Resolution::ApplyLocal
+    }
+}
+
+/// Always keep the remote state
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RemoteWins;
+
+impl ConflictResolver for RemoteWins {
+    fn resolve(&self, _conflict: &Conflict) -> Resolution {
+        Resolution::KeepRemote
+    }
+}
+
+/// Leave every conflicting mutation in the outbox
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeferConflicts;
+
+impl ConflictResolver for DeferConflicts {
+    fn resolve(&self, _conflict: &Conflict) -> Resolution {
+        Resolution::Defer
+    }
+}
+
+/// Outcome of [`OfflineStore::sync_outbox`]
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    /// Outbox entries replayed successfully
+    pub applied: Vec<i64>,
+    /// Conflicts detected, with the resolution that was applied
+    pub conflicts: Vec<(Conflict, Resolution)>,
+    /// Entry whose replay failed, which stopped the replay
+    pub failed: Option<(i64, String)>,
+    /// Entries still in the outbox
+    pub remaining: usize,
+}
+
+/// SQLite-backed replica of an account with an outbox for offline writes
+///
+/// The replica is loaded from disk when opened, so reads work without network access.
+/// Writes are queued with [`OfflineStore::queue`] and replayed with
+/// [`OfflineStore::sync_outbox`] once connectivity returns. SQLite calls are blocking but short.
+///
+/// ```rust,no_run
+/// use todoist_api::offline::{Mutation, OfflineStore, RemoteWins};
+/// use todoist_api::TodoistWrapper;
+///
+/// # async fn example() -> todoist_api::Result<()> {
+/// let wrapper = TodoistWrapper::new("your-api-token".to_string());
+/// let mut offline = OfflineStore::open("todoist.db", wrapper)?.with_resolver(RemoteWins);
+/// println!("{} cached tasks", offline.store().tasks().len());
+///
+/// offline.queue(Mutation::CompleteTask { task_id: "123".to_string() })?;
+/// let report = offline.sync_outbox().await?;
+/// println!("{} changes replayed", report.applied.len());
+/// # Ok(())
+/// # }
+/// ```
+pub struct OfflineStore {
+    conn: Connection,
+    /// Replica as last synced, used to detect conflicts
+    remote: TodoistStore,
+    /// Replica with the outbox applied on top, as seen by readers
+    store: TodoistStore,
+    resolver: Box<dyn ConflictResolver>,
+}
+
+impl OfflineStore {
+    /// Open or create a store at `path`
+    pub fn open(path: impl AsRef<Path>, wrapper: TodoistWrapper) -> Result<Self> {
+        let conn = Connection::open(path.as_ref())
+            .with_context(|| format!("failed to open offline store {}", path.as_ref().display()))?;
+        Self::from_connection(conn, wrapper)
+    }
+
+    /// Create a store kept in memory only
+    pub fn open_in_memory(wrapper: TodoistWrapper) -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?, wrapper)
+    }
+
+    fn from_connection(conn: Connection, wrapper: TodoistWrapper) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        let snapshot = StoreSnapshot {
+            sync_token: conn
+                .query_row("SELECT value FROM meta WHERE key = 'sync_token'", [], |row| row.get(0))
+                .optional()?
+                .unwrap_or_else(|| FULL_SYNC_TOKEN.to_string()),
+            projects: load_table(&conn, "projects")?,
+            sections: load_table(&conn, "sections")?,
+            tasks: load_table(&conn, "tasks")?,
+            labels: load_table(&conn, "labels")?,
+            comments: load_table(&conn, "comments")?,
+        };
+        let mut offline = Self {
+            conn,
+            store: TodoistStore::from_snapshot(wrapper.clone(), snapshot.clone()),
+            remote: TodoistStore::from_snapshot(wrapper, snapshot),
+            resolver: Box::new(DeferConflicts),
+        };
+        offline.update_view()?;
+        Ok(offline)
+    }
+
+    /// Use a custom conflict resolution strategy (conflicts are deferred by default)
+    #[must_use]
+    pub fn with_resolver(mut self, resolver: impl ConflictResolver + 'static) -> Self {
+        self.resolver = Box::new(resolver);
+        self
+    }
+
+    /// The local replica, with queued mutations applied
+    ///
+    /// Objects created while offline have the temporary ID `local-{outbox_id}` until the outbox
+    /// is replayed, and later mutations may refer to them by that ID. Subscribers receive an event for every change, including queued mutations.
+    pub fn store(&self) -> &TodoistStore {
+        &self.store
+    }
+
+    /// The replica as last synced, without queued mutations
+    pub fn remote(&self) -> &TodoistStore {
+        &self.remote
+    }
+
+    /// Fetch remote changes and persist them
+    pub async fn refresh(&mut self) -> Result<Vec<StoreEvent>> {
+        let events = self.remote.refresh().await?;
+        self.persist(&events)?;
+        self.update_view()?;
+        Ok(events)
+    }
+
+    /// Queue a mutation for later replay and return its outbox ID
+    pub fn queue(&mut self, mutation: Mutation) -> Result<i64> {
+        let base = mutation
+            .target()
+            .and_then(|(kind, id)| current_resource(&self.remote, kind, id));
+        let base = base.map(|b| serde_json::to_string(&b)).transpose()?;
+        self.conn.execute(
+            "INSERT INTO outbox (mutation, base) VALUES (?1, ?2)",
+            params![serde_json::to_string(&mutation)?, base],
+        )?;
+        let entry_id = self.conn.last_insert_rowid();
+        self.update_view()?;
+        Ok(entry_id)
+    }
+
+    /// Queued mutations, oldest first
+    pub fn outbox(&self) -> Result<Vec<OutboxEntry>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT id, mutation, base, queued_at FROM outbox ORDER BY id")?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let (id, mutation, base, queued_at) = row?;
+            entries.push(OutboxEntry {
+                id,
+                mutation: serde_json::from_str(&mutation)?,
+                base: base.map(|b| serde_json::from_str(&b)).transpose()?,
+                queued_at,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Remove a mutation from the outbox without replaying it
+    pub fn discard(&mut self, entry_id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM outbox WHERE id = ?1", params![entry_id])?;
+        self.update_view()?;
+        Ok(())
+    }
+
+    /// Replay the outbox, resolving conflicts with the configured strategy
+    ///
+    /// The replica is refreshed first so conflicts are detected against the latest remote
+    /// state, then again once the outbox has been replayed. Replay stops at the first mutation
+    /// that fails; it stays in the outbox together with every later one.
+    ///
+    /// Once an object created offline exists on the server, its real ID replaces the local one in
+    /// every later mutation. The mapping is kept on disk until the outbox is empty, so a replay
+    /// interrupted midway resumes with the right IDs.
+    pub async fn sync_outbox(&mut self) -> Result<ReplayReport> {
+        self.refresh().await?;
+
+        let mut report = ReplayReport::default();
+        let mut real_ids = self.real_ids()?;
+        for entry in self.outbox()? {
+            // Objects created by the outbox may not be in the replica yet, and cannot conflict
+            let created_offline = entry.mutation.target().is_some_and(|(_, id)| real_ids.contains_key(id));
+            let mutation = entry.mutation.with_real_ids(&real_ids)?;
+            if let Some((kind, id)) = mutation.target().filter(|_| !created_offline) {
+                let remote = current_resource(&self.remote, kind, id);
+                if remote.is_none() || remote != entry.base {
+                    let conflict = Conflict {
+                        entry: entry.clone(),
+                        remote,
+                    };
+                    let resolution = match self.resolver.resolve(&conflict) {
+                        // Nothing is left to replay the mutation on
+                        Resolution::ApplyLocal if conflict.remote.is_none() => Resolution::KeepRemote,
+                        resolution => resolution,
+                    };
+                    report.conflicts.push((conflict, resolution));
+                    match resolution {
+                        Resolution::ApplyLocal => {}
+                        Resolution::KeepRemote => {
+                            self.discard(entry.id)?;
+                            continue;
+                        }
+                        Resolution::Defer => continue,
+                    }
+                }
+            }
+
+            match mutation.apply(self.remote.wrapper()).await {
+                Ok(created) => {
+                    if let Some(real_id) = created {
+                        let local_id = local_id(entry.id);
+                        self.conn.execute(
+                            "INSERT OR REPLACE INTO id_map (local_id, real_id) VALUES (?1, ?2)",
+                            params![local_id, real_id],
+                        )?;
+                        real_ids.insert(local_id, real_id);
+                    }
+                    self.discard(entry.id)?;
+                    report.applied.push(entry.id);
+                }
+                Err(e) => {
+                    report.failed = Some((entry.id, e.to_string()));
+                    break;
+                }
+            }
+        }
+
+        if !report.applied.is_empty() {
+            self.refresh().await?;
+        }
+        report.remaining = self.outbox()?.len();
+        if report.remaining == 0 {
+            self.conn.execute("DELETE FROM id_map", [])?;
+        }
+        Ok(report)
+    }
+
+    /// Real IDs of objects created by replayed outbox entries, by local ID
+    fn real_ids(&self) -> Result<HashMap<String, String>> {
+        let mut statement = self.conn.prepare("SELECT local_id, real_id FROM id_map")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Rebuild the view from the synced replica and the outbox
+    fn update_view(&mut self) -> Result<()> {
+        let mut snapshot = self.remote.snapshot();
+        let real_ids = self.real_ids()?;
+        for entry in self.outbox()? {
+            entry.mutation.with_real_ids(&real_ids)?.apply_locally(
+                &mut snapshot,
+                &local_id(entry.id),
+                &entry.queued_at,
+            );
+        }
+        self.store.replace(snapshot);
+        Ok(())
+    }
+
+    fn persist(&mut self, events: &[StoreEvent]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for event in events {
+            match event {
+                StoreEvent::Task(change) => persist_change(&tx, "tasks", change, |t| &t.id)?,
+                StoreEvent::Project(change) => persist_change(&tx, "projects", change, |p| &p.id)?,
+                StoreEvent::Section(change) => persist_change(&tx, "sections", change, |s| &s.id)?,
+                StoreEvent::Label(change) => persist_change(&tx, "labels", change, |l| &l.id)?,
+                StoreEvent::Comment(change) => persist_change(&tx, "comments", change, |c| &c.id)?,
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('sync_token', ?1)",
+            params![self.remote.sync_token()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Temporary ID of the object created by an outbox entry
+fn local_id(entry_id: i64) -> String {
+    format!("local-{entry_id}")
+}
+
+fn current_resource(store: &TodoistStore, kind: ResourceKind, id: &str) -> Option<Resource> {
+    match kind {
+        ResourceKind::Task => store.task(id).cloned().map(Resource::Task),
+        ResourceKind::Project => store.project(id).cloned().map(Resource::Project),
+        ResourceKind::Section => store.section(id).cloned().map(Resource::Section),
+        ResourceKind::Label => store.label(id).cloned().map(Resource::Label),
+        ResourceKind::Comment => store.comment(id).cloned().map(Resource::Comment),
+    }
+}
+
+fn load_table<T: DeserializeOwned>(conn: &Connection, table: &str) -> Result<Vec<T>> {
+    let mut statement = conn.prepare(&format!("SELECT data FROM {table}"))?;
+    let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+    let mut values = Vec::new();
+    for row in rows {
+        values.push(serde_json::from_str(&row?)?);
+    }
+    Ok(values)
+}
+
+fn persist_change<T: Serialize>(
+    conn: &Connection,
+    table: &str,
+    change: &Change<T>,
+    id: impl Fn(&T) -> &String,
+) -> Result<()> {
+    match change {
+        Change::Created(value) | Change::Updated { after: value, .. } => {
+            conn.execute(
+                &format!("INSERT OR REPLACE INTO {table} (id, data) VALUES (?1, ?2)"),
+                params![id(value), serde_json::to_string(value)?],
+            )?;
+        }
+        Change::Completed(value) | Change::Deleted(value) => {
+            conn.execute(&format!("DELETE FROM {table} WHERE id = ?1"), params![id(value)])?;
+        }
+    }
+    Ok(())
+}
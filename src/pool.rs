@@ -0,0 +1,197 @@
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::credentials::{CredentialProvider, StaticToken};
+use crate::models::*;
+use crate::rate_limit::RateLimit;
+use crate::wrapper::{TodoistWrapper, TODOIST_API_BASE, TODOIST_SYNC_API_BASE};
+
+/// Number of accounts queried at the same time by default in fan-out helpers
+pub const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+/// Clients for many Todoist accounts, keyed by an account name of your choice
+///
+/// All clients share one HTTP connection pool, while each account keeps its own rate-limit
+/// budget. Fan-out helpers query every account with bounded parallelism and report errors per
+/// account, so one failing account never hides the results of the others.
+///
+/// ```rust,no_run
+/// use todoist_api::TodoistPool;
+///
+/// # async fn run() {
+/// let mut pool = TodoistPool::new().with_max_concurrency(4);
+/// pool.add_token("alice", "alice-token".to_string());
+/// pool.add_token("bob", "bob-token".to_string());
+///
+/// for (account, tasks) in pool.get_tasks_all().await {
+///     match tasks {
+///         Ok(tasks) => println!("{account}: {} tasks", tasks.len()),
+///         Err(error) => eprintln!("{account}: {error}"),
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TodoistPool {
+    client: Client,
+    accounts: BTreeMap<String, TodoistWrapper>,
+    rate_limit: RateLimit,
+    max_concurrency: usize,
+    base_url: String,
+    sync_base_url: String,
+}
+
+impl Default for TodoistPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TodoistPool {
+    /// Create an empty pool talking to the Todoist API
+    pub fn new() -> Self {
+        Self::with_base_urls(TODOIST_API_BASE, TODOIST_SYNC_API_BASE)
+    }
+
+    /// Create an empty pool talking to custom REST and Sync API base URLs
+    pub fn with_base_urls(base_url: &str, sync_base_url: &str) -> Self {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+        Self {
+            client,
+            accounts: BTreeMap::new(),
+            rate_limit: RateLimit::default(),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            base_url: base_url.to_string(),
+            sync_base_url: sync_base_url.to_string(),
+        }
+    }
+
+    /// Rate limit applied to each account added afterwards
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = limit;
+        self
+    }
+
+    /// Maximum number of accounts queried at the same time by fan-out helpers
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Add an account, replacing any account with the same key
+    pub fn add_account(
+        &mut self,
+        key: impl Into<String>,
+        credentials: impl CredentialProvider + 'static,
+    ) -> &TodoistWrapper {
+        let wrapper = TodoistWrapper::with_client(
+            self.client.clone(),
+            Arc::new(credentials),
+            &self.base_url,
+            &self.sync_base_url,
+        )
+        .with_rate_limit(self.rate_limit);
+        let key = key.into();
+        self.accounts.insert(key.clone(), wrapper);
+        &self.accounts[&key]
+    }
+
+    /// Add an account with a fixed API token
+    pub fn add_token(&mut self, key: impl Into<String>, api_token: String) -> &TodoistWrapper {
+        self.add_account(key, StaticToken::new(api_token))
+    }
+
+    /// Remove an account
+    pub fn remove_account(&mut self, key: &str) -> Option<TodoistWrapper> {
+        self.accounts.remove(key)
+    }
+
+    /// Client for an account
+    pub fn account(&self, key: &str) -> Option<&TodoistWrapper> {
+        self.accounts.get(key)
+    }
+
+    /// Keys of all accounts, in order
+    pub fn accounts(&self) -> impl Iterator<Item = &str> {
+        self.accounts.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Run an operation for every account with bounded parallelism
+    ///
+    /// Each account gets its own result; an error or a panic in one account does not affect the
+    /// others.
+    pub async fn for_each<F, Fut, T>(&self, operation: F) -> BTreeMap<String, Result<T>>
+    where
+        F: Fn(TodoistWrapper) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let operation = Arc::new(operation);
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrency));
+        let mut tasks = JoinSet::new();
+        let mut keys = BTreeMap::new();
+
+        for (key, wrapper) in &self.accounts {
+            let (operation, semaphore, wrapper) = (operation.clone(), semaphore.clone(), wrapper.clone());
+            let handle = tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await?;
+                operation(wrapper).await
+            });
+            keys.insert(handle.id(), key.clone());
+        }
+
+        let mut results = BTreeMap::new();
+        while let Some(joined) = tasks.join_next_with_id().await {
+            match joined {
+                Ok((id, result)) => {
+                    results.insert(keys[&id].clone(), result);
+                }
+                Err(error) => {
+                    let key = keys[&error.id()].clone();
+                    results.insert(
+                        key.clone(),
+                        Err(anyhow!("operation for account {key} panicked: {error}")),
+                    );
+                }
+            }
+        }
+        results
+    }
+
+    /// Active tasks of every account
+    pub async fn get_tasks_all(&self) -> BTreeMap<String, Result<Vec<Task>>> {
+        self.for_each(|todoist| async move { todoist.get_tasks().await }).await
+    }
+
+    /// Projects of every account
+    pub async fn get_projects_all(&self) -> BTreeMap<String, Result<Vec<Project>>> {
+        self.for_each(|todoist| async move { todoist.get_projects().await })
+            .await
+    }
+
+    /// Tasks matching a filter query in every account
+    pub async fn get_tasks_by_filter_all(&self, args: &TaskFilterArgs) -> BTreeMap<String, Result<Vec<Task>>> {
+        let args = Arc::new(args.clone());
+        self.for_each(move |todoist| {
+            let args = args.clone();
+            async move { todoist.get_tasks_by_filter(&args).await }
+        })
+        .await
+    }
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::Task;
+
+/// Options for [`crate::TodoistWrapper::quick_add`]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct QuickAddOptions {
+    /// Comment added to the new task
+    pub note: Option<String>,
+    /// Natural-language reminder, such as `tomorrow 2pm`
+    pub reminder: Option<String>,
+    /// Add the default reminder when the text sets a due time
+    pub auto_reminder: Option<bool>,
+}
+
+/// A task created by quick add, with the parts of the text Todoist interpreted
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickAddResult {
+    pub task: Task,
+    /// Project token, such as `#Work`, when it names the project the task was created in
+    pub project: Option<String>,
+    /// Label tokens, such as `@phone`
+    pub labels: Vec<String>,
+    /// Priority token, such as `p2`
+    pub priority: Option<String>,
+    /// Due date as understood by Todoist, such as `tomorrow 3pm`
+    pub due: Option<String>,
+}
+
+impl QuickAddResult {
+    /// Match the tokens of the quick add text against the created task
+    ///
+    /// Todoist strips the tokens it interprets from the task content, so a `#project`, `@label`
+    /// or `p1`-`p4` token counts as interpreted when it no longer appears in the content. The
+    /// project token is only a candidate here; [`crate::TodoistWrapper::quick_add`] keeps it once
+    /// it matches the name of the task's project.
+    pub(crate) fn interpret(text: &str, task: Task) -> Self {
+        let remaining: Vec<&str> = task.content.split_whitespace().collect();
+        let interpreted = |token: &&str| !remaining.contains(token);
+
+        let tokens: Vec<&str> = text.split_whitespace().filter(interpreted).collect();
+        let project = tokens
+            .iter()
+            .find(|token| token.len() > 1 && token.starts_with('#'))
+            .map(|token| token.to_string());
+        let labels = tokens
+            .iter()
+            .filter(|token| {
+                token.len() > 1
+                    && token.starts_with('@')
+                    && task.labels.iter().any(|label| label.eq_ignore_ascii_case(&token[1..]))
+            })
+            .map(|token| token.to_string())
+            .collect();
+        let priority = tokens
+            .iter()
+            .find(|token| priority_token(token) == Some(task.priority))
+            .map(|token| token.to_string());
+        let due = task.due.as_ref().map(|due| due.string.clone());
+
+        QuickAddResult {
+            task,
+            project,
+            labels,
+            priority,
+            due,
+        }
+    }
+}
+
+/// API priority of a `p1`-`p4` token, where `p1` is the most urgent
+fn priority_token(token: &str) -> Option<i32> {
+    match token.to_ascii_lowercase().as_str() {
+        "p1" => Some(4),
+        "p2" => Some(3),
+        "p3" => Some(2),
+        "p4" => Some(1),
+        _ => None,
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Maximum number of requests allowed in a sliding time window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub max_requests: usize,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    pub fn new(max_requests: usize, per: Duration) -> Self {
+        Self { max_requests, per }
+    }
+}
+
+impl Default for RateLimit {
+    /// The documented Todoist limit of 450 requests per user every 15 minutes
+    fn default() -> Self {
+        Self::new(450, Duration::from_secs(15 * 60))
+    }
+}
+
+/// Waits until a request fits in the budget of a [`RateLimit`]
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    sent: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            sent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Wait for a free slot and record a request in it
+    pub(crate) async fn acquire(&self) {
+        // Holding the lock while sleeping makes waiting requests go out in order
+        let mut sent = self.sent.lock().await;
+        loop {
+            let now = Instant::now();
+            while sent.front().is_some_and(|at| now.duration_since(*at) >= self.limit.per) {
+                sent.pop_front();
+            }
+            if sent.len() < self.limit.max_requests.max(1) {
+                sent.push_back(now);
+                return;
+            }
+            let oldest = *sent.front().expect("window is full");
+            tokio::time::sleep_until(oldest + self.limit.per).await;
+        }
+    }
+}
@@ -0,0 +1,532 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tokio::sync::broadcast;
+
+use crate::filter::{FilterContext, FilterQuery};
+use crate::models::*;
+use crate::sync::{SyncResponse, FULL_SYNC_TOKEN};
+use crate::wrapper::TodoistWrapper;
+
+/// Resource types kept in a [`TodoistStore`]
+pub const STORE_RESOURCE_TYPES: &[&str] = &["items", "projects", "sections", "labels", "notes", "project_notes"];
+
+/// Number of events buffered for slow subscribers before they start lagging
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// A change detected on a single resource
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<T> {
+    Created(T),
+    Updated {
+        before: T,
+        after: T,
+    },
+    /// Only emitted for tasks, which leave the replica once completed
+    Completed(T),
+    Deleted(T),
+}
+
+/// A change detected by [`TodoistStore::refresh`]
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum StoreEvent {
+    Task(Change<Task>),
+    Project(Change<Project>),
+    Section(Change<Section>),
+    Label(Change<Label>),
+    Comment(Change<Comment>),
+}
+
+/// Serializable content of a [`TodoistStore`], used to persist and reload a replica
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StoreSnapshot {
+    pub sync_token: String,
+    pub projects: Vec<Project>,
+    pub sections: Vec<Section>,
+    pub tasks: Vec<Task>,
+    pub labels: Vec<Label>,
+    pub comments: Vec<Comment>,
+}
+
+/// Local replica of a Todoist account, kept up to date with incremental syncs
+///
+/// The replica holds active tasks, projects, sections, personal labels and comments. Each call
+/// to [`TodoistStore::refresh`] only downloads what changed since the previous one and returns
+/// the differences as [`StoreEvent`]s, which are also broadcast to subscribers.
+///
+/// ```rust,no_run
+/// use todoist_api::{TodoistStore, TodoistWrapper};
+///
+/// # async fn example() -> todoist_api::Result<()> {
+/// let mut store = TodoistStore::new(TodoistWrapper::new("your-api-token".to_string()));
+/// store.refresh().await?;
+/// for task in store.tasks_by_label("work") {
+///     println!("{}", task.content);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct TodoistStore {
+    wrapper: TodoistWrapper,
+    sync_token: String,
+    tasks: HashMap<String, Task>,
+    projects: HashMap<String, Project>,
+    sections: HashMap<String, Section>,
+    labels: HashMap<String, Label>,
+    comments: HashMap<String, Comment>,
+    index: TaskIndex,
+    events: broadcast::Sender<StoreEvent>,
+}
+
+impl TodoistStore {
+    /// Create an empty store; call [`TodoistStore::refresh`] to populate it
+    #[must_use]
+    pub fn new(wrapper: TodoistWrapper) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            wrapper,
+            sync_token: FULL_SYNC_TOKEN.to_string(),
+            tasks: HashMap::new(),
+            projects: HashMap::new(),
+            sections: HashMap::new(),
+            labels: HashMap::new(),
+            comments: HashMap::new(),
+            index: TaskIndex::default(),
+            events,
+        }
+    }
+
+    /// Rebuild a store from a snapshot; the next refresh is incremental from its sync token
+    #[must_use]
+    pub fn from_snapshot(wrapper: TodoistWrapper, snapshot: StoreSnapshot) -> Self {
+        let mut store = Self::new(wrapper);
+        store.sync_token = snapshot.sync_token;
+        store.projects = snapshot.projects.into_iter().map(|p| (p.id.clone(), p)).collect();
+        store.sections = snapshot.sections.into_iter().map(|s| (s.id.clone(), s)).collect();
+        store.labels = snapshot.labels.into_iter().map(|l| (l.id.clone(), l)).collect();
+        store.comments = snapshot.comments.into_iter().map(|c| (c.id.clone(), c)).collect();
+        for task in snapshot.tasks {
+            store.index.insert(&task);
+            store.tasks.insert(task.id.clone(), task);
+        }
+        store
+    }
+
+    /// Copy the current content of the store
+    pub fn snapshot(&self) -> StoreSnapshot {
+        StoreSnapshot {
+            sync_token: self.sync_token.clone(),
+            projects: self.projects().into_iter().cloned().collect(),
+            sections: self.sections().into_iter().cloned().collect(),
+            tasks: self.tasks().into_iter().cloned().collect(),
+            labels: self.labels().into_iter().cloned().collect(),
+            comments: self.comments().into_iter().cloned().collect(),
+        }
+    }
+
+    /// Client used to refresh the store
+    pub fn wrapper(&self) -> &TodoistWrapper {
+        &self.wrapper
+    }
+
+    /// Sync token of the last refresh, or [`FULL_SYNC_TOKEN`] if the store was never refreshed
+    pub fn sync_token(&self) -> &str {
+        &self.sync_token
+    }
+
+    /// Receive the events of every future refresh
+    pub fn subscribe(&self) -> broadcast::Receiver<StoreEvent> {
+        self.events.subscribe()
+    }
+
+    /// Fetch changes since the last refresh and apply them
+    pub async fn refresh(&mut self) -> Result<Vec<StoreEvent>> {
+        let response = self.wrapper.sync(&self.sync_token, STORE_RESOURCE_TYPES).await?;
+        Ok(self.apply(response))
+    }
+
+    /// Apply a Sync API response obtained elsewhere
+    ///
+    /// On a full sync, resources missing from the response are considered deleted.
+    pub fn apply(&mut self, response: SyncResponse) -> Vec<StoreEvent> {
+        let full_sync = response.full_sync;
+        let mut events = Vec::new();
+
+        let projects = response.projects.into_iter().map(|p| {
+            let id = p.id.clone();
+            let incoming = if p.is_deleted || p.is_archived {
+                Incoming::Deleted
+            } else {
+                Incoming::Live(Project::from(p))
+            };
+            (id, incoming)
+        });
+        events.extend(
+            merge(&mut self.projects, projects, full_sync)
+                .into_iter()
+                .map(StoreEvent::Project),
+        );
+
+        let sections = response.sections.into_iter().map(|s| {
+            let id = s.id.clone();
+            let incoming = if s.is_deleted || s.is_archived {
+                Incoming::Deleted
+            } else {
+                Incoming::Live(Section::from(s))
+            };
+            (id, incoming)
+        });
+        events.extend(
+            merge(&mut self.sections, sections, full_sync)
+                .into_iter()
+                .map(StoreEvent::Section),
+        );
+
+        let labels = response.labels.into_iter().map(|l| {
+            let id = l.id.clone();
+            let incoming = if l.is_deleted {
+                Incoming::Deleted
+            } else {
+                Incoming::Live(Label::from(l))
+            };
+            (id, incoming)
+        });
+        events.extend(
+            merge(&mut self.labels, labels, full_sync)
+                .into_iter()
+                .map(StoreEvent::Label),
+        );
+
+        let items = response.items.into_iter().map(|i| {
+            let id = i.id.clone();
+            let incoming = if i.is_deleted {
+                Incoming::Deleted
+            } else if i.checked {
+                Incoming::Completed
+            } else {
+                Incoming::Live(Task::from(i))
+            };
+            (id, incoming)
+        });
+        let task_changes = merge(&mut self.tasks, items, full_sync);
+        self.reindex(&task_changes);
+        events.extend(task_changes.into_iter().map(StoreEvent::Task));
+
+        let notes = response.notes.into_iter().chain(response.project_notes).map(|n| {
+            let id = n.id.clone();
+            let incoming = if n.is_deleted {
+                Incoming::Deleted
+            } else {
+                Incoming::Live(Comment::from(n))
+            };
+            (id, incoming)
+        });
+        events.extend(
+            merge(&mut self.comments, notes, full_sync)
+                .into_iter()
+                .map(StoreEvent::Comment),
+        );
+
+        self.sync_token = response.sync_token;
+        self.broadcast(&events);
+        events
+    }
+
+    /// Replace the whole content of the store, broadcasting the differences as events
+    #[cfg(feature = "sqlite")]
+    pub(crate) fn replace(&mut self, snapshot: StoreSnapshot) -> Vec<StoreEvent> {
+        fn live<T>(values: Vec<T>, id: impl Fn(&T) -> &String) -> impl Iterator<Item = (String, Incoming<T>)> {
+            values
+                .into_iter()
+                .map(move |value| (id(&value).clone(), Incoming::Live(value)))
+        }
+
+        let mut events = Vec::new();
+        events.extend(
+            merge(&mut self.projects, live(snapshot.projects, |p| &p.id), true)
+                .into_iter()
+                .map(StoreEvent::Project),
+        );
+        events.extend(
+            merge(&mut self.sections, live(snapshot.sections, |s| &s.id), true)
+                .into_iter()
+                .map(StoreEvent::Section),
+        );
+        events.extend(
+            merge(&mut self.labels, live(snapshot.labels, |l| &l.id), true)
+                .into_iter()
+                .map(StoreEvent::Label),
+        );
+        let task_changes = merge(&mut self.tasks, live(snapshot.tasks, |t| &t.id), true);
+        self.reindex(&task_changes);
+        events.extend(task_changes.into_iter().map(StoreEvent::Task));
+        events.extend(
+            merge(&mut self.comments, live(snapshot.comments, |c| &c.id), true)
+                .into_iter()
+                .map(StoreEvent::Comment),
+        );
+
+        self.sync_token = snapshot.sync_token;
+        self.broadcast(&events);
+        events
+    }
+
+    fn reindex(&mut self, changes: &[Change<Task>]) {
+        for change in changes {
+            match change {
+                Change::Created(task) => self.index.insert(task),
+                Change::Updated { before, after } => {
+                    self.index.remove(before);
+                    self.index.insert(after);
+                }
+                Change::Completed(task) | Change::Deleted(task) => self.index.remove(task),
+            }
+        }
+    }
+
+    fn broadcast(&self, events: &[StoreEvent]) {
+        for event in events {
+            // Sending only fails when nobody is subscribed
+            let _ = self.events.send(event.clone());
+        }
+    }
+
+    // ===== LOOKUPS =====
+
+    /// All active tasks, ordered by project and position
+    pub fn tasks(&self) -> Vec<&Task> {
+        sorted_tasks(self.tasks.values())
+    }
+
+    /// Get a task by ID
+    pub fn task(&self, task_id: &str) -> Option<&Task> {
+        self.tasks.get(task_id)
+    }
+
+    /// Active tasks of a project
+    pub fn tasks_by_project(&self, project_id: &str) -> Vec<&Task> {
+        self.lookup(self.index.by_project.get(project_id))
+    }
+
+    /// Active tasks of a section
+    pub fn tasks_by_section(&self, section_id: &str) -> Vec<&Task> {
+        self.lookup(self.index.by_section.get(section_id))
+    }
+
+    /// Active tasks carrying a label, by label name
+    pub fn tasks_by_label(&self, label_name: &str) -> Vec<&Task> {
+        self.lookup(self.index.by_label.get(label_name))
+    }
+
+    /// Active tasks due on a date (`YYYY-MM-DD`)
+    pub fn tasks_due_on(&self, date: &str) -> Vec<&Task> {
+        self.lookup(self.index.by_due_date.get(date))
+    }
+
+    /// Active tasks due between two dates (`YYYY-MM-DD`), both included
+    pub fn tasks_due_between(&self, from: &str, to: &str) -> Vec<&Task> {
+        if from > to {
+            return Vec::new();
+        }
+        let ids = self
+            .index
+            .by_due_date
+            .range(from.to_string()..=to.to_string())
+            .flat_map(|(_, ids)| ids.iter());
+        sorted_tasks(ids.filter_map(|id| self.tasks.get(id)))
+    }
+
+    /// Context to evaluate filter queries against the projects and sections of the store
+    pub fn filter_context(&self, today: NaiveDate) -> FilterContext {
+        let mut context = FilterContext::new(today);
+        context.projects = self.projects.values().cloned().collect();
+        context.sections = self.sections.values().cloned().collect();
+        context
+    }
+
+    /// Active tasks matching a filter query, evaluated locally
+    pub fn filter_tasks(&self, query: &FilterQuery, context: &FilterContext) -> Vec<&Task> {
+        sorted_tasks(self.tasks.values().filter(|t| query.matches(t, context)))
+    }
+
+    /// All projects, ordered by position
+    pub fn projects(&self) -> Vec<&Project> {
+        let mut projects: Vec<&Project> = self.projects.values().collect();
+        projects.sort_by(|a, b| (a.order, &a.id).cmp(&(b.order, &b.id)));
+        projects
+    }
+
+    /// Get a project by ID
+    pub fn project(&self, project_id: &str) -> Option<&Project> {
+        self.projects.get(project_id)
+    }
+
+    /// All sections, ordered by project and position
+    pub fn sections(&self) -> Vec<&Section> {
+        let mut sections: Vec<&Section> = self.sections.values().collect();
+        sections.sort_by(|a, b| (&a.project_id, a.order, &a.id).cmp(&(&b.project_id, b.order, &b.id)));
+        sections
+    }
+
+    /// Get a section by ID
+    pub fn section(&self, section_id: &str) -> Option<&Section> {
+        self.sections.get(section_id)
+    }
+
+    /// All personal labels, ordered by position
+    pub fn labels(&self) -> Vec<&Label> {
+        let mut labels: Vec<&Label> = self.labels.values().collect();
+        labels.sort_by(|a, b| (a.order, &a.id).cmp(&(b.order, &b.id)));
+        labels
+    }
+
+    /// Get a personal label by ID
+    pub fn label(&self, label_id: &str) -> Option<&Label> {
+        self.labels.get(label_id)
+    }
+
+    /// All comments, ordered by posting time
+    pub fn comments(&self) -> Vec<&Comment> {
+        let mut comments: Vec<&Comment> = self.comments.values().collect();
+        comments.sort_by(|a, b| (&a.posted_at, &a.id).cmp(&(&b.posted_at, &b.id)));
+        comments
+    }
+
+    /// Get a comment by ID
+    pub fn comment(&self, comment_id: &str) -> Option<&Comment> {
+        self.comments.get(comment_id)
+    }
+
+    /// Comments posted on a task, ordered by posting time
+    pub fn comments_for_task(&self, task_id: &str) -> Vec<&Comment> {
+        self.comments()
+            .into_iter()
+            .filter(|c| c.task_id.as_deref() == Some(task_id))
+            .collect()
+    }
+
+    fn lookup(&self, ids: Option<&BTreeSet<String>>) -> Vec<&Task> {
+        match ids {
+            Some(ids) => sorted_tasks(ids.iter().filter_map(|id| self.tasks.get(id))),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Secondary indexes over the active tasks of a store
+#[derive(Debug, Default)]
+struct TaskIndex {
+    by_project: HashMap<String, BTreeSet<String>>,
+    by_section: HashMap<String, BTreeSet<String>>,
+    by_label: HashMap<String, BTreeSet<String>>,
+    by_due_date: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TaskIndex {
+    fn insert(&mut self, task: &Task) {
+        self.by_project
+            .entry(task.project_id.clone())
+            .or_default()
+            .insert(task.id.clone());
+        if let Some(section_id) = &task.section_id {
+            self.by_section
+                .entry(section_id.clone())
+                .or_default()
+                .insert(task.id.clone());
+        }
+        for label in &task.labels {
+            self.by_label.entry(label.clone()).or_default().insert(task.id.clone());
+        }
+        if let Some(due) = &task.due {
+            self.by_due_date
+                .entry(due.date.clone())
+                .or_default()
+                .insert(task.id.clone());
+        }
+    }
+
+    fn remove(&mut self, task: &Task) {
+        remove_from(&mut self.by_project, &task.project_id, &task.id);
+        if let Some(section_id) = &task.section_id {
+            remove_from(&mut self.by_section, section_id, &task.id);
+        }
+        for label in &task.labels {
+            remove_from(&mut self.by_label, label, &task.id);
+        }
+        if let Some(due) = &task.due {
+            if let Some(ids) = self.by_due_date.get_mut(&due.date) {
+                ids.remove(&task.id);
+                if ids.is_empty() {
+                    self.by_due_date.remove(&due.date);
+                }
+            }
+        }
+    }
+}
+
+fn remove_from(index: &mut HashMap<String, BTreeSet<String>>, key: &str, id: &str) {
+    if let Some(ids) = index.get_mut(key) {
+        ids.remove(id);
+        if ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+fn sorted_tasks<'a>(tasks: impl Iterator<Item = &'a Task>) -> Vec<&'a Task> {
+    let mut tasks: Vec<&Task> = tasks.collect();
+    tasks.sort_by(|a, b| (&a.project_id, a.order, &a.id).cmp(&(&b.project_id, b.order, &b.id)));
+    tasks
+}
+
+/// State of a resource as reported by the Sync API
+enum Incoming<T> {
+    Live(T),
+    Completed,
+    Deleted,
+}
+
+/// Merge incoming resources into `current` and return the detected changes
+fn merge<T: Clone + PartialEq>(
+    current: &mut HashMap<String, T>,
+    incoming: impl Iterator<Item = (String, Incoming<T>)>,
+    full_sync: bool,
+) -> Vec<Change<T>> {
+    let mut changes = Vec::new();
+    let mut seen = BTreeSet::new();
+
+    for (id, state) in incoming {
+        seen.insert(id.clone());
+        match state {
+            Incoming::Live(after) => match current.insert(id, after.clone()) {
+                None => changes.push(Change::Created(after)),
+                Some(before) if before != after => changes.push(Change::Updated { before, after }),
+                Some(_) => {}
+            },
+            Incoming::Completed => {
+                if let Some(before) = current.remove(&id) {
+                    changes.push(Change::Completed(before));
+                }
+            }
+            Incoming::Deleted => {
+                if let Some(before) = current.remove(&id) {
+                    changes.push(Change::Deleted(before));
+                }
+            }
+        }
+    }
+
+    if full_sync {
+        let mut missing: Vec<String> = current.keys().filter(|id| !seen.contains(*id)).cloned().collect();
+        missing.sort();
+        for id in missing {
+            if let Some(before) = current.remove(&id) {
+                changes.push(Change::Deleted(before));
+            }
+        }
+    }
+
+    changes
+}
@@ -0,0 +1,509 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::*;
+
+/// Sync token requesting a full sync
+pub const FULL_SYNC_TOKEN: &str = "*";
+
+/// Todoist web app base used to build object URLs, which the Sync API does not return
+const TODOIST_APP_BASE: &str = "https://app.todoist.com/app";
+
+/// Response of a Sync API read request
+///
+/// Resources are returned in their Sync API shape; use the `From` conversions to get the
+/// REST models used everywhere else in the crate.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncResponse {
+    pub sync_token: String,
+    #[serde(default)]
+    pub full_sync: bool,
+    #[serde(default)]
+    pub items: Vec<SyncItem>,
+    #[serde(default)]
+    pub projects: Vec<SyncProject>,
+    #[serde(default)]
+    pub sections: Vec<SyncSection>,
+    #[serde(default)]
+    pub labels: Vec<SyncLabel>,
+    #[serde(default)]
+    pub notes: Vec<SyncNote>,
+    #[serde(default)]
+    pub project_notes: Vec<SyncNote>,
+    #[serde(default)]
+    pub reminders: Vec<SyncReminder>,
+    #[serde(default)]
+    pub filters: Vec<SyncFilter>,
+    pub user: Option<SyncUser>,
+}
+
+/// Sync API task ("item")
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncItem {
+    pub id: String,
+    pub project_id: String,
+    pub content: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_priority")]
+    pub priority: i32,
+    pub due: Option<SyncDue>,
+    pub deadline: Option<Deadline>,
+    pub duration: Option<Duration>,
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub child_order: i32,
+    pub section_id: Option<String>,
+    #[serde(default)]
+    pub checked: bool,
+    #[serde(default)]
+    pub is_deleted: bool,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub added_at: String,
+    pub responsible_uid: Option<String>,
+}
+
+/// Sync API due date, where `date` holds either a date or a floating/UTC datetime
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncDue {
+    pub date: String,
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub string: String,
+    pub lang: Option<String>,
+    #[serde(default)]
+    pub is_recurring: bool,
+}
+
+/// Sync API user
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncUser {
+    pub id: String,
+    pub full_name: String,
+    pub email: String,
+    pub avatar_big: Option<String>,
+    #[serde(default)]
+    pub is_premium: bool,
+    pub business_account_id: Option<String>,
+    #[serde(default)]
+    pub tz_info: SyncTimezone,
+    #[serde(default = "default_start_day")]
+    pub start_day: i32,
+    #[serde(default)]
+    pub lang: String,
+    #[serde(default)]
+    pub daily_goal: i32,
+    #[serde(default)]
+    pub weekly_goal: i32,
+    #[serde(default)]
+    pub karma: f64,
+}
+
+/// Sync API timezone of a user
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncTimezone {
+    #[serde(default)]
+    pub timezone: String,
+    #[serde(default)]
+    pub gmt_string: String,
+}
+
+fn default_start_day() -> i32 {
+    1
+}
+
+/// Response of `completed/get_stats`
+#[derive(Debug, Deserialize)]
+pub(crate) struct ProductivityStatsResponse {
+    #[serde(default)]
+    karma: f64,
+    #[serde(default)]
+    karma_trend: String,
+    #[serde(default)]
+    completed_count: i64,
+    #[serde(default)]
+    goals: StatsGoals,
+    #[serde(default)]
+    days_items: Vec<DayCompletions>,
+    #[serde(default)]
+    week_items: Vec<WeekCompletions>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct StatsGoals {
+    #[serde(default)]
+    daily_goal: i32,
+    #[serde(default)]
+    weekly_goal: i32,
+    #[serde(default)]
+    current_daily_streak: Streak,
+    #[serde(default)]
+    max_daily_streak: Streak,
+    #[serde(default)]
+    current_weekly_streak: Streak,
+    #[serde(default)]
+    max_weekly_streak: Streak,
+}
+
+/// Sync API saved filter
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncFilter {
+    pub id: String,
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub color: String,
+    #[serde(default)]
+    pub item_order: i32,
+    #[serde(default)]
+    pub is_favorite: bool,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+/// Sync API reminder
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncReminder {
+    pub id: String,
+    pub item_id: String,
+    pub notify_uid: Option<String>,
+    #[serde(rename = "type")]
+    pub reminder_type: String,
+    pub due: Option<SyncDue>,
+    pub minute_offset: Option<i32>,
+    pub name: Option<String>,
+    pub loc_lat: Option<String>,
+    pub loc_long: Option<String>,
+    pub loc_trigger: Option<String>,
+    pub radius: Option<i32>,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+/// Sync API project
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncProject {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub color: String,
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub child_order: i32,
+    #[serde(default)]
+    pub is_favorite: bool,
+    #[serde(default)]
+    pub is_archived: bool,
+    #[serde(default)]
+    pub is_deleted: bool,
+    #[serde(default)]
+    pub shared: bool,
+    #[serde(default)]
+    pub view_style: String,
+    #[serde(default)]
+    pub inbox_project: bool,
+    #[serde(default)]
+    pub team_inbox: bool,
+}
+
+/// Sync API section
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncSection {
+    pub id: String,
+    pub name: String,
+    pub project_id: String,
+    #[serde(default)]
+    pub section_order: i32,
+    #[serde(default)]
+    pub is_archived: bool,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+/// Sync API personal label
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncLabel {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub color: String,
+    #[serde(default)]
+    pub item_order: i32,
+    #[serde(default)]
+    pub is_favorite: bool,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+/// Sync API comment ("note"), either on a task or on a project
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncNote {
+    pub id: String,
+    pub item_id: Option<String>,
+    pub project_id: Option<String>,
+    pub content: String,
+    #[serde(default)]
+    pub posted_at: String,
+    pub file_attachment: Option<Attachment>,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+/// Response of the archived sections endpoint
+#[derive(Debug, Deserialize)]
+pub(crate) struct ArchivedSectionsResponse {
+    #[serde(default)]
+    pub sections: Vec<SyncSection>,
+    #[serde(default)]
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+impl From<ArchivedSectionsResponse> for ArchivedSections {
+    fn from(response: ArchivedSectionsResponse) -> Self {
+        ArchivedSections {
+            sections: response.sections.into_iter().map(Section::from).collect(),
+            next_cursor: response.next_cursor.filter(|_| response.has_more),
+        }
+    }
+}
+
+/// Response of the project data endpoint, which also covers archived projects
+#[cfg(feature = "backup")]
+#[derive(Debug, Deserialize, Default)]
+pub(crate) struct ProjectDataResponse {
+    #[serde(default)]
+    pub items: Vec<SyncItem>,
+    #[serde(default)]
+    pub sections: Vec<SyncSection>,
+    #[serde(default)]
+    pub project_notes: Vec<SyncNote>,
+}
+
+/// Response of the completed tasks history endpoint
+#[derive(Debug, Deserialize, Default)]
+pub(crate) struct CompletedItemsResponse {
+    #[serde(default)]
+    pub items: Vec<SyncCompletedItem>,
+    #[serde(default)]
+    pub projects: HashMap<String, SyncProject>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SyncCompletedItem {
+    pub id: String,
+    pub task_id: String,
+    pub content: String,
+    pub project_id: String,
+    pub section_id: Option<String>,
+    pub completed_at: String,
+    #[serde(default)]
+    pub note_count: i32,
+    pub item_object: Option<SyncItem>,
+}
+
+impl CompletedItemsResponse {
+    pub(crate) fn into_completed_tasks(self) -> Vec<CompletedTask> {
+        let projects = self.projects;
+        self.items
+            .into_iter()
+            .map(|item| CompletedTask {
+                project: projects.get(&item.project_id).cloned().map(Project::from),
+                task: item.item_object.map(Task::from),
+                id: item.id,
+                task_id: item.task_id,
+                content: item.content,
+                project_id: item.project_id,
+                section_id: item.section_id,
+                completed_at: item.completed_at,
+                note_count: item.note_count,
+            })
+            .collect()
+    }
+}
+
+fn default_priority() -> i32 {
+    1
+}
+
+impl From<SyncDue> for Due {
+    fn from(due: SyncDue) -> Self {
+        let (date, datetime) = if due.date.len() > 10 {
+            (due.date[..10].to_string(), Some(due.date.clone()))
+        } else {
+            (due.date, None)
+        };
+        Due {
+            string: due.string,
+            date,
+            is_recurring: due.is_recurring,
+            datetime,
+            timezone: due.timezone,
+        }
+    }
+}
+
+impl From<SyncItem> for Task {
+    fn from(item: SyncItem) -> Self {
+        Task {
+            url: format!("{TODOIST_APP_BASE}/task/{}", item.id),
+            id: item.id,
+            content: item.content,
+            description: item.description,
+            project_id: item.project_id,
+            section_id: item.section_id,
+            parent_id: item.parent_id,
+            order: item.child_order,
+            priority: item.priority,
+            is_completed: item.checked,
+            labels: item.labels,
+            created_at: item.added_at,
+            due: item.due.map(Due::from),
+            deadline: item.deadline,
+            duration: item.duration,
+            assignee_id: item.responsible_uid,
+            comment_count: 0,
+        }
+    }
+}
+
+impl From<SyncProject> for Project {
+    fn from(project: SyncProject) -> Self {
+        Project {
+            url: format!("{TODOIST_APP_BASE}/project/{}", project.id),
+            id: project.id,
+            name: project.name,
+            comment_count: 0,
+            order: project.child_order,
+            color: project.color,
+            is_shared: project.shared,
+            is_favorite: project.is_favorite,
+            is_inbox_project: project.inbox_project,
+            is_team_inbox: project.team_inbox,
+            view_style: project.view_style,
+            parent_id: project.parent_id,
+        }
+    }
+}
+
+impl From<SyncSection> for Section {
+    fn from(section: SyncSection) -> Self {
+        Section {
+            url: format!("{TODOIST_APP_BASE}/section/{}", section.id),
+            id: section.id,
+            name: section.name,
+            project_id: section.project_id,
+            order: section.section_order,
+        }
+    }
+}
+
+impl From<SyncLabel> for Label {
+    fn from(label: SyncLabel) -> Self {
+        Label {
+            id: label.id,
+            name: label.name,
+            color: label.color,
+            order: label.item_order,
+            is_favorite: label.is_favorite,
+        }
+    }
+}
+
+impl From<SyncNote> for Comment {
+    fn from(note: SyncNote) -> Self {
+        Comment {
+            id: note.id,
+            content: note.content,
+            posted_at: note.posted_at,
+            attachment: note.file_attachment,
+            // Task comments only carry the task ID, project comments only the project ID
+            project_id: if note.item_id.is_some() { None } else { note.project_id },
+            task_id: note.item_id,
+        }
+    }
+}
+
+impl From<SyncUser> for UserProfile {
+    fn from(user: SyncUser) -> Self {
+        UserProfile {
+            user: User {
+                id: user.id,
+                name: user.full_name,
+                email: user.email,
+                avatar_url: user.avatar_big,
+                is_premium: user.is_premium,
+                is_business_account: user.business_account_id.is_some(),
+            },
+            timezone: user.tz_info.timezone,
+            utc_offset: user.tz_info.gmt_string,
+            start_day: user.start_day,
+            lang: user.lang,
+            daily_goal: user.daily_goal,
+            weekly_goal: user.weekly_goal,
+            karma: user.karma,
+        }
+    }
+}
+
+impl From<ProductivityStatsResponse> for ProductivityStats {
+    fn from(stats: ProductivityStatsResponse) -> Self {
+        ProductivityStats {
+            karma: stats.karma,
+            karma_trend: stats.karma_trend,
+            completed_count: stats.completed_count,
+            daily_goal: stats.goals.daily_goal,
+            weekly_goal: stats.goals.weekly_goal,
+            current_daily_streak: stats.goals.current_daily_streak,
+            max_daily_streak: stats.goals.max_daily_streak,
+            current_weekly_streak: stats.goals.current_weekly_streak,
+            max_weekly_streak: stats.goals.max_weekly_streak,
+            days: stats.days_items,
+            weeks: stats.week_items,
+        }
+    }
+}
+
+impl From<SyncFilter> for SavedFilter {
+    fn from(filter: SyncFilter) -> Self {
+        SavedFilter {
+            id: filter.id,
+            name: filter.name,
+            query: filter.query,
+            color: filter.color,
+            order: filter.item_order,
+            is_favorite: filter.is_favorite,
+        }
+    }
+}
+
+impl From<SyncReminder> for Reminder {
+    fn from(reminder: SyncReminder) -> Self {
+        let trigger = match reminder.reminder_type.as_str() {
+            "relative" => ReminderTrigger::Relative {
+                minute_offset: reminder.minute_offset.unwrap_or_default(),
+            },
+            "location" => ReminderTrigger::Location {
+                name: reminder.name.unwrap_or_default(),
+                latitude: reminder.loc_lat.unwrap_or_default(),
+                longitude: reminder.loc_long.unwrap_or_default(),
+                trigger: match reminder.loc_trigger.as_deref() {
+                    Some("on_leave") => LocationTrigger::OnLeave,
+                    _ => LocationTrigger::OnEnter,
+                },
+                radius: reminder.radius.unwrap_or_default(),
+            },
+            _ => ReminderTrigger::Absolute {
+                datetime: reminder.due.map(|due| due.date).unwrap_or_default(),
+            },
+        };
+        Reminder {
+            id: reminder.id,
+            task_id: reminder.item_id,
+            notify_uid: reminder.notify_uid,
+            trigger,
+        }
+    }
+}
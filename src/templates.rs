@@ -0,0 +1,504 @@
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::models::{CompletedTask, CreateLabelArgs, CreateProjectArgs, CreateSectionArgs, CreateTaskArgs};
+use crate::wrapper::TodoistWrapper;
+
+/// Declarative description of projects, sections, tasks and labels, in TOML or YAML
+///
+/// ```toml
+/// [[labels]]
+/// name = "client"
+///
+/// [[projects]]
+/// name = "Acme"
+///
+/// [[projects.sections]]
+/// name = "Onboarding"
+///
+/// [[projects.sections.tasks]]
+/// content = "Kickoff call"
+/// labels = ["client"]
+/// due_in_days = 3
+///
+/// [[projects.sections.tasks.subtasks]]
+/// content = "Send agenda"
+///
+/// [[projects.tasks]]
+/// content = "Weekly report"
+/// due = "every friday"
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Template {
+    #[serde(default)]
+    pub labels: Vec<LabelTemplate>,
+    #[serde(default)]
+    pub projects: Vec<ProjectTemplate>,
+}
+
+/// A label in a [`Template`]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct LabelTemplate {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+/// A project in a [`Template`], with its sub-projects
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ProjectTemplate {
+    pub name: String,
+    pub color: Option<String>,
+    pub view_style: Option<String>,
+    #[serde(default)]
+    pub sections: Vec<SectionTemplate>,
+    /// Tasks outside any section
+    #[serde(default)]
+    pub tasks: Vec<TaskTemplate>,
+    #[serde(default)]
+    pub projects: Vec<ProjectTemplate>,
+}
+
+/// A section in a [`Template`]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SectionTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub tasks: Vec<TaskTemplate>,
+}
+
+/// A task in a [`Template`], with its subtasks
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TaskTemplate {
+    pub content: String,
+    pub description: Option<String>,
+    pub priority: Option<i32>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Natural-language due date, such as `every monday`
+    pub due: Option<String>,
+    /// Due date as a number of days after the template is applied
+    pub due_in_days: Option<i64>,
+    #[serde(default)]
+    pub subtasks: Vec<TaskTemplate>,
+}
+
+impl Template {
+    /// Parse a TOML template
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let template: Template = toml::from_str(text).context("invalid TOML template")?;
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Parse a YAML template
+    pub fn from_yaml(text: &str) -> Result<Self> {
+        let template: Template = serde_norway::from_str(text).context("invalid YAML template")?;
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Read a template file, as YAML for `.yaml` and `.yml` files and as TOML otherwise
+    pub async fn load(path: &Path) -> Result<Self> {
+        let text = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml" | "yml") => Self::from_yaml(&text),
+            _ => Self::from_toml(&text),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let today = chrono::Local::now().date_naive();
+        for node in flatten(self) {
+            if let NodeSpec::Task(task) = node.spec {
+                if task.due.is_some() && task.due_in_days.is_some() {
+                    bail!("task {} sets both due and due_in_days", node.path);
+                }
+                if let Some(days) = task.due_in_days {
+                    if relative_date(today, days).is_none() {
+                        bail!("task {}: due_in_days {days} is out of range", node.path);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether applying a template creates an object or finds it already there
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanAction {
+    Create,
+    Exists,
+}
+
+/// Kind of object described by a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateObject {
+    Label,
+    Project,
+    Section,
+    Task,
+}
+
+impl fmt::Display for TemplateObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TemplateObject::Label => "label",
+            TemplateObject::Project => "project",
+            TemplateObject::Section => "section",
+            TemplateObject::Task => "task",
+        })
+    }
+}
+
+/// One object of a template plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanStep {
+    pub action: PlanAction,
+    pub object: TemplateObject,
+    /// Names from the top-level project down to the object, joined with `/`
+    pub path: String,
+    /// ID of the existing or created object; `None` for objects a plan would create
+    pub id: Option<String>,
+}
+
+/// Outcome of planning or applying a template, in template order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplatePlan {
+    pub steps: Vec<PlanStep>,
+}
+
+impl TemplatePlan {
+    /// Steps that create an object
+    pub fn creations(&self) -> impl Iterator<Item = &PlanStep> {
+        self.steps.iter().filter(|step| step.action == PlanAction::Create)
+    }
+
+    /// Whether everything in the template already exists
+    pub fn is_up_to_date(&self) -> bool {
+        self.creations().next().is_none()
+    }
+}
+
+impl fmt::Display for TemplatePlan {
+    /// One line per object, `+` for creations and `=` for existing objects
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            let sign = match step.action {
+                PlanAction::Create => '+',
+                PlanAction::Exists => '=',
+            };
+            writeln!(f, "{sign} {} {}", step.object, step.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error of [`crate::TodoistWrapper::apply_template`], with the steps done before it
+#[derive(Debug)]
+pub struct TemplateError {
+    /// Objects found or created before the error; created objects are not rolled back
+    pub plan: TemplatePlan,
+    pub source: anyhow::Error,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "applying the template failed after {} steps: {:#}",
+            self.plan.steps.len(),
+            self.source
+        )
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl TodoistWrapper {
+    /// Show what applying a template would create, without changing anything
+    pub async fn plan_template(&self, template: &Template) -> Result<TemplatePlan> {
+        let mut plan = TemplatePlan::default();
+        self.run_template(template, false, &mut plan).await?;
+        Ok(plan)
+    }
+
+    /// Create the parts of a template that do not exist yet
+    ///
+    /// Objects are matched by name under the same parent, ignoring ASCII case, and tasks also match
+    /// completed tasks, so applying a template again only creates what is missing. Relative due
+    /// dates are counted from today. On error, the [`TemplateError`] holds the steps done so far.
+    pub async fn apply_template(&self, template: &Template) -> std::result::Result<TemplatePlan, TemplateError> {
+        let mut plan = TemplatePlan::default();
+        match self.run_template(template, true, &mut plan).await {
+            Ok(()) => Ok(plan),
+            Err(source) => Err(TemplateError { plan, source }),
+        }
+    }
+
+    async fn run_template(&self, template: &Template, apply: bool, plan: &mut TemplatePlan) -> Result<()> {
+        let today = chrono::Local::now().date_naive();
+        let (labels, projects, sections, tasks) = futures::try_join!(
+            self.get_labels(),
+            self.get_projects(),
+            self.get_sections(),
+            self.get_tasks()
+        )?;
+        // Completed tasks of the existing projects, fetched when a task is first matched in them
+        let mut completed: HashMap<String, Vec<CompletedTask>> = HashMap::new();
+
+        for label in &template.labels {
+            let existing = labels
+                .iter()
+                .find(|existing| existing.name.eq_ignore_ascii_case(&label.name));
+            let (action, id) = match existing {
+                Some(existing) => (PlanAction::Exists, Some(existing.id.clone())),
+                None if apply => {
+                    let args = CreateLabelArgs {
+                        name: label.name.clone(),
+                        color: label.color.clone(),
+                        ..Default::default()
+                    };
+                    (PlanAction::Create, Some(self.create_label(&args).await?.id))
+                }
+                None => (PlanAction::Create, None),
+            };
+            plan.steps.push(PlanStep {
+                action,
+                object: TemplateObject::Label,
+                path: label.name.clone(),
+                id,
+            });
+        }
+
+        let nodes = flatten(template);
+        let mut ids: Vec<Option<String>> = Vec::with_capacity(nodes.len());
+        for node in &nodes {
+            let project_id = node.project.and_then(|index| ids[index].clone());
+            let section_id = node.section.and_then(|index| ids[index].clone());
+            let parent_id = node.parent.and_then(|index| ids[index].clone());
+            // Nothing can exist under an ancestor that does not exist yet
+            let resolved = [node.project, node.section, node.parent]
+                .into_iter()
+                .flatten()
+                .all(|index| ids[index].is_some());
+
+            let (object, existing) = match node.spec {
+                NodeSpec::Project(project) => (
+                    TemplateObject::Project,
+                    projects
+                        .iter()
+                        .find(|existing| {
+                            existing.name.eq_ignore_ascii_case(&project.name) && existing.parent_id == project_id
+                        })
+                        .map(|existing| existing.id.clone()),
+                ),
+                NodeSpec::Section(section) => (
+                    TemplateObject::Section,
+                    sections
+                        .iter()
+                        .find(|existing| {
+                            existing.name.eq_ignore_ascii_case(&section.name)
+                                && Some(&existing.project_id) == project_id.as_ref()
+                        })
+                        .map(|existing| existing.id.clone()),
+                ),
+                NodeSpec::Task(task) => {
+                    let active = tasks
+                        .iter()
+                        .find(|existing| {
+                            existing.content.eq_ignore_ascii_case(&task.content)
+                                && Some(&existing.project_id) == project_id.as_ref()
+                                && existing.section_id == section_id
+                                && existing.parent_id == parent_id
+                        })
+                        .map(|existing| existing.id.clone());
+                    let existing = match (active, &project_id) {
+                        (Some(id), _) => Some(id),
+                        (None, Some(project_id)) if resolved => {
+                            if !completed.contains_key(project_id) {
+                                let history = self.get_completed_tasks_for_project(project_id).await?;
+                                completed.insert(project_id.clone(), history);
+                            }
+                            completed[project_id]
+                                .iter()
+                                .find(|existing| {
+                                    existing.content.eq_ignore_ascii_case(&task.content)
+                                        && existing.section_id == section_id
+                                        && existing.task.as_ref().and_then(|task| task.parent_id.clone()) == parent_id
+                                })
+                                .map(|existing| existing.task_id.clone())
+                        }
+                        (None, _) => None,
+                    };
+                    (TemplateObject::Task, existing)
+                }
+            };
+
+            let (action, id) = match existing.filter(|_| resolved) {
+                Some(id) => (PlanAction::Exists, Some(id)),
+                None if apply => {
+                    let id = match node.spec {
+                        NodeSpec::Project(project) => {
+                            let args = CreateProjectArgs {
+                                name: project.name.clone(),
+                                color: project.color.clone(),
+                                parent_id: project_id,
+                                is_favorite: None,
+                                view_style: project.view_style.clone(),
+                            };
+                            self.create_project(&args).await?.id
+                        }
+                        NodeSpec::Section(section) => {
+                            let args = CreateSectionArgs {
+                                name: section.name.clone(),
+                                project_id: project_id.with_context(|| format!("{} has no project", node.path))?,
+                                order: None,
+                            };
+                            self.create_section(&args).await?.id
+                        }
+                        NodeSpec::Task(task) => {
+                            let project_id = project_id.with_context(|| format!("{} has no project", node.path))?;
+                            let args = task_args(task, project_id, section_id, parent_id, today)?;
+                            self.create_task(&args).await?.id
+                        }
+                    };
+                    (PlanAction::Create, Some(id))
+                }
+                None => (PlanAction::Create, None),
+            };
+            ids.push(id.clone());
+            plan.steps.push(PlanStep {
+                action,
+                object,
+                path: node.path.clone(),
+                id,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// A project, section or task of a template
+enum NodeSpec<'a> {
+    Project(&'a ProjectTemplate),
+    Section(&'a SectionTemplate),
+    Task(&'a TaskTemplate),
+}
+
+/// A template object with the positions of its ancestors in the flattened template
+struct Node<'a> {
+    pub spec: NodeSpec<'a>,
+    pub path: String,
+    /// Parent project of a project, or project of a section or task
+    pub project: Option<usize>,
+    /// Section of a task
+    pub section: Option<usize>,
+    /// Parent task of a subtask
+    pub parent: Option<usize>,
+}
+
+/// Projects, sections and tasks of a template, every parent before its children
+fn flatten(template: &Template) -> Vec<Node<'_>> {
+    let mut nodes = Vec::new();
+    for project in &template.projects {
+        push_project(&mut nodes, project, None, "");
+    }
+    nodes
+}
+
+fn push_project<'a>(nodes: &mut Vec<Node<'a>>, project: &'a ProjectTemplate, parent: Option<usize>, prefix: &str) {
+    let path = format!("{prefix}{}", project.name);
+    let index = nodes.len();
+    nodes.push(Node {
+        spec: NodeSpec::Project(project),
+        path: path.clone(),
+        project: parent,
+        section: None,
+        parent: None,
+    });
+    for task in &project.tasks {
+        push_task(nodes, task, index, None, None, &path);
+    }
+    for section in &project.sections {
+        let section_path = format!("{path}/{}", section.name);
+        let section_index = nodes.len();
+        nodes.push(Node {
+            spec: NodeSpec::Section(section),
+            path: section_path.clone(),
+            project: Some(index),
+            section: None,
+            parent: None,
+        });
+        for task in &section.tasks {
+            push_task(nodes, task, index, Some(section_index), None, &section_path);
+        }
+    }
+    for child in &project.projects {
+        push_project(nodes, child, Some(index), &format!("{path}/"));
+    }
+}
+
+fn push_task<'a>(
+    nodes: &mut Vec<Node<'a>>,
+    task: &'a TaskTemplate,
+    project: usize,
+    section: Option<usize>,
+    parent: Option<usize>,
+    prefix: &str,
+) {
+    let path = format!("{prefix}/{}", task.content);
+    let index = nodes.len();
+    nodes.push(Node {
+        spec: NodeSpec::Task(task),
+        path: path.clone(),
+        project: Some(project),
+        section,
+        parent,
+    });
+    for subtask in &task.subtasks {
+        push_task(nodes, subtask, project, section, Some(index), &path);
+    }
+}
+
+/// Creation arguments of a template task, with relative due dates counted from `today`
+fn task_args(
+    task: &TaskTemplate,
+    project_id: String,
+    section_id: Option<String>,
+    parent_id: Option<String>,
+    today: NaiveDate,
+) -> Result<CreateTaskArgs> {
+    let due_date = match task.due_in_days {
+        Some(days) => {
+            let date = relative_date(today, days)
+                .with_context(|| format!("due_in_days {days} of {} is out of range", task.content))?;
+            Some(date.format("%Y-%m-%d").to_string())
+        }
+        None => None,
+    };
+    Ok(CreateTaskArgs {
+        content: task.content.clone(),
+        description: task.description.clone(),
+        project_id: Some(project_id),
+        section_id,
+        parent_id,
+        priority: task.priority,
+        labels: Some(task.labels.clone()).filter(|labels| !labels.is_empty()),
+        due_string: task.due.clone(),
+        due_date,
+        ..Default::default()
+    })
+}
+
+/// Date `days` after `today`, or `None` past the range of dates
+fn relative_date(today: NaiveDate, days: i64) -> Option<NaiveDate> {
+    chrono::Duration::try_days(days).and_then(|days| today.checked_add_signed(days))
+}
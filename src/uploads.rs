@@ -0,0 +1,53 @@
+use std::path::{Path, PathBuf};
+
+/// Largest upload accepted by default, the Todoist limit for paid plans
+pub const DEFAULT_MAX_UPLOAD_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Number of leading bytes inspected to detect the MIME type of a file
+pub(crate) const SNIFF_LEN: usize = 8192;
+
+/// Content of a file to upload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadSource {
+    /// A file on disk, streamed during the upload
+    Path(PathBuf),
+    /// Content already in memory
+    Bytes(Vec<u8>),
+}
+
+impl From<PathBuf> for UploadSource {
+    fn from(path: PathBuf) -> Self {
+        UploadSource::Path(path)
+    }
+}
+
+impl From<&Path> for UploadSource {
+    fn from(path: &Path) -> Self {
+        UploadSource::Path(path.to_path_buf())
+    }
+}
+
+impl From<Vec<u8>> for UploadSource {
+    fn from(bytes: Vec<u8>) -> Self {
+        UploadSource::Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for UploadSource {
+    fn from(bytes: &[u8]) -> Self {
+        UploadSource::Bytes(bytes.to_vec())
+    }
+}
+
+/// Guess the MIME type of a file from its first bytes, then from its name
+///
+/// Falls back to `application/octet-stream`.
+pub fn sniff_mime(head: &[u8], file_name: &str) -> String {
+    if let Some(kind) = infer::get(head) {
+        return kind.mime_type().to_string();
+    }
+    mime_guess::from_path(file_name)
+        .first_raw()
+        .unwrap_or("application/octet-stream")
+        .to_string()
+}
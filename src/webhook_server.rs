@@ -0,0 +1,162 @@
+use anyhow::Result;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+use crate::webhooks::{Webhook, WebhookError, WebhookVerifier, DELIVERY_ID_HEADER, SIGNATURE_HEADER};
+
+/// Route used when none is configured
+pub const DEFAULT_ROUTE: &str = "/webhooks/todoist";
+
+type HandlerFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+type Handler = Arc<dyn Fn(Webhook) -> HandlerFuture + Send + Sync>;
+type ErrorHook = Arc<dyn Fn(&Webhook, anyhow::Error) + Send + Sync>;
+
+/// Receives webhook deliveries and dispatches them to async handlers
+///
+/// Deliveries are verified and deduplicated with a [`WebhookVerifier`], acknowledged right away
+/// and handled in a background task, so slow handlers never make Todoist time out and retry.
+///
+/// ```rust,no_run
+/// use todoist_api::webhook_server::WebhookServer;
+/// use todoist_api::webhooks::WebhookEvent;
+///
+/// # async fn run() -> anyhow::Result<()> {
+/// let server = WebhookServer::new("client-secret")
+///     .with_route("/todoist")
+///     .on("item:added", |webhook| async move {
+///         if let WebhookEvent::ItemAdded(task) = webhook.event {
+///             println!("New task: {}", task.content);
+///         }
+///         Ok(())
+///     });
+///
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
+/// server.serve(listener).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct WebhookServer {
+    verifier: WebhookVerifier,
+    route: String,
+    handlers: HashMap<String, Vec<Handler>>,
+    fallback: Vec<Handler>,
+    on_error: Option<ErrorHook>,
+}
+
+impl WebhookServer {
+    /// Create a server verifying deliveries with the client secret of a Todoist app
+    pub fn new(client_secret: impl Into<String>) -> Self {
+        Self::with_verifier(WebhookVerifier::new(client_secret))
+    }
+
+    /// Create a server using a configured verifier
+    pub fn with_verifier(verifier: WebhookVerifier) -> Self {
+        Self {
+            verifier,
+            route: DEFAULT_ROUTE.to_string(),
+            handlers: HashMap::new(),
+            fallback: Vec::new(),
+            on_error: None,
+        }
+    }
+
+    /// Path the webhook is received on
+    pub fn with_route(mut self, route: impl Into<String>) -> Self {
+        self.route = route.into();
+        self
+    }
+
+    /// Handle events with a Todoist event name, such as `item:added`
+    pub fn on<F, Fut>(mut self, event_name: &str, handler: F) -> Self
+    where
+        F: Fn(Webhook) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.handlers
+            .entry(event_name.to_string())
+            .or_default()
+            .push(boxed(handler));
+        self
+    }
+
+    /// Handle every event, after the handlers registered for its name
+    pub fn on_any<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(Webhook) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.fallback.push(boxed(handler));
+        self
+    }
+
+    /// Called with errors returned by handlers, which are otherwise dropped
+    pub fn on_error<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Webhook, anyhow::Error) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Arc::new(hook));
+        self
+    }
+
+    /// Build a router to merge into an existing axum application
+    pub fn router(self) -> Router {
+        let route = self.route.clone();
+        Router::new().route(&route, post(receive)).with_state(Arc::new(self))
+    }
+
+    /// Serve webhooks on a listener until the process stops
+    pub async fn serve(self, listener: TcpListener) -> Result<()> {
+        axum::serve(listener, self.router()).await?;
+        Ok(())
+    }
+
+    async fn dispatch(&self, webhook: Webhook) {
+        let handlers = self
+            .handlers
+            .get(webhook.event.name())
+            .into_iter()
+            .flatten()
+            .chain(&self.fallback);
+        for handler in handlers {
+            if let Err(error) = handler(webhook.clone()).await {
+                if let Some(hook) = &self.on_error {
+                    hook(&webhook, error);
+                }
+            }
+        }
+    }
+}
+
+fn boxed<F, Fut>(handler: F) -> Handler
+where
+    F: Fn(Webhook) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    Arc::new(move |webhook| Box::pin(handler(webhook)) as HandlerFuture)
+}
+
+async fn receive(State(server): State<Arc<WebhookServer>>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+    match server
+        .verifier
+        .verify(&body, header(SIGNATURE_HEADER), header(DELIVERY_ID_HEADER))
+    {
+        Ok(webhook) => {
+            tokio::spawn(async move { server.dispatch(webhook).await });
+            StatusCode::OK
+        }
+        // Acknowledge retries of processed deliveries so Todoist stops sending them
+        Err(WebhookError::Replayed(_)) => StatusCode::OK,
+        Err(WebhookError::MissingSignature | WebhookError::InvalidSignature) => StatusCode::UNAUTHORIZED,
+        Err(WebhookError::MissingDeliveryId | WebhookError::InvalidPayload(_)) => StatusCode::BAD_REQUEST,
+    }
+}
@@ -0,0 +1,366 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::models::*;
+use crate::sync::{SyncItem, SyncLabel, SyncNote, SyncProject, SyncSection};
+
+/// Header carrying the base64 HMAC-SHA256 of the request body
+pub const SIGNATURE_HEADER: &str = "X-Todoist-Hmac-SHA256";
+
+/// Header carrying the delivery ID, which stays the same when Todoist retries a delivery
+pub const DELIVERY_ID_HEADER: &str = "X-Todoist-Delivery-ID";
+
+/// Number of delivery IDs remembered by default to reject replays
+pub const DEFAULT_REPLAY_WINDOW: usize = 10_000;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Error returned when a webhook delivery is rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookError {
+    /// The signature header is absent
+    MissingSignature,
+    /// The signature does not match the body
+    InvalidSignature,
+    /// The delivery ID header is absent, so replays cannot be detected
+    MissingDeliveryId,
+    /// A delivery with the same ID was already accepted
+    Replayed(String),
+    /// The body is not a valid webhook payload
+    InvalidPayload(String),
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookError::MissingSignature => write!(f, "missing {SIGNATURE_HEADER} header"),
+            WebhookError::InvalidSignature => write!(f, "invalid webhook signature"),
+            WebhookError::MissingDeliveryId => write!(f, "missing {DELIVERY_ID_HEADER} header"),
+            WebhookError::Replayed(id) => write!(f, "delivery {id} was already processed"),
+            WebhookError::InvalidPayload(message) => write!(f, "invalid webhook payload: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for WebhookError {}
+
+/// User who triggered a webhook event
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Initiator {
+    pub id: String,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub full_name: String,
+    pub image_id: Option<String>,
+    #[serde(default)]
+    pub is_premium: bool,
+}
+
+/// A typed webhook event, carrying the affected resource
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebhookEvent {
+    ItemAdded(Task),
+    /// `before` holds the task as it was, when Todoist sends it
+    ItemUpdated {
+        task: Task,
+        before: Option<Task>,
+    },
+    ItemCompleted(Task),
+    ItemUncompleted(Task),
+    ItemDeleted(Task),
+    NoteAdded(Comment),
+    NoteUpdated(Comment),
+    NoteDeleted(Comment),
+    ProjectAdded(Project),
+    ProjectUpdated(Project),
+    ProjectDeleted(Project),
+    ProjectArchived(Project),
+    ProjectUnarchived(Project),
+    SectionAdded(Section),
+    SectionUpdated(Section),
+    SectionDeleted(Section),
+    SectionArchived(Section),
+    SectionUnarchived(Section),
+    LabelAdded(Label),
+    LabelUpdated(Label),
+    LabelDeleted(Label),
+    /// An event this crate does not model, such as `reminder:fired`
+    Other {
+        event_name: String,
+        data: Value,
+    },
+}
+
+impl WebhookEvent {
+    /// Todoist event name, such as `item:added`
+    pub fn name(&self) -> &str {
+        match self {
+            WebhookEvent::ItemAdded(_) => "item:added",
+            WebhookEvent::ItemUpdated { .. } => "item:updated",
+            WebhookEvent::ItemCompleted(_) => "item:completed",
+            WebhookEvent::ItemUncompleted(_) => "item:uncompleted",
+            WebhookEvent::ItemDeleted(_) => "item:deleted",
+            WebhookEvent::NoteAdded(_) => "note:added",
+            WebhookEvent::NoteUpdated(_) => "note:updated",
+            WebhookEvent::NoteDeleted(_) => "note:deleted",
+            WebhookEvent::ProjectAdded(_) => "project:added",
+            WebhookEvent::ProjectUpdated(_) => "project:updated",
+            WebhookEvent::ProjectDeleted(_) => "project:deleted",
+            WebhookEvent::ProjectArchived(_) => "project:archived",
+            WebhookEvent::ProjectUnarchived(_) => "project:unarchived",
+            WebhookEvent::SectionAdded(_) => "section:added",
+            WebhookEvent::SectionUpdated(_) => "section:updated",
+            WebhookEvent::SectionDeleted(_) => "section:deleted",
+            WebhookEvent::SectionArchived(_) => "section:archived",
+            WebhookEvent::SectionUnarchived(_) => "section:unarchived",
+            WebhookEvent::LabelAdded(_) => "label:added",
+            WebhookEvent::LabelUpdated(_) => "label:updated",
+            WebhookEvent::LabelDeleted(_) => "label:deleted",
+            WebhookEvent::Other { event_name, .. } => event_name,
+        }
+    }
+
+    fn from_payload(event_name: &str, data: Value, extra: Option<&Value>) -> serde_json::Result<Self> {
+        let task = |data: Value| serde_json::from_value::<SyncItem>(data).map(Task::from);
+        let comment = |data: Value| serde_json::from_value::<SyncNote>(data).map(Comment::from);
+        let project = |data: Value| serde_json::from_value::<SyncProject>(data).map(Project::from);
+        let section = |data: Value| serde_json::from_value::<SyncSection>(data).map(Section::from);
+        let label = |data: Value| serde_json::from_value::<SyncLabel>(data).map(Label::from);
+
+        Ok(match event_name {
+            "item:added" => WebhookEvent::ItemAdded(task(data)?),
+            "item:updated" => WebhookEvent::ItemUpdated {
+                task: task(data)?,
+                before: match extra.and_then(|extra| extra.get("old_item")) {
+                    Some(old) if !old.is_null() => Some(task(old.clone())?),
+                    _ => None,
+                },
+            },
+            "item:completed" => WebhookEvent::ItemCompleted(task(data)?),
+            "item:uncompleted" => WebhookEvent::ItemUncompleted(task(data)?),
+            "item:deleted" => WebhookEvent::ItemDeleted(task(data)?),
+            "note:added" => WebhookEvent::NoteAdded(comment(data)?),
+            "note:updated" => WebhookEvent::NoteUpdated(comment(data)?),
+            "note:deleted" => WebhookEvent::NoteDeleted(comment(data)?),
+            "project:added" => WebhookEvent::ProjectAdded(project(data)?),
+            "project:updated" => WebhookEvent::ProjectUpdated(project(data)?),
+            "project:deleted" => WebhookEvent::ProjectDeleted(project(data)?),
+            "project:archived" => WebhookEvent::ProjectArchived(project(data)?),
+            "project:unarchived" => WebhookEvent::ProjectUnarchived(project(data)?),
+            "section:added" => WebhookEvent::SectionAdded(section(data)?),
+            "section:updated" => WebhookEvent::SectionUpdated(section(data)?),
+            "section:deleted" => WebhookEvent::SectionDeleted(section(data)?),
+            "section:archived" => WebhookEvent::SectionArchived(section(data)?),
+            "section:unarchived" => WebhookEvent::SectionUnarchived(section(data)?),
+            "label:added" => WebhookEvent::LabelAdded(label(data)?),
+            "label:updated" => WebhookEvent::LabelUpdated(label(data)?),
+            "label:deleted" => WebhookEvent::LabelDeleted(label(data)?),
+            _ => WebhookEvent::Other {
+                event_name: event_name.to_string(),
+                data,
+            },
+        })
+    }
+}
+
+/// A parsed webhook delivery
+#[derive(Debug, Clone, PartialEq)]
+pub struct Webhook {
+    pub event: WebhookEvent,
+    /// ID of the user the webhook was delivered for
+    pub user_id: String,
+    pub initiator: Option<Initiator>,
+    /// Sync API version of the payload
+    pub version: String,
+    /// Value of the [`DELIVERY_ID_HEADER`] header, when known
+    pub delivery_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawWebhook {
+    event_name: String,
+    #[serde(default, deserialize_with = "string_or_number")]
+    user_id: String,
+    event_data: Value,
+    event_data_extra: Option<Value>,
+    initiator: Option<Initiator>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    version: String,
+}
+
+/// Todoist has sent some IDs as numbers in older payloads
+pub(crate) fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(optional_string_or_number(deserializer)?.unwrap_or_default())
+}
+
+/// Like [`string_or_number`], keeping a missing ID as `None`
+pub(crate) fn optional_string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => Some(s),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    })
+}
+
+impl Webhook {
+    /// Parse a webhook body without checking its signature
+    pub fn parse(body: &[u8]) -> Result<Self, WebhookError> {
+        let raw: RawWebhook = serde_json::from_slice(body).map_err(|e| WebhookError::InvalidPayload(e.to_string()))?;
+        let event = WebhookEvent::from_payload(&raw.event_name, raw.event_data, raw.event_data_extra.as_ref())
+            .map_err(|e| WebhookError::InvalidPayload(format!("{}: {e}", raw.event_name)))?;
+        Ok(Self {
+            event,
+            user_id: raw.user_id,
+            initiator: raw.initiator,
+            version: raw.version,
+            delivery_id: None,
+        })
+    }
+}
+
+/// Compute the signature Todoist sends for a body
+pub fn sign(client_secret: &str, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(client_secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    BASE64.encode(mac.finalize().into_bytes())
+}
+
+/// Check a signature against a body in constant time
+pub fn verify_signature(client_secret: &str, body: &[u8], signature: &str) -> bool {
+    let Ok(expected) = BASE64.decode(signature.trim()) else {
+        return false;
+    };
+    let mut mac = HmacSha256::new_from_slice(client_secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Verifies, parses and deduplicates webhook deliveries for an app
+///
+/// ```rust
+/// use todoist_api::webhooks::{self, WebhookVerifier};
+///
+/// let verifier = WebhookVerifier::new("client-secret");
+/// let body = br#"{"event_name":"label:added","user_id":"1","event_data":{"id":"2","name":"work"}}"#;
+/// let signature = webhooks::sign("client-secret", body);
+///
+/// let webhook = verifier.verify(body, Some(&signature), Some("delivery-1")).unwrap();
+/// assert_eq!(webhook.event.name(), "label:added");
+/// // Retries of an accepted delivery are rejected
+/// assert!(verifier.verify(body, Some(&signature), Some("delivery-1")).is_err());
+/// ```
+pub struct WebhookVerifier {
+    client_secret: String,
+    seen: Mutex<SeenDeliveries>,
+    allow_missing_delivery_id: bool,
+}
+
+impl fmt::Debug for WebhookVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookVerifier")
+            .field("client_secret", &"[redacted]")
+            .finish_non_exhaustive()
+    }
+}
+
+impl WebhookVerifier {
+    /// Create a verifier for the client secret of a Todoist app
+    pub fn new(client_secret: impl Into<String>) -> Self {
+        Self::with_replay_window(client_secret, DEFAULT_REPLAY_WINDOW)
+    }
+
+    /// Create a verifier remembering up to `replay_window` delivery IDs
+    pub fn with_replay_window(client_secret: impl Into<String>, replay_window: usize) -> Self {
+        Self {
+            client_secret: client_secret.into(),
+            seen: Mutex::new(SeenDeliveries {
+                capacity: replay_window,
+                order: VecDeque::new(),
+                ids: HashSet::new(),
+            }),
+            allow_missing_delivery_id: false,
+        }
+    }
+
+    /// Accept deliveries without a delivery ID, which are then never deduplicated
+    ///
+    /// Deliveries lacking the header are rejected by default, since stripping it would otherwise
+    /// let a captured delivery be replayed.
+    #[must_use]
+    pub fn allow_missing_delivery_id(mut self) -> Self {
+        self.allow_missing_delivery_id = true;
+        self
+    }
+
+    /// Verify the signature of a delivery, reject replays and parse the payload
+    ///
+    /// A delivery ID is only remembered once the payload has been accepted, so a delivery that
+    /// failed to parse can still be retried.
+    pub fn verify(
+        &self,
+        body: &[u8],
+        signature: Option<&str>,
+        delivery_id: Option<&str>,
+    ) -> Result<Webhook, WebhookError> {
+        let signature = signature.ok_or(WebhookError::MissingSignature)?;
+        if !verify_signature(&self.client_secret, body, signature) {
+            return Err(WebhookError::InvalidSignature);
+        }
+        match delivery_id {
+            Some(id) if self.is_replay(id) => return Err(WebhookError::Replayed(id.to_string())),
+            None if !self.allow_missing_delivery_id => return Err(WebhookError::MissingDeliveryId),
+            _ => {}
+        }
+
+        let mut webhook = Webhook::parse(body)?;
+        if let Some(id) = delivery_id {
+            if !self.remember(id) {
+                return Err(WebhookError::Replayed(id.to_string()));
+            }
+            webhook.delivery_id = Some(id.to_string());
+        }
+        Ok(webhook)
+    }
+
+    /// Whether a delivery ID was already accepted
+    pub fn is_replay(&self, delivery_id: &str) -> bool {
+        self.seen.lock().unwrap().ids.contains(delivery_id)
+    }
+
+    /// Remember a delivery ID, returning `false` if it was already known
+    fn remember(&self, delivery_id: &str) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        if !seen.ids.insert(delivery_id.to_string()) {
+            return false;
+        }
+        seen.order.push_back(delivery_id.to_string());
+        while seen.order.len() > seen.capacity {
+            if let Some(oldest) = seen.order.pop_front() {
+                seen.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Bounded set of the most recent delivery IDs
+struct SeenDeliveries {
+    capacity: usize,
+    order: VecDeque<String>,
+    ids: HashSet<String>,
+}
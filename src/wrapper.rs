@@ -1,41 +1,167 @@
-use anyhow::Result;
-use reqwest::Client;
+use anyhow::{anyhow, bail, Context, Result};
+use futures::stream::{self, Stream, TryStreamExt};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Body, Client, RequestBuilder, Response, StatusCode};
 use serde_json::Value;
-use std::collections::HashMap;
-
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+
+use crate::activity::{ActivityArgs, ActivityEvent, ActivityResponse};
+use crate::attachments::{self, ArchiveReport, AttachmentManifest, ExistingFileCheck, ManifestEntry};
+use crate::batch::{self, Batch, BatchResult, CommandStatus, SyncCommandResponse, SYNC_COMMAND_LIMIT};
+use crate::credentials::{CredentialProvider, StaticToken};
+use crate::labels::{merge_labels, LabelUsage};
 use crate::models::*;
+use crate::quick_add::{QuickAddOptions, QuickAddResult};
+use crate::rate_limit::{RateLimit, RateLimiter};
+use crate::sync::{
+    ArchivedSectionsResponse, CompletedItemsResponse, ProductivityStatsResponse, SyncItem, SyncProject, SyncResponse,
+    FULL_SYNC_TOKEN,
+};
+use crate::uploads::{sniff_mime, UploadSource, DEFAULT_MAX_UPLOAD_SIZE, SNIFF_LEN};
+
+pub(crate) const TODOIST_API_BASE: &str = "https://api.todoist.com/rest/v2";
+pub(crate) const TODOIST_SYNC_API_BASE: &str = "https://api.todoist.com/sync/v9";
+
+/// Largest page the completed tasks history returns
+const COMPLETED_TASKS_PAGE_LIMIT: i32 = 200;
+
+/// Page size used to list every archived project
+const ARCHIVED_PROJECTS_PAGE_LIMIT: i32 = 500;
+
+/// Time allowed for uploading or downloading a file
+const TRANSFER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10 * 60);
 
-const TODOIST_API_BASE: &str = "https://api.todoist.com/rest/v2";
+/// Largest page the activity log returns
+const ACTIVITY_PAGE_LIMIT: i32 = 100;
 
 /// A comprehensive wrapper around the Todoist REST API v2
 #[derive(Clone)]
 pub struct TodoistWrapper {
-    client: Client,
-    api_token: String,
+    pub(crate) client: Client,
+    credentials: Arc<dyn CredentialProvider>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    max_upload_size: u64,
+    base_url: String,
+    pub(crate) sync_base_url: String,
+}
+
+impl fmt::Debug for TodoistWrapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TodoistWrapper")
+            .field("credentials", &self.credentials)
+            .field("rate_limiter", &self.rate_limiter)
+            .field("max_upload_size", &self.max_upload_size)
+            .field("base_url", &self.base_url)
+            .field("sync_base_url", &self.sync_base_url)
+            .finish_non_exhaustive()
+    }
 }
 
 impl TodoistWrapper {
     /// Create a new Todoist client
     #[must_use]
     pub fn new(api_token: String) -> Self {
+        Self::with_base_urls(api_token, TODOIST_API_BASE, TODOIST_SYNC_API_BASE)
+    }
+
+    /// Create a new Todoist client talking to custom REST and Sync API base URLs
+    ///
+    /// This is mostly useful for pointing the client at a mock server in tests.
+    #[must_use]
+    pub fn with_base_urls(api_token: String, base_url: &str, sync_base_url: &str) -> Self {
+        Self::with_credentials_and_base_urls(StaticToken::new(api_token), base_url, sync_base_url)
+    }
+
+    /// Create a new Todoist client asking a provider for the token before each request
+    #[must_use]
+    pub fn with_credentials(credentials: impl CredentialProvider + 'static) -> Self {
+        Self::with_credentials_and_base_urls(credentials, TODOIST_API_BASE, TODOIST_SYNC_API_BASE)
+    }
+
+    /// Create a new Todoist client with a credential provider and custom base URLs
+    #[must_use]
+    pub fn with_credentials_and_base_urls(
+        credentials: impl CredentialProvider + 'static,
+        base_url: &str,
+        sync_base_url: &str,
+    ) -> Self {
         let client = Client::builder()
             .timeout(std::time::Duration::from_secs(10))
             .build()
             .unwrap_or_else(|_| Client::new());
-        Self { client, api_token }
+        Self::with_client(client, Arc::new(credentials), base_url, sync_base_url)
+    }
+
+    /// Create a client sharing an existing connection pool
+    pub(crate) fn with_client(
+        client: Client,
+        credentials: Arc<dyn CredentialProvider>,
+        base_url: &str,
+        sync_base_url: &str,
+    ) -> Self {
+        Self {
+            client,
+            credentials,
+            rate_limiter: None,
+            max_upload_size: DEFAULT_MAX_UPLOAD_SIZE,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            sync_base_url: sync_base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Delay requests so they stay within a rate limit
+    ///
+    /// Clones of the client share the same budget.
+    #[must_use]
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(limit)));
+        self
+    }
+
+    /// Reject uploads larger than `bytes` before sending them
+    ///
+    /// Defaults to [`DEFAULT_MAX_UPLOAD_SIZE`]; lower it to match the limit of a free plan.
+    #[must_use]
+    pub fn with_max_upload_size(mut self, bytes: u64) -> Self {
+        self.max_upload_size = bytes;
+        self
+    }
+
+    /// Send a request with the current token, retrying once with a new one after a 401
+    pub(crate) async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+        let token = self.credentials.token().await?;
+        let retry = request.try_clone();
+        let response = request.bearer_auth(&token).send().await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        match (retry, self.credentials.on_unauthorized(&token).await?) {
+            (Some(retry), Some(new_token)) => {
+                if let Some(rate_limiter) = &self.rate_limiter {
+                    rate_limiter.acquire().await;
+                }
+                Ok(retry.bearer_auth(new_token).send().await?)
+            }
+            _ => Ok(response),
+        }
     }
 
     // ===== PROJECT OPERATIONS =====
 
     /// Get all projects
     pub async fn get_projects(&self) -> Result<Vec<Project>> {
-        let url = format!("{TODOIST_API_BASE}/projects");
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/projects", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let projects: Vec<Project> = response.json().await?;
         Ok(projects)
@@ -43,7 +169,7 @@ impl TodoistWrapper {
 
     /// Get projects with filtering and pagination
     pub async fn get_projects_filtered(&self, args: &ProjectFilterArgs) -> Result<Vec<Project>> {
-        let mut url = format!("{TODOIST_API_BASE}/projects");
+        let mut url = format!("{}/projects", self.base_url);
         let mut query_params = Vec::new();
 
         if let Some(limit) = args.limit {
@@ -57,12 +183,7 @@ impl TodoistWrapper {
             url.push_str(&format!("?{}", query_params.join("&")));
         }
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let projects: Vec<Project> = response.json().await?;
         Ok(projects)
@@ -70,13 +191,8 @@ impl TodoistWrapper {
 
     /// Get a specific project by ID
     pub async fn get_project(&self, project_id: &str) -> Result<Project> {
-        let url = format!("{TODOIST_API_BASE}/projects/{project_id}");
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/projects/{project_id}", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let project: Project = response.json().await?;
         Ok(project)
@@ -84,7 +200,7 @@ impl TodoistWrapper {
 
     /// Create a new project
     pub async fn create_project(&self, args: &CreateProjectArgs) -> Result<Project> {
-        let url = format!("{TODOIST_API_BASE}/projects");
+        let url = format!("{}/projects", self.base_url);
 
         let mut body: HashMap<String, Value> = HashMap::new();
         body.insert("name".to_string(), serde_json::to_value(&args.name)?);
@@ -102,12 +218,12 @@ impl TodoistWrapper {
         }
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
             .await?;
 
         let project: Project = response.json().await?;
@@ -116,7 +232,7 @@ impl TodoistWrapper {
 
     /// Update an existing project
     pub async fn update_project(&self, project_id: &str, args: &UpdateProjectArgs) -> Result<Project> {
-        let url = format!("{TODOIST_API_BASE}/projects/{project_id}");
+        let url = format!("{}/projects/{project_id}", self.base_url);
 
         let mut body: HashMap<String, Value> = HashMap::new();
         if let Some(name) = &args.name {
@@ -133,12 +249,12 @@ impl TodoistWrapper {
         }
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
             .await?;
 
         let project: Project = response.json().await?;
@@ -147,27 +263,72 @@ impl TodoistWrapper {
 
     /// Delete a project
     pub async fn delete_project(&self, project_id: &str) -> Result<()> {
-        let url = format!("{TODOIST_API_BASE}/projects/{project_id}");
-        self.client
-            .delete(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/projects/{project_id}", self.base_url);
+        self.send(self.client.delete(&url)).await?.error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Archive a project, keeping its tasks for later
+    pub async fn archive_project(&self, project_id: &str) -> Result<()> {
+        let mut batch = Batch::new();
+        batch.archive_project(project_id);
+        self.execute_command(&batch).await?;
+        Ok(())
+    }
 
+    /// Restore an archived project
+    pub async fn unarchive_project(&self, project_id: &str) -> Result<()> {
+        let mut batch = Batch::new();
+        batch.unarchive_project(project_id);
+        self.execute_command(&batch).await?;
         Ok(())
     }
 
+    /// Get a page of archived projects
+    pub async fn get_archived_projects(&self, args: &ArchivedProjectArgs) -> Result<Vec<Project>> {
+        let url = format!("{}/projects/get_archived", self.sync_base_url);
+        let mut query_params = Vec::new();
+
+        if let Some(limit) = args.limit {
+            query_params.push(("limit", limit));
+        }
+        if let Some(offset) = args.offset {
+            query_params.push(("offset", offset));
+        }
+
+        let response = self
+            .send(self.client.get(&url).query(&query_params))
+            .await?
+            .error_for_status()?;
+
+        let projects: Vec<SyncProject> = response.json().await?;
+        Ok(projects.into_iter().map(Project::from).collect())
+    }
+
+    /// Get every archived project, across all pages
+    pub async fn get_all_archived_projects(&self) -> Result<Vec<Project>> {
+        let mut projects = Vec::new();
+        loop {
+            let args = ArchivedProjectArgs {
+                limit: Some(ARCHIVED_PROJECTS_PAGE_LIMIT),
+                offset: Some(projects.len() as i32),
+            };
+            let page = self.get_archived_projects(&args).await?;
+            let done = page.len() < ARCHIVED_PROJECTS_PAGE_LIMIT as usize;
+            projects.extend(page);
+            if done {
+                return Ok(projects);
+            }
+        }
+    }
+
     // ===== TASK OPERATIONS =====
 
     /// Get all tasks
     pub async fn get_tasks(&self) -> Result<Vec<Task>> {
-        let url = format!("{TODOIST_API_BASE}/tasks");
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/tasks", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let tasks: Vec<Task> = response.json().await?;
         Ok(tasks)
@@ -175,13 +336,8 @@ impl TodoistWrapper {
 
     /// Get tasks for a specific project
     pub async fn get_tasks_for_project(&self, project_id: &str) -> Result<Vec<Task>> {
-        let url = format!("{TODOIST_API_BASE}/tasks?project_id={project_id}");
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/tasks?project_id={project_id}", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let tasks: Vec<Task> = response.json().await?;
         Ok(tasks)
@@ -189,13 +345,8 @@ impl TodoistWrapper {
 
     /// Get a specific task by ID
     pub async fn get_task(&self, task_id: &str) -> Result<Task> {
-        let url = format!("{TODOIST_API_BASE}/tasks/{task_id}");
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/tasks/{task_id}", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let task: Task = response.json().await?;
         Ok(task)
@@ -203,27 +354,24 @@ impl TodoistWrapper {
 
     /// Get tasks by filter query
     pub async fn get_tasks_by_filter(&self, args: &TaskFilterArgs) -> Result<Vec<Task>> {
-        let mut url = format!("{TODOIST_API_BASE}/tasks");
-        let mut query_params = vec![format!("query={}", args.query)];
+        let url = format!("{}/tasks", self.base_url);
+        let mut query_params = vec![("filter", args.query.clone())];
 
         if let Some(lang) = &args.lang {
-            query_params.push(format!("lang={}", lang));
+            query_params.push(("lang", lang.clone()));
         }
         if let Some(limit) = args.limit {
-            query_params.push(format!("limit={}", limit));
+            query_params.push(("limit", limit.to_string()));
         }
         if let Some(cursor) = &args.cursor {
-            query_params.push(format!("cursor={}", cursor));
+            query_params.push(("cursor", cursor.clone()));
         }
 
-        url.push_str(&format!("?{}", query_params.join("&")));
-
+        // Filter queries contain '&', '#' and spaces, so they must be URL-encoded
         let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+            .send(self.client.get(&url).query(&query_params))
+            .await?
+            .error_for_status()?;
 
         let tasks: Vec<Task> = response.json().await?;
         Ok(tasks)
@@ -231,7 +379,7 @@ impl TodoistWrapper {
 
     /// Create a new task
     pub async fn create_task(&self, args: &CreateTaskArgs) -> Result<Task> {
-        let url = format!("{TODOIST_API_BASE}/tasks");
+        let url = format!("{}/tasks", self.base_url);
 
         let mut body: HashMap<String, Value> = HashMap::new();
         body.insert("content".to_string(), serde_json::to_value(&args.content)?);
@@ -285,12 +433,12 @@ impl TodoistWrapper {
         }
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
             .await?;
 
         let task: Task = response.json().await?;
@@ -299,7 +447,7 @@ impl TodoistWrapper {
 
     /// Update an existing task
     pub async fn update_task(&self, task_id: &str, args: &UpdateTaskArgs) -> Result<Task> {
-        let url = format!("{TODOIST_API_BASE}/tasks/{task_id}");
+        let url = format!("{}/tasks/{task_id}", self.base_url);
 
         let mut body: HashMap<String, Value> = HashMap::new();
         if let Some(content) = &args.content {
@@ -343,12 +491,12 @@ impl TodoistWrapper {
         }
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
             .await?;
 
         let task: Task = response.json().await?;
@@ -357,51 +505,67 @@ impl TodoistWrapper {
 
     /// Complete a task
     pub async fn complete_task(&self, task_id: &str) -> Result<()> {
-        let url = format!("{TODOIST_API_BASE}/tasks/{task_id}/close");
-        self.client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/tasks/{task_id}/close", self.base_url);
+        self.send(self.client.post(&url)).await?.error_for_status()?;
 
         Ok(())
     }
 
     /// Reopen a completed task
     pub async fn reopen_task(&self, task_id: &str) -> Result<()> {
-        let url = format!("{TODOIST_API_BASE}/tasks/{task_id}/reopen");
-        self.client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/tasks/{task_id}/reopen", self.base_url);
+        self.send(self.client.post(&url)).await?.error_for_status()?;
 
         Ok(())
     }
 
     /// Delete a task
     pub async fn delete_task(&self, task_id: &str) -> Result<()> {
-        let url = format!("{TODOIST_API_BASE}/tasks/{task_id}");
-        self.client
-            .delete(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/tasks/{task_id}", self.base_url);
+        self.send(self.client.delete(&url)).await?.error_for_status()?;
 
         Ok(())
     }
 
+    /// Create a task from natural-language text, as the Todoist quick add bar does
+    ///
+    /// The text may contain a `#project`, `@labels`, a `p1`-`p4` priority and a due date, such as
+    /// `Call Bob tomorrow 3pm #Work @phone p2`. The result reports which of them were interpreted.
+    /// A project token is checked against the created task's project, which costs one extra
+    /// request when the text contains one.
+    pub async fn quick_add(&self, text: &str, options: &QuickAddOptions) -> Result<QuickAddResult> {
+        let url = format!("{}/quick/add", self.sync_base_url);
+        let mut form = vec![("text", text.to_string())];
+        if let Some(note) = &options.note {
+            form.push(("note", note.clone()));
+        }
+        if let Some(reminder) = &options.reminder {
+            form.push(("reminder", reminder.clone()));
+        }
+        if let Some(auto_reminder) = options.auto_reminder {
+            form.push(("auto_reminder", auto_reminder.to_string()));
+        }
+
+        let response = self
+            .send(self.client.post(&url).form(&form))
+            .await?
+            .error_for_status()?;
+
+        let item: SyncItem = response.json().await?;
+        let mut result = QuickAddResult::interpret(text, Task::from(item));
+        if let Some(token) = result.project.take() {
+            let project = self.get_project(&result.task.project_id).await?;
+            result.project = project.name.eq_ignore_ascii_case(&token[1..]).then_some(token);
+        }
+        Ok(result)
+    }
+
     // ===== LABEL OPERATIONS =====
 
     /// Get all labels
     pub async fn get_labels(&self) -> Result<Vec<Label>> {
-        let url = format!("{TODOIST_API_BASE}/labels");
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/labels", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let labels: Vec<Label> = response.json().await?;
         Ok(labels)
@@ -409,7 +573,7 @@ impl TodoistWrapper {
 
     /// Get labels with filtering and pagination
     pub async fn get_labels_filtered(&self, args: &LabelFilterArgs) -> Result<Vec<Label>> {
-        let mut url = format!("{TODOIST_API_BASE}/labels");
+        let mut url = format!("{}/labels", self.base_url);
         let mut query_params = Vec::new();
 
         if let Some(limit) = args.limit {
@@ -423,12 +587,7 @@ impl TodoistWrapper {
             url.push_str(&format!("?{}", query_params.join("&")));
         }
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let labels: Vec<Label> = response.json().await?;
         Ok(labels)
@@ -436,13 +595,8 @@ impl TodoistWrapper {
 
     /// Get a specific label by ID
     pub async fn get_label(&self, label_id: &str) -> Result<Label> {
-        let url = format!("{TODOIST_API_BASE}/labels/{label_id}");
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/labels/{label_id}", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let label: Label = response.json().await?;
         Ok(label)
@@ -450,7 +604,7 @@ impl TodoistWrapper {
 
     /// Create a new label
     pub async fn create_label(&self, args: &CreateLabelArgs) -> Result<Label> {
-        let url = format!("{TODOIST_API_BASE}/labels");
+        let url = format!("{}/labels", self.base_url);
 
         let mut body: HashMap<String, Value> = HashMap::new();
         body.insert("name".to_string(), serde_json::to_value(&args.name)?);
@@ -465,12 +619,12 @@ impl TodoistWrapper {
         }
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
             .await?;
 
         let label: Label = response.json().await?;
@@ -479,7 +633,7 @@ impl TodoistWrapper {
 
     /// Update an existing label
     pub async fn update_label(&self, label_id: &str, args: &UpdateLabelArgs) -> Result<Label> {
-        let url = format!("{TODOIST_API_BASE}/labels/{label_id}");
+        let url = format!("{}/labels/{label_id}", self.base_url);
 
         let mut body: HashMap<String, Value> = HashMap::new();
         if let Some(name) = &args.name {
@@ -496,12 +650,12 @@ impl TodoistWrapper {
         }
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
             .await?;
 
         let label: Label = response.json().await?;
@@ -510,27 +664,77 @@ impl TodoistWrapper {
 
     /// Delete a label
     pub async fn delete_label(&self, label_id: &str) -> Result<()> {
-        let url = format!("{TODOIST_API_BASE}/labels/{label_id}");
-        self.client
-            .delete(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/labels/{label_id}", self.base_url);
+        self.send(self.client.delete(&url)).await?.error_for_status()?;
 
         Ok(())
     }
 
+    /// Get the names of labels used in shared projects
+    ///
+    /// With `omit_personal`, names of the user's personal labels are left out.
+    pub async fn get_shared_labels(&self, omit_personal: bool) -> Result<Vec<String>> {
+        let url = format!("{}/labels/shared", self.base_url);
+        let response = self
+            .send(self.client.get(&url).query(&[("omit_personal", omit_personal)]))
+            .await?
+            .error_for_status()?;
+
+        let names: Vec<String> = response.json().await?;
+        Ok(names)
+    }
+
+    /// Rename a shared label on every task carrying it
+    pub async fn rename_shared_label(&self, name: &str, new_name: &str) -> Result<()> {
+        let url = format!("{}/labels/shared/rename", self.base_url);
+        let mut body: HashMap<String, Value> = HashMap::new();
+        body.insert("name".to_string(), serde_json::to_value(name)?);
+        body.insert("new_name".to_string(), serde_json::to_value(new_name)?);
+
+        self.send(
+            self.client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .json(&body),
+        )
+        .await?
+        .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Remove a shared label from every task carrying it
+    pub async fn remove_shared_label(&self, name: &str) -> Result<()> {
+        let url = format!("{}/labels/shared/remove", self.base_url);
+        let mut body: HashMap<String, Value> = HashMap::new();
+        body.insert("name".to_string(), serde_json::to_value(name)?);
+
+        self.send(
+            self.client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .json(&body),
+        )
+        .await?
+        .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Get every label name in use, reconciling personal labels, shared labels and active tasks
+    pub async fn get_label_usage(&self) -> Result<Vec<LabelUsage>> {
+        let labels = self.get_labels().await?;
+        let shared_labels = self.get_shared_labels(true).await?;
+        let tasks = self.get_tasks().await?;
+        Ok(merge_labels(&labels, &shared_labels, &tasks))
+    }
+
     // ===== SECTION OPERATIONS =====
 
     /// Get all sections
     pub async fn get_sections(&self) -> Result<Vec<Section>> {
-        let url = format!("{TODOIST_API_BASE}/sections");
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/sections", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let sections: Vec<Section> = response.json().await?;
         Ok(sections)
@@ -538,7 +742,7 @@ impl TodoistWrapper {
 
     /// Get sections with filtering and pagination
     pub async fn get_sections_filtered(&self, args: &SectionFilterArgs) -> Result<Vec<Section>> {
-        let mut url = format!("{TODOIST_API_BASE}/sections");
+        let mut url = format!("{}/sections", self.base_url);
         let mut query_params = Vec::new();
 
         if let Some(project_id) = &args.project_id {
@@ -555,12 +759,7 @@ impl TodoistWrapper {
             url.push_str(&format!("?{}", query_params.join("&")));
         }
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let sections: Vec<Section> = response.json().await?;
         Ok(sections)
@@ -568,13 +767,8 @@ impl TodoistWrapper {
 
     /// Get a specific section by ID
     pub async fn get_section(&self, section_id: &str) -> Result<Section> {
-        let url = format!("{TODOIST_API_BASE}/sections/{section_id}");
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/sections/{section_id}", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let section: Section = response.json().await?;
         Ok(section)
@@ -582,7 +776,7 @@ impl TodoistWrapper {
 
     /// Create a new section
     pub async fn create_section(&self, args: &CreateSectionArgs) -> Result<Section> {
-        let url = format!("{TODOIST_API_BASE}/sections");
+        let url = format!("{}/sections", self.base_url);
 
         let mut body: HashMap<String, Value> = HashMap::new();
         body.insert("name".to_string(), serde_json::to_value(&args.name)?);
@@ -592,12 +786,12 @@ impl TodoistWrapper {
         }
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
             .await?;
 
         let section: Section = response.json().await?;
@@ -606,18 +800,18 @@ impl TodoistWrapper {
 
     /// Update an existing section
     pub async fn update_section(&self, section_id: &str, args: &UpdateSectionArgs) -> Result<Section> {
-        let url = format!("{TODOIST_API_BASE}/sections/{section_id}");
+        let url = format!("{}/sections/{section_id}", self.base_url);
 
         let mut body: HashMap<String, Value> = HashMap::new();
         body.insert("name".to_string(), serde_json::to_value(&args.name)?);
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
             .await?;
 
         let section: Section = response.json().await?;
@@ -626,27 +820,72 @@ impl TodoistWrapper {
 
     /// Delete a section
     pub async fn delete_section(&self, section_id: &str) -> Result<()> {
-        let url = format!("{TODOIST_API_BASE}/sections/{section_id}");
-        self.client
-            .delete(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/sections/{section_id}", self.base_url);
+        self.send(self.client.delete(&url)).await?.error_for_status()?;
+
+        Ok(())
+    }
 
+    /// Archive a section, keeping its tasks for later
+    pub async fn archive_section(&self, section_id: &str) -> Result<()> {
+        let mut batch = Batch::new();
+        batch.archive_section(section_id);
+        self.execute_command(&batch).await?;
         Ok(())
     }
 
+    /// Restore an archived section
+    pub async fn unarchive_section(&self, section_id: &str) -> Result<()> {
+        let mut batch = Batch::new();
+        batch.unarchive_section(section_id);
+        self.execute_command(&batch).await?;
+        Ok(())
+    }
+
+    /// Get a page of the archived sections of a project
+    pub async fn get_archived_sections(&self, args: &ArchivedSectionArgs) -> Result<ArchivedSections> {
+        let url = format!("{}/archive/sections", self.sync_base_url);
+        let mut query_params = vec![("project_id", args.project_id.clone())];
+
+        if let Some(limit) = args.limit {
+            query_params.push(("limit", limit.to_string()));
+        }
+        if let Some(cursor) = &args.cursor {
+            query_params.push(("cursor", cursor.clone()));
+        }
+
+        let response = self
+            .send(self.client.get(&url).query(&query_params))
+            .await?
+            .error_for_status()?;
+
+        let sections: ArchivedSectionsResponse = response.json().await?;
+        Ok(sections.into())
+    }
+
+    /// Get every archived section of a project, across all pages
+    pub async fn get_all_archived_sections(&self, project_id: &str) -> Result<Vec<Section>> {
+        let mut args = ArchivedSectionArgs {
+            project_id: project_id.to_string(),
+            ..Default::default()
+        };
+        let mut sections = Vec::new();
+        loop {
+            let page = self.get_archived_sections(&args).await?;
+            sections.extend(page.sections);
+            match page.next_cursor {
+                Some(cursor) => args.cursor = Some(cursor),
+                None => return Ok(sections),
+            }
+        }
+    }
+
     // ===== COMMENT OPERATIONS =====
 
     /// Get all comments
     pub async fn get_comments(&self) -> Result<Vec<Comment>> {
-        let url = format!("{TODOIST_API_BASE}/comments");
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/comments", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let comments: Vec<Comment> = response.json().await?;
         Ok(comments)
@@ -654,7 +893,7 @@ impl TodoistWrapper {
 
     /// Get comments with filtering and pagination
     pub async fn get_comments_filtered(&self, args: &CommentFilterArgs) -> Result<Vec<Comment>> {
-        let mut url = format!("{TODOIST_API_BASE}/comments");
+        let mut url = format!("{}/comments", self.base_url);
         let mut query_params = Vec::new();
 
         if let Some(task_id) = &args.task_id {
@@ -674,12 +913,7 @@ impl TodoistWrapper {
             url.push_str(&format!("?{}", query_params.join("&")));
         }
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let comments: Vec<Comment> = response.json().await?;
         Ok(comments)
@@ -687,13 +921,8 @@ impl TodoistWrapper {
 
     /// Get a specific comment by ID
     pub async fn get_comment(&self, comment_id: &str) -> Result<Comment> {
-        let url = format!("{TODOIST_API_BASE}/comments/{comment_id}");
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+        let url = format!("{}/comments/{comment_id}", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
 
         let comment: Comment = response.json().await?;
         Ok(comment)
@@ -701,7 +930,7 @@ impl TodoistWrapper {
 
     /// Create a new comment
     pub async fn create_comment(&self, args: &CreateCommentArgs) -> Result<Comment> {
-        let url = format!("{TODOIST_API_BASE}/comments");
+        let url = format!("{}/comments", self.base_url);
 
         let mut body: HashMap<String, Value> = HashMap::new();
         body.insert("content".to_string(), serde_json::to_value(&args.content)?);
@@ -716,12 +945,12 @@ impl TodoistWrapper {
         }
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
             .await?;
 
         let comment: Comment = response.json().await?;
@@ -730,18 +959,18 @@ impl TodoistWrapper {
 
     /// Update an existing comment
     pub async fn update_comment(&self, comment_id: &str, args: &UpdateCommentArgs) -> Result<Comment> {
-        let url = format!("{TODOIST_API_BASE}/comments/{comment_id}");
+        let url = format!("{}/comments/{comment_id}", self.base_url);
 
         let mut body: HashMap<String, Value> = HashMap::new();
         body.insert("content".to_string(), serde_json::to_value(&args.content)?);
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
             .await?;
 
         let comment: Comment = response.json().await?;
@@ -750,16 +979,699 @@ impl TodoistWrapper {
 
     /// Delete a comment
     pub async fn delete_comment(&self, comment_id: &str) -> Result<()> {
-        let url = format!("{TODOIST_API_BASE}/comments/{comment_id}");
-        self.client
-            .delete(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
+        let url = format!("{}/comments/{comment_id}", self.base_url);
+        self.send(self.client.delete(&url)).await?.error_for_status()?;
+
+        Ok(())
+    }
+
+    // ===== ATTACHMENT OPERATIONS =====
+
+    /// Upload a file, returning an attachment to use in a new comment
+    ///
+    /// Files on disk are streamed rather than read into memory. Without an explicit `mime`, the
+    /// MIME type is detected from the content, then from the file name.
+    pub async fn upload_file(
+        &self,
+        source: impl Into<UploadSource>,
+        file_name: &str,
+        mime: Option<&str>,
+    ) -> Result<Attachment> {
+        let url = format!("{}/uploads/add", self.sync_base_url);
+
+        let part = match source.into() {
+            UploadSource::Path(path) => {
+                let mut file = tokio::fs::File::open(&path)
+                    .await
+                    .with_context(|| format!("failed to open {}", path.display()))?;
+                let size = file.metadata().await?.len();
+                self.check_upload_size(file_name, size)?;
+
+                let mime = match mime {
+                    Some(mime) => mime.to_string(),
+                    None => {
+                        let mut head = Vec::with_capacity(SNIFF_LEN);
+                        (&mut file).take(SNIFF_LEN as u64).read_to_end(&mut head).await?;
+                        file.rewind().await?;
+                        sniff_mime(&head, file_name)
+                    }
+                };
+                Part::stream_with_length(Body::wrap_stream(ReaderStream::new(file)), size).mime_str(&mime)?
+            }
+            UploadSource::Bytes(bytes) => {
+                self.check_upload_size(file_name, bytes.len() as u64)?;
+                let mime = match mime {
+                    Some(mime) => mime.to_string(),
+                    None => sniff_mime(&bytes[..bytes.len().min(SNIFF_LEN)], file_name),
+                };
+                Part::bytes(bytes).mime_str(&mime)?
+            }
+        };
+
+        let form = Form::new()
+            .text("file_name", file_name.to_string())
+            .part("file", part.file_name(file_name.to_string()));
+        let response = self
+            .send(self.client.post(&url).timeout(TRANSFER_TIMEOUT).multipart(form))
+            .await?
+            .error_for_status()?;
+
+        let attachment: Attachment = response.json().await?;
+        Ok(attachment)
+    }
+
+    /// Upload a file from disk and post it as a comment on a task
+    pub async fn comment_with_file(&self, task_id: &str, text: &str, path: impl AsRef<Path>) -> Result<Comment> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .with_context(|| format!("{} is not a file", path.display()))?
+            .to_string_lossy()
+            .into_owned();
+        let attachment = self.upload_file(path, &file_name, None).await?;
+
+        let args = CreateCommentArgs {
+            content: text.to_string(),
+            task_id: Some(task_id.to_string()),
+            project_id: None,
+            attachment: Some(attachment),
+        };
+        self.create_comment(&args).await
+    }
+
+    /// Download an attachment into a writer, returning the number of bytes written
+    ///
+    /// Files hosted by Todoist are requested with the client's credentials and rate limit;
+    /// credentials are never sent to other hosts.
+    pub async fn download_attachment<W>(&self, attachment: &Attachment, writer: &mut W) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let request = self.client.get(&attachment.file_url).timeout(TRANSFER_TIMEOUT);
+        let response = if self.is_todoist_url(&attachment.file_url) {
+            self.send(request).await?
+        } else {
+            request.send().await?
+        };
+        let mut response = response
+            .error_for_status()
+            .with_context(|| format!("failed to download {}", attachment.file_name))?;
+
+        let mut written = 0;
+        while let Some(chunk) = response.chunk().await? {
+            writer.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        writer.flush().await?;
+        Ok(written)
+    }
+
+    /// Download every attachment of a project's comments and its active tasks' comments
+    ///
+    /// Files are written to `dir` along with a [`MANIFEST_FILE`](crate::attachments::MANIFEST_FILE)
+    /// describing them. Attachments already in the manifest and matching `check` on disk are
+    /// skipped, so the routine can be re-run to pick up new files. A failed download is reported
+    /// without stopping the others.
+    pub async fn archive_project_attachments(
+        &self,
+        project_id: &str,
+        dir: impl AsRef<Path>,
+        check: ExistingFileCheck,
+    ) -> Result<ArchiveReport> {
+        let dir = dir.as_ref();
+        tokio::fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        let mut manifest = AttachmentManifest::load(dir).await?;
+
+        let mut comments = self
+            .get_comments_filtered(&CommentFilterArgs {
+                task_id: None,
+                project_id: Some(project_id.to_string()),
+                limit: None,
+                cursor: None,
+            })
             .await?;
+        for task in self.get_tasks_for_project(project_id).await? {
+            let task_comments = self
+                .get_comments_filtered(&CommentFilterArgs {
+                    task_id: Some(task.id),
+                    project_id: None,
+                    limit: None,
+                    cursor: None,
+                })
+                .await?;
+            comments.extend(task_comments);
+        }
+
+        let mut report = ArchiveReport::default();
+        for comment in comments {
+            let Some(attachment) = comment.attachment.as_ref().filter(|a| !a.file_url.is_empty()) else {
+                continue;
+            };
+            if let Some(entry) = manifest.entry(&comment.id) {
+                if attachments::is_archived(&dir.join(&entry.path), entry, check).await {
+                    report.skipped.push(comment.id);
+                    continue;
+                }
+            }
+            match self.archive_attachment(dir, &comment, attachment).await {
+                Ok(entry) => {
+                    manifest.upsert(entry);
+                    report.downloaded.push(comment.id);
+                }
+                Err(error) => report.failed.push((comment.id, error)),
+            }
+        }
+
+        manifest.save(dir).await?;
+        Ok(report)
+    }
+
+    /// Download one attachment into an archive directory and describe it
+    async fn archive_attachment(
+        &self,
+        dir: &Path,
+        comment: &Comment,
+        attachment: &Attachment,
+    ) -> Result<ManifestEntry> {
+        let file_name = attachments::archive_file_name(&comment.id, &attachment.file_name);
+        let path = dir.join(&file_name);
+        // Download next to the final file so an interrupted download is never mistaken for a complete one
+        let partial = dir.join(format!("{file_name}.part"));
+
+        let mut file = tokio::fs::File::create(&partial)
+            .await
+            .with_context(|| format!("failed to create {}", partial.display()))?;
+        let downloaded = self.download_attachment(attachment, &mut file).await;
+        drop(file);
+        if let Err(error) = downloaded {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(error);
+        }
+        tokio::fs::rename(&partial, &path).await?;
+
+        let (size, sha256) = attachments::hash_file(&path).await?;
+        Ok(ManifestEntry {
+            comment_id: comment.id.clone(),
+            task_id: comment.task_id.clone(),
+            project_id: comment.project_id.clone(),
+            file_name: attachment.file_name.clone(),
+            file_type: attachment.file_type.clone(),
+            file_url: attachment.file_url.clone(),
+            path: file_name,
+            size,
+            sha256,
+            downloaded_at: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Whether a URL points at Todoist or at this client's API
+    fn is_todoist_url(&self, url: &str) -> bool {
+        let Ok(url) = reqwest::Url::parse(url) else {
+            return false;
+        };
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let api_host = |base: &str| {
+            reqwest::Url::parse(base)
+                .ok()
+                .and_then(|base| base.host_str().map(str::to_string))
+        };
+        host == "todoist.com"
+            || host.ends_with(".todoist.com")
+            || api_host(&self.base_url).as_deref() == Some(host)
+            || api_host(&self.sync_base_url).as_deref() == Some(host)
+    }
+
+    fn check_upload_size(&self, file_name: &str, size: u64) -> Result<()> {
+        if size > self.max_upload_size {
+            bail!(
+                "{file_name} is {size} bytes, over the upload limit of {} bytes",
+                self.max_upload_size
+            );
+        }
+        Ok(())
+    }
+
+    // ===== COLLABORATOR OPERATIONS =====
+
+    /// Get the collaborators of a shared project
+    pub async fn get_project_collaborators(&self, project_id: &str) -> Result<Vec<User>> {
+        let url = format!("{}/projects/{project_id}/collaborators", self.base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
+
+        let collaborators: Vec<User> = response.json().await?;
+        Ok(collaborators)
+    }
+
+    /// Share a project with someone, inviting them by email
+    pub async fn share_project(&self, project_id: &str, email: &str) -> Result<()> {
+        let mut batch = Batch::new();
+        batch.share_project(project_id, email);
+        self.execute_command(&batch).await?;
+        Ok(())
+    }
+
+    /// Remove a collaborator from a shared project
+    pub async fn remove_collaborator(&self, project_id: &str, email: &str) -> Result<()> {
+        let mut batch = Batch::new();
+        batch.delete_collaborator(project_id, email);
+        self.execute_command(&batch).await?;
+        Ok(())
+    }
+
+    /// Check that a user can be assigned tasks in a project and return them
+    pub async fn validate_assignee(&self, project_id: &str, assignee_id: &str) -> Result<User> {
+        let collaborators = self.get_project_collaborators(project_id).await?;
+        collaborators
+            .into_iter()
+            .find(|user| user.id == assignee_id)
+            .ok_or_else(|| anyhow!("user {assignee_id} is not a collaborator of project {project_id}"))
+    }
+
+    /// Create a task after checking that its assignee is a collaborator of the task's project
+    ///
+    /// The project follows the parent task or section when `project_id` is not set. Costs one or two
+    /// extra requests when `assignee_id` is set; otherwise the same as [`Self::create_task`].
+    pub async fn create_task_validated(&self, args: &CreateTaskArgs) -> Result<Task> {
+        if let Some(assignee_id) = &args.assignee_id {
+            let project_id = self.new_task_project_id(args).await?;
+            self.validate_assignee(&project_id, assignee_id).await?;
+        }
+        self.create_task(args).await
+    }
+
+    /// Update a task after checking that its new assignee is a collaborator of the task's project
+    ///
+    /// Costs two extra requests when `assignee_id` is set; otherwise the same as [`Self::update_task`].
+    pub async fn update_task_validated(&self, task_id: &str, args: &UpdateTaskArgs) -> Result<Task> {
+        if let Some(assignee_id) = &args.assignee_id {
+            let task = self.get_task(task_id).await?;
+            self.validate_assignee(&task.project_id, assignee_id).await?;
+        }
+        self.update_task(task_id, args).await
+    }
+
+    /// Resolve the assignees of tasks to users, keyed by user ID
+    ///
+    /// Collaborators are fetched once per project. Assignees who left a project are omitted.
+    pub async fn resolve_assignees(&self, tasks: &[Task]) -> Result<HashMap<String, User>> {
+        let mut project_ids: Vec<&str> = tasks
+            .iter()
+            .filter(|task| task.assignee_id.is_some())
+            .map(|task| task.project_id.as_str())
+            .collect();
+        project_ids.sort_unstable();
+        project_ids.dedup();
+
+        let mut users = HashMap::new();
+        for project_id in project_ids {
+            for user in self.get_project_collaborators(project_id).await? {
+                users.insert(user.id.clone(), user);
+            }
+        }
+
+        let assignees: HashSet<&str> = tasks.iter().filter_map(|task| task.assignee_id.as_deref()).collect();
+        users.retain(|id, _| assignees.contains(id.as_str()));
+        Ok(users)
+    }
+
+    /// Get the user a task is assigned to, if any
+    pub async fn get_task_assignee(&self, task: &Task) -> Result<Option<User>> {
+        match &task.assignee_id {
+            Some(assignee_id) => {
+                let collaborators = self.get_project_collaborators(&task.project_id).await?;
+                Ok(collaborators.into_iter().find(|user| &user.id == assignee_id))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Project a new task will be created in, following its parent or section
+    async fn new_task_project_id(&self, args: &CreateTaskArgs) -> Result<String> {
+        if let Some(project_id) = &args.project_id {
+            return Ok(project_id.clone());
+        }
+        if let Some(parent_id) = &args.parent_id {
+            return Ok(self.get_task(parent_id).await?.project_id);
+        }
+        if let Some(section_id) = &args.section_id {
+            return Ok(self.get_section(section_id).await?.project_id);
+        }
+        bail!("tasks can only be assigned in a shared project, but no project_id was given")
+    }
+
+    // ===== SAVED FILTER OPERATIONS =====
+
+    /// Get all saved filters, in their display order
+    pub async fn get_saved_filters(&self) -> Result<Vec<SavedFilter>> {
+        let sync = self.sync(FULL_SYNC_TOKEN, &["filters"]).await?;
+        let mut filters: Vec<SavedFilter> = sync
+            .filters
+            .into_iter()
+            .filter(|filter| !filter.is_deleted)
+            .map(SavedFilter::from)
+            .collect();
+        filters.sort_by_key(|filter| filter.order);
+        Ok(filters)
+    }
+
+    /// Get a saved filter by name, ignoring case
+    pub async fn get_saved_filter(&self, name: &str) -> Result<Option<SavedFilter>> {
+        let filters = self.get_saved_filters().await?;
+        Ok(filters
+            .into_iter()
+            .find(|filter| filter.name.eq_ignore_ascii_case(name)))
+    }
+
+    /// Create a saved filter
+    ///
+    /// The filter is read back with a sync, so its color and order are the ones Todoist assigned.
+    pub async fn create_saved_filter(&self, args: &CreateSavedFilterArgs) -> Result<SavedFilter> {
+        let mut batch = Batch::new();
+        let temp_id = batch.add_filter(args);
+        let result = self.execute_command(&batch).await?;
+        let id = result
+            .real_id(&temp_id)
+            .ok_or_else(|| anyhow!("filter_add returned no ID"))?;
+
+        self.get_saved_filters()
+            .await?
+            .into_iter()
+            .find(|filter| filter.id == id)
+            .ok_or_else(|| anyhow!("filter {id} was created but is missing from the sync"))
+    }
+
+    /// Update a saved filter
+    pub async fn update_saved_filter(&self, filter_id: &str, args: &UpdateSavedFilterArgs) -> Result<()> {
+        let mut batch = Batch::new();
+        batch.update_filter(filter_id, args);
+        self.execute_command(&batch).await?;
+        Ok(())
+    }
+
+    /// Delete a saved filter
+    pub async fn delete_saved_filter(&self, filter_id: &str) -> Result<()> {
+        let mut batch = Batch::new();
+        batch.delete_filter(filter_id);
+        self.execute_command(&batch).await?;
+        Ok(())
+    }
+
+    /// Reorder saved filters to match the order of `filter_ids`
+    pub async fn reorder_saved_filters(&self, filter_ids: &[&str]) -> Result<()> {
+        let mut batch = Batch::new();
+        batch.reorder_filters(filter_ids);
+        self.execute_command(&batch).await?;
+        Ok(())
+    }
+
+    /// Get the active tasks matching the query of a saved filter, looked up by name
+    pub async fn run_saved_filter(&self, name: &str) -> Result<Vec<Task>> {
+        let filter = self
+            .get_saved_filter(name)
+            .await?
+            .ok_or_else(|| anyhow!("no saved filter named {name}"))?;
+        self.get_tasks_by_filter(&TaskFilterArgs {
+            query: filter.query,
+            lang: None,
+            limit: None,
+            cursor: None,
+        })
+        .await
+    }
+
+    // ===== REMINDER OPERATIONS =====
+
+    /// Get all reminders
+    pub async fn get_reminders(&self) -> Result<Vec<Reminder>> {
+        let sync = self.sync(FULL_SYNC_TOKEN, &["reminders"]).await?;
+        Ok(sync
+            .reminders
+            .into_iter()
+            .filter(|reminder| !reminder.is_deleted)
+            .map(Reminder::from)
+            .collect())
+    }
 
+    /// Get the reminders of a task
+    pub async fn get_task_reminders(&self, task_id: &str) -> Result<Vec<Reminder>> {
+        let mut reminders = self.get_reminders().await?;
+        reminders.retain(|reminder| reminder.task_id == task_id);
+        Ok(reminders)
+    }
+
+    /// Create a reminder
+    ///
+    /// Relative reminders are only accepted on tasks with a due time. The reminder is read back with
+    /// a sync, so fields Todoist fills in, such as `notify_uid`, are set.
+    pub async fn create_reminder(&self, args: &CreateReminderArgs) -> Result<Reminder> {
+        let mut batch = Batch::new();
+        let temp_id = batch.add_reminder(args);
+        let result = self.execute_command(&batch).await?;
+        let id = result
+            .real_id(&temp_id)
+            .ok_or_else(|| anyhow!("reminder_add returned no ID"))?;
+
+        self.get_reminders()
+            .await?
+            .into_iter()
+            .find(|reminder| reminder.id == id)
+            .ok_or_else(|| anyhow!("reminder {id} was created but is missing from the sync"))
+    }
+
+    /// Update a reminder
+    pub async fn update_reminder(&self, reminder_id: &str, args: &UpdateReminderArgs) -> Result<()> {
+        let mut batch = Batch::new();
+        batch.update_reminder(reminder_id, args);
+        self.execute_command(&batch).await?;
         Ok(())
     }
 
+    /// Delete a reminder
+    pub async fn delete_reminder(&self, reminder_id: &str) -> Result<()> {
+        let mut batch = Batch::new();
+        batch.delete_reminder(reminder_id);
+        self.execute_command(&batch).await?;
+        Ok(())
+    }
+
+    /// Remind about a task a number of minutes before it is due
+    pub async fn remind_before(&self, task_id: &str, minutes: i32) -> Result<Reminder> {
+        self.create_reminder(&CreateReminderArgs {
+            task_id: task_id.to_string(),
+            trigger: ReminderTrigger::Relative { minute_offset: minutes },
+            notify_uid: None,
+        })
+        .await
+    }
+
+    // ===== COMPLETED TASK OPERATIONS =====
+
+    /// Get one page of completed tasks, most recently completed first
+    pub async fn get_completed_tasks(&self, args: &CompletedTaskArgs) -> Result<Vec<CompletedTask>> {
+        let url = format!("{}/completed/get_all", self.sync_base_url);
+        let mut query_params = vec![("annotate_items", "true".to_string())];
+
+        if let Some(project_id) = &args.project_id {
+            query_params.push(("project_id", project_id.clone()));
+        }
+        if let Some(since) = &args.since {
+            query_params.push(("since", since.clone()));
+        }
+        if let Some(until) = &args.until {
+            query_params.push(("until", until.clone()));
+        }
+        if let Some(limit) = args.limit {
+            query_params.push(("limit", limit.to_string()));
+        }
+        if let Some(offset) = args.offset {
+            query_params.push(("offset", offset.to_string()));
+        }
+
+        let response = self
+            .send(self.client.get(&url).query(&query_params))
+            .await?
+            .error_for_status()?;
+
+        let completed: CompletedItemsResponse = response.json().await?;
+        Ok(completed.into_completed_tasks())
+    }
+
+    /// Stream completed tasks across every page of the history
+    ///
+    /// Pages of `args.limit` tasks (200 at most, and by default) are fetched as the stream is consumed.
+    pub fn completed_tasks_stream<'a>(
+        &'a self,
+        args: &CompletedTaskArgs,
+    ) -> impl Stream<Item = Result<CompletedTask>> + 'a {
+        let args = args.clone();
+        paginate(
+            args.offset,
+            args.limit,
+            COMPLETED_TASKS_PAGE_LIMIT,
+            move |offset, limit| {
+                let args = CompletedTaskArgs {
+                    offset: Some(offset),
+                    limit: Some(limit),
+                    ..args.clone()
+                };
+                async move { self.get_completed_tasks(&args).await }
+            },
+        )
+    }
+
+    /// Get every completed task matching the arguments, across all pages
+    pub async fn get_all_completed_tasks(&self, args: &CompletedTaskArgs) -> Result<Vec<CompletedTask>> {
+        self.completed_tasks_stream(args).try_collect().await
+    }
+
+    /// Get every completed task of a project
+    pub async fn get_completed_tasks_for_project(&self, project_id: &str) -> Result<Vec<CompletedTask>> {
+        let args = CompletedTaskArgs {
+            project_id: Some(project_id.to_string()),
+            ..Default::default()
+        };
+        self.get_all_completed_tasks(&args).await
+    }
+
+    /// Get every task completed between two datetimes such as `2024-05-01T00:00:00`
+    pub async fn get_completed_tasks_between(&self, since: &str, until: &str) -> Result<Vec<CompletedTask>> {
+        let args = CompletedTaskArgs {
+            since: Some(since.to_string()),
+            until: Some(until.to_string()),
+            ..Default::default()
+        };
+        self.get_all_completed_tasks(&args).await
+    }
+
+    // ===== ACTIVITY OPERATIONS =====
+
+    /// Get one page of the activity log, most recent events first
+    pub async fn get_activity(&self, args: &ActivityArgs) -> Result<Vec<ActivityEvent>> {
+        let url = format!("{}/activity/get", self.sync_base_url);
+        let response = self
+            .send(self.client.get(&url).query(&args.query_params()))
+            .await?
+            .error_for_status()?;
+
+        let activity: ActivityResponse = response.json().await?;
+        Ok(activity.events.into_iter().map(ActivityEvent::from).collect())
+    }
+
+    /// Stream activity log events across every page
+    ///
+    /// Pages of `args.limit` events (100 at most, and by default) are fetched as the stream is consumed.
+    pub fn activity_stream<'a>(&'a self, args: &ActivityArgs) -> impl Stream<Item = Result<ActivityEvent>> + 'a {
+        let args = args.clone();
+        paginate(args.offset, args.limit, ACTIVITY_PAGE_LIMIT, move |offset, limit| {
+            let args = ActivityArgs {
+                offset: Some(offset),
+                limit: Some(limit),
+                ..args.clone()
+            };
+            async move { self.get_activity(&args).await }
+        })
+    }
+
+    /// Get every activity log event matching the arguments, across all pages
+    pub async fn get_all_activity(&self, args: &ActivityArgs) -> Result<Vec<ActivityEvent>> {
+        self.activity_stream(args).try_collect().await
+    }
+
+    // ===== USER OPERATIONS =====
+
+    /// Get the profile of the authenticated user, including their timezone
+    pub async fn get_current_user(&self) -> Result<UserProfile> {
+        let sync = self.sync(FULL_SYNC_TOKEN, &["user"]).await?;
+        let user = sync.user.ok_or_else(|| anyhow!("sync response has no user"))?;
+        Ok(UserProfile::from(user))
+    }
+
+    /// Get the karma, goals, streaks and completion counts of the authenticated user
+    pub async fn get_productivity_stats(&self) -> Result<ProductivityStats> {
+        let url = format!("{}/completed/get_stats", self.sync_base_url);
+        let response = self.send(self.client.get(&url)).await?.error_for_status()?;
+
+        let stats: ProductivityStatsResponse = response.json().await?;
+        Ok(ProductivityStats::from(stats))
+    }
+
+    // ===== SYNC OPERATIONS =====
+
+    /// Read resources through the Sync API
+    ///
+    /// Pass [`crate::sync::FULL_SYNC_TOKEN`] to receive every resource, or the `sync_token` of a previous
+    /// response to only receive what changed since then.
+    pub async fn sync(&self, sync_token: &str, resource_types: &[&str]) -> Result<SyncResponse> {
+        let url = format!("{}/sync", self.sync_base_url);
+        let form = [
+            ("sync_token", sync_token.to_string()),
+            ("resource_types", serde_json::to_string(resource_types)?),
+        ];
+
+        let response = self
+            .send(self.client.post(&url).form(&form))
+            .await?
+            .error_for_status()?;
+
+        let sync: SyncResponse = response.json().await?;
+        Ok(sync)
+    }
+
+    // ===== BATCH OPERATIONS =====
+
+    /// Execute a batch of commands through the Sync API
+    ///
+    /// Commands are submitted in chunks of [`SYNC_COMMAND_LIMIT`]. Temporary IDs resolved by an
+    /// earlier chunk are replaced with real IDs before the next chunk is sent, so commands can
+    /// reference any object created earlier in the batch. A command rejected by the server is
+    /// reported in the returned [`BatchResult`]; an error is only returned when a request itself
+    /// fails, in which case the commands of previous chunks have already been applied. A batch
+    /// holding a [`Batch::serialization_error`] is rejected before anything is sent.
+    pub async fn execute_batch(&self, batch: &Batch) -> Result<BatchResult> {
+        if let Some(error) = batch.serialization_error() {
+            bail!("batch not sent: {error}");
+        }
+        let url = format!("{}/sync", self.sync_base_url);
+        let mut result = BatchResult::default();
+
+        for (index, chunk) in batch::chunks(batch).enumerate() {
+            let commands = result.resolve_temp_ids(chunk);
+            let form = [("commands", serde_json::to_string(&commands)?)];
+
+            let response = self
+                .send(self.client.post(&url).form(&form))
+                .await?
+                .error_for_status()
+                .with_context(|| {
+                    format!(
+                        "batch chunk {} of {} failed",
+                        index + 1,
+                        batch.len().div_ceil(SYNC_COMMAND_LIMIT)
+                    )
+                })?;
+
+            let response: SyncCommandResponse = response.json().await?;
+            result.record_chunk(&commands, response);
+        }
+
+        Ok(result)
+    }
+
+    /// Execute a batch and fail with the first command the server rejected
+    async fn execute_command(&self, batch: &Batch) -> Result<BatchResult> {
+        let result = self.execute_batch(batch).await?;
+        for rejected in result.errors() {
+            if let CommandStatus::Error { code, message } = &rejected.status {
+                bail!("{} rejected: {message} (error {code})", rejected.command_type);
+            }
+        }
+        Ok(result)
+    }
+
     // ===== CONVENIENCE METHODS =====
 
     /// Create a simple task with just content
@@ -806,3 +1718,32 @@ impl TodoistWrapper {
         self.update_task(task_id, &args).await
     }
 }
+
+/// Stream the items of an endpoint paginated by offset, starting at `offset`
+///
+/// The page size is clamped to `1..=max_limit`, and pages are fetched until one comes back short.
+fn paginate<'a, T, F, Fut>(
+    offset: Option<i32>,
+    limit: Option<i32>,
+    max_limit: i32,
+    fetch: F,
+) -> impl Stream<Item = Result<T>> + 'a
+where
+    T: 'a,
+    F: Fn(i32, i32) -> Fut + 'a,
+    Fut: Future<Output = Result<Vec<T>>> + 'a,
+{
+    let limit = limit.unwrap_or(max_limit).clamp(1, max_limit);
+    stream::try_unfold(Some(offset.unwrap_or(0)), move |next| {
+        let page = next.map(|offset| (offset, fetch(offset, limit)));
+        async move {
+            let Some((offset, page)) = page else {
+                return Ok::<_, anyhow::Error>(None);
+            };
+            let page = page.await?;
+            let next = (page.len() >= limit as usize).then_some(offset + limit);
+            Ok(Some((stream::iter(page.into_iter().map(Ok::<_, anyhow::Error>)), next)))
+        }
+    })
+    .try_flatten()
+}
@@ -0,0 +1,300 @@
+mod common;
+
+use serde_json::{json, Value};
+use todoist_api::backup::BACKUP_VERSION;
+use todoist_api::*;
+use wiremock::matchers::{body_string_contains, method, path, query_param};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use common::{request_commands, wrapper};
+
+/// Mock Sync API that accepts every command and maps each temporary ID to `new-<temp_id>`
+fn accept_all(request: &Request) -> ResponseTemplate {
+    let commands = request_commands(request);
+    let sync_status: serde_json::Map<String, Value> = commands
+        .iter()
+        .map(|command| (command["uuid"].as_str().unwrap().to_string(), json!("ok")))
+        .collect();
+    let temp_id_mapping: serde_json::Map<String, Value> = commands
+        .iter()
+        .filter_map(|command| command["temp_id"].as_str())
+        .map(|temp_id| (temp_id.to_string(), json!(format!("new-{temp_id}"))))
+        .collect();
+    ResponseTemplate::new(200).set_body_json(json!({
+        "sync_status": sync_status,
+        "temp_id_mapping": temp_id_mapping,
+    }))
+}
+
+fn item(id: &str, project_id: &str, parent_id: Option<&str>, order: i32) -> Value {
+    json!({
+        "id": id,
+        "project_id": project_id,
+        "content": format!("Task {id}"),
+        "parent_id": parent_id,
+        "section_id": "s1",
+        "child_order": order,
+        "priority": 2,
+        "labels": ["client"],
+        "due": { "date": "2024-05-06", "string": "every monday", "is_recurring": true },
+        "responsible_uid": "u1",
+    })
+}
+
+/// Mock an account with an inbox, a project with a sub-project, an archived project and section,
+/// tasks, comments and history
+async fn mount_account(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("resource_types"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "sync_token": "token",
+            "full_sync": true,
+            "projects": [
+                { "id": "inbox", "name": "Inbox", "inbox_project": true, "child_order": 0 },
+                { "id": "p2", "name": "Phase 1", "parent_id": "p1", "child_order": 1, "color": "red" },
+                { "id": "p1", "name": "Client", "child_order": 2, "color": "blue" },
+                { "id": "p3", "name": "Gone", "is_deleted": true },
+            ],
+            "sections": [
+                { "id": "s1", "name": "Backlog", "project_id": "p2", "section_order": 1 },
+                { "id": "s3", "name": "Done", "project_id": "p1", "section_order": 2, "is_archived": true },
+            ],
+            "items": [
+                item("t2", "p2", Some("t1"), 1),
+                item("t1", "p2", None, 2),
+            ],
+            "labels": [{ "id": "l1", "name": "client", "color": "green", "item_order": 1 }],
+            "notes": [{
+                "id": "n1",
+                "item_id": "t1",
+                "content": "Contract attached",
+                "posted_at": "2024-05-01T10:00:00Z",
+                "file_attachment": {
+                    "file_name": "contract.pdf",
+                    "file_type": "application/pdf",
+                    "file_url": "https://files.todoist.com/contract.pdf",
+                    "resource_type": "file",
+                },
+            }],
+            "project_notes": [{ "id": "n2", "project_id": "p1", "content": "Kickoff notes" }],
+            "filters": [{ "id": "f1", "name": "Client work", "query": "#Client", "item_order": 1 }],
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/projects/get_archived"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "id": "a1", "name": "Old client", "child_order": 3, "is_archived": true },
+        ])))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/projects/get_data"))
+        .and(query_param("project_id", "a1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "project": { "id": "a1", "name": "Old client", "is_archived": true },
+            "items": [{ "id": "t5", "project_id": "a1", "content": "Task t5", "child_order": 1 }],
+            "sections": [],
+            "project_notes": [{ "id": "n3", "project_id": "a1", "content": "Wrap-up" }],
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/completed/get_all"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "items": [{
+                "id": "c1",
+                "task_id": "t9",
+                "content": "Sign contract",
+                "project_id": "p1",
+                "section_id": null,
+                "completed_at": "2024-05-01T10:00:00Z",
+                "note_count": 0,
+            }],
+            "projects": {},
+        })))
+        .mount(server)
+        .await;
+}
+
+/// Mock a target account holding only an inbox and a label named like one in the backup
+async fn mount_target(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("resource_types"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "sync_token": "token",
+            "full_sync": true,
+            "projects": [{ "id": "target-inbox", "name": "Inbox", "inbox_project": true }],
+            "labels": [{ "id": "target-l1", "name": "Client" }],
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("commands"))
+        .respond_with(accept_all)
+        .mount(server)
+        .await;
+}
+
+async fn account_backup() -> Backup {
+    let server = MockServer::start().await;
+    mount_account(&server).await;
+    wrapper(&server).backup().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_backup_snapshots_account() {
+    let backup = account_backup().await;
+    assert_eq!(backup.format, "todoist-api-backup");
+    assert_eq!(backup.version, BACKUP_VERSION);
+    assert_eq!(backup.projects.len(), 3);
+    assert_eq!(backup.archived_projects[0].name, "Old client");
+    assert_eq!(backup.sections.len(), 1);
+    assert_eq!(backup.archived_sections[0].name, "Done");
+    assert_eq!(backup.tasks.len(), 3);
+    assert!(backup.tasks.iter().any(|task| task.project_id == "a1"));
+    assert_eq!(backup.completed_tasks[0].content, "Sign contract");
+    assert_eq!(backup.labels[0].name, "client");
+    assert_eq!(backup.comments.len(), 3);
+    assert_eq!(
+        backup.comments[0].attachment.as_ref().unwrap().file_name,
+        "contract.pdf"
+    );
+    assert_eq!(backup.filters[0].query, "#Client");
+}
+
+#[tokio::test]
+async fn test_backup_roundtrip() {
+    let backup = account_backup().await;
+
+    let json = backup.to_bytes(BackupFormat::Json).unwrap();
+    assert!(json.starts_with(b"{"));
+    assert_eq!(Backup::from_bytes(&json).unwrap(), backup);
+
+    let zip = backup.to_bytes(BackupFormat::Zip).unwrap();
+    assert!(zip.starts_with(b"PK"));
+    assert_eq!(Backup::from_bytes(&zip).unwrap(), backup);
+
+    let path = std::env::temp_dir().join(format!("todoist-backup-{}.zip", uuid::Uuid::new_v4()));
+    backup.save(&path).await.unwrap();
+    assert!(std::fs::read(&path).unwrap().starts_with(b"PK"));
+    assert_eq!(Backup::load(&path).await.unwrap(), backup);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_backup_rejects_foreign_and_newer_files() {
+    assert!(Backup::from_bytes(b"not json").is_err());
+
+    let backup = json!({
+        "format": "todoist-api-backup",
+        "version": BACKUP_VERSION + 1,
+        "created_at": "2024-05-01T10:00:00Z",
+        "projects": [], "sections": [], "tasks": [], "completed_tasks": [],
+        "labels": [], "comments": [], "filters": [],
+    });
+    let error = Backup::from_bytes(backup.to_string().as_bytes()).unwrap_err();
+    assert!(error.to_string().contains("newer than the supported version"));
+
+    let mut other = backup;
+    other["format"] = json!("something-else");
+    other["version"] = json!(1);
+    let error = Backup::from_bytes(other.to_string().as_bytes()).unwrap_err();
+    assert!(error.to_string().contains("not a backup"));
+}
+
+#[tokio::test]
+async fn test_restore_dry_run_sends_no_commands() {
+    let backup = account_backup().await;
+    let server = MockServer::start().await;
+    mount_target(&server).await;
+
+    let report = wrapper(&server).restore_backup(&backup, true).await.unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.projects, 3);
+    assert_eq!(report.sections, 2);
+    assert_eq!(report.tasks, 3);
+    assert_eq!(report.completed_tasks, 1);
+    assert_eq!(report.comments, 3);
+    assert_eq!(report.labels, 0);
+    assert_eq!(report.filters, 1);
+    assert_eq!(report.archived, 2);
+    assert_eq!(report.skipped, vec!["label client already exists"]);
+    assert_eq!(report.id_mapping["inbox"], "target-inbox");
+    assert_eq!(report.id_mapping["l1"], "target-l1");
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+}
+
+#[tokio::test]
+async fn test_restore_remaps_ids_and_keeps_hierarchy() {
+    let backup = account_backup().await;
+    let server = MockServer::start().await;
+    mount_target(&server).await;
+
+    let report = wrapper(&server).restore_backup(&backup, false).await.unwrap();
+    assert!(!report.dry_run);
+    assert!(report.failed.is_empty());
+
+    let requests = server.received_requests().await.unwrap();
+    let commands: Vec<Value> = requests
+        .iter()
+        .filter(|request| String::from_utf8_lossy(&request.body).contains("commands"))
+        .flat_map(request_commands)
+        .collect();
+    let temp_id = |index: usize| commands[index]["temp_id"].as_str().unwrap().to_string();
+    let types: Vec<_> = commands.iter().map(|c| c["type"].as_str().unwrap()).collect();
+    assert_eq!(
+        types,
+        vec![
+            "project_add",
+            "project_add",
+            "project_add",
+            "section_add",
+            "section_add",
+            "item_add",
+            "item_add",
+            "item_add",
+            "item_add",
+            "item_close",
+            "note_add",
+            "project_note_add",
+            "project_note_add",
+            "filter_add",
+            "section_archive",
+            "project_archive",
+        ]
+    );
+
+    // Parents are created first and referenced through their temporary IDs
+    assert_eq!(commands[0]["args"]["name"], "Client");
+    assert_eq!(commands[1]["args"]["name"], "Old client");
+    assert_eq!(commands[2]["args"]["parent_id"], temp_id(0));
+    assert_eq!(commands[3]["args"]["project_id"], temp_id(2));
+    assert_eq!(commands[4]["args"]["project_id"], temp_id(0));
+    assert_eq!(commands[5]["args"]["project_id"], temp_id(1));
+    assert_eq!(commands[6]["args"]["content"], "Task t1");
+    assert_eq!(commands[6]["args"]["section_id"], temp_id(3));
+    assert_eq!(commands[6]["args"]["due"], json!({ "string": "every monday" }));
+    assert!(commands[6]["args"].get("responsible_uid").is_none());
+    assert_eq!(commands[7]["args"]["parent_id"], temp_id(6));
+    assert_eq!(commands[8]["args"]["content"], "Sign contract");
+    assert_eq!(commands[9]["args"]["id"], temp_id(8));
+    assert_eq!(commands[10]["args"]["item_id"], temp_id(6));
+    assert_eq!(commands[10]["args"]["file_attachment"]["file_name"], "contract.pdf");
+    assert_eq!(commands[11]["args"]["project_id"], temp_id(0));
+    assert_eq!(commands[12]["args"]["project_id"], temp_id(1));
+
+    // Archived projects and sections are archived again once their content exists
+    assert_eq!(commands[14]["args"]["id"], temp_id(4));
+    assert_eq!(commands[15]["args"]["id"], temp_id(1));
+
+    assert_eq!(report.id_mapping["p1"], format!("new-{}", temp_id(0)));
+    assert_eq!(report.id_mapping["a1"], format!("new-{}", temp_id(1)));
+    assert_eq!(report.id_mapping["t2"], format!("new-{}", temp_id(7)));
+    assert_eq!(report.id_mapping["inbox"], "target-inbox");
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use serde_json::json;
+use todoist_api::*;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use common::{request_commands, wrapper};
+
+/// Mock Sync API that accepts every command and assigns `real-<n>` IDs
+fn accept_all(request: &Request) -> ResponseTemplate {
+    let commands = request_commands(request);
+    let mut sync_status = serde_json::Map::new();
+    let mut temp_id_mapping = serde_json::Map::new();
+    for command in &commands {
+        let uuid = command["uuid"].as_str().unwrap();
+        sync_status.insert(uuid.to_string(), json!("ok"));
+        if let Some(temp_id) = command["temp_id"].as_str() {
+            let real_id = format!("real-{}", &temp_id[..8]);
+            temp_id_mapping.insert(temp_id.to_string(), json!(real_id));
+        }
+    }
+    ResponseTemplate::new(200).set_body_json(json!({
+        "sync_status": sync_status,
+        "temp_id_mapping": temp_id_mapping,
+    }))
+}
+
+#[test]
+fn test_batch_chains_temp_ids() {
+    let mut batch = Batch::new();
+    assert!(batch.is_empty());
+
+    let project_id = batch.add_project(&CreateProjectArgs {
+        name: "Client".to_string(),
+        ..Default::default()
+    });
+    let section_id = batch.add_section(&CreateSectionArgs {
+        name: "Week 1".to_string(),
+        project_id: project_id.clone(),
+        order: Some(1),
+    });
+    batch.add_task(&CreateTaskArgs {
+        content: "Kick-off".to_string(),
+        project_id: Some(project_id.clone()),
+        section_id: Some(section_id.clone()),
+        order: Some(2),
+        assignee_id: Some("user_1".to_string()),
+        due_string: Some("tomorrow".to_string()),
+        duration: Some(30),
+        ..Default::default()
+    });
+
+    let commands = batch.commands();
+    assert_eq!(commands.len(), 3);
+    assert_eq!(commands[0].command_type, "project_add");
+    assert_eq!(commands[0].temp_id.as_deref(), Some(project_id.as_str()));
+    assert_eq!(commands[1].command_type, "section_add");
+    assert_eq!(commands[1].args["project_id"], json!(project_id));
+    assert_eq!(commands[1].args["section_order"], json!(1));
+    assert_eq!(commands[2].command_type, "item_add");
+    assert_eq!(commands[2].args["section_id"], json!(section_id));
+    assert_eq!(commands[2].args["child_order"], json!(2));
+    assert_eq!(commands[2].args["responsible_uid"], json!("user_1"));
+    assert_eq!(commands[2].args["due"], json!({ "string": "tomorrow" }));
+    assert_eq!(commands[2].args["duration"], json!({ "amount": 30, "unit": "minute" }));
+}
+
+#[test]
+fn test_batch_command_types() {
+    let mut batch = Batch::new();
+    batch.complete_task("1");
+    batch.reopen_task("1");
+    batch.delete_task("1");
+    batch.update_section(
+        "2",
+        &UpdateSectionArgs {
+            name: "Renamed".to_string(),
+        },
+    );
+    batch.add_comment(&CreateCommentArgs {
+        content: "On the project".to_string(),
+        project_id: Some("3".to_string()),
+        ..Default::default()
+    });
+    batch.add_comment(&CreateCommentArgs {
+        content: "On the task".to_string(),
+        task_id: Some("1".to_string()),
+        ..Default::default()
+    });
+
+    let types: Vec<&str> = batch.commands().iter().map(|c| c.command_type.as_str()).collect();
+    assert_eq!(
+        types,
+        vec![
+            "item_close",
+            "item_uncomplete",
+            "item_delete",
+            "section_update",
+            "project_note_add",
+            "note_add"
+        ]
+    );
+    assert_eq!(batch.commands()[5].args["item_id"], json!("1"));
+    assert!(batch.commands()[0].temp_id.is_none());
+}
+
+#[tokio::test]
+async fn test_execute_batch_reports_real_ids() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(header("Authorization", "Bearer test-token"))
+        .respond_with(accept_all)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut batch = Batch::new();
+    let project_id = batch.add_project(&CreateProjectArgs {
+        name: "Client".to_string(),
+        ..Default::default()
+    });
+    let delete_uuid = batch.delete_label("42");
+
+    let result = wrapper(&server).execute_batch(&batch).await.unwrap();
+    assert!(result.is_success());
+    assert_eq!(result.results.len(), 2);
+    assert_eq!(
+        result.real_id(&project_id),
+        Some(format!("real-{}", &project_id[..8]).as_str())
+    );
+    assert_eq!(result.results[0].id.as_deref(), result.real_id(&project_id));
+    assert_eq!(result.results[1].uuid, delete_uuid);
+    assert!(result.results[1].id.is_none());
+}
+
+#[tokio::test]
+async fn test_execute_batch_reports_command_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .respond_with(|request: &Request| {
+            let commands = request_commands(request);
+            ResponseTemplate::new(200).set_body_json(json!({
+                "sync_status": {
+                    commands[0]["uuid"].as_str().unwrap(): "ok",
+                    commands[1]["uuid"].as_str().unwrap(): {
+                        "error_code": 22,
+                        "error": "Item not found",
+                    },
+                },
+                "temp_id_mapping": {},
+            }))
+        })
+        .mount(&server)
+        .await;
+
+    let mut batch = Batch::new();
+    batch.complete_task("1");
+    batch.complete_task("missing");
+
+    let result = wrapper(&server).execute_batch(&batch).await.unwrap();
+    assert!(!result.is_success());
+    let errors = result.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].status,
+        CommandStatus::Error {
+            code: 22,
+            message: "Item not found".to_string()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_execute_batch_splits_and_resolves_across_chunks() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .respond_with(accept_all)
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let mut batch = Batch::new();
+    let project_id = batch.add_project(&CreateProjectArgs {
+        name: "Big project".to_string(),
+        ..Default::default()
+    });
+    for i in 0..batch::SYNC_COMMAND_LIMIT {
+        batch.add_task(&CreateTaskArgs {
+            content: format!("Task {i}"),
+            project_id: Some(project_id.clone()),
+            ..Default::default()
+        });
+    }
+
+    let result = wrapper(&server).execute_batch(&batch).await.unwrap();
+    assert!(result.is_success());
+    assert_eq!(result.results.len(), batch::SYNC_COMMAND_LIMIT + 1);
+
+    // The last task was sent in the second request, after the project temp ID was resolved
+    let requests = server.received_requests().await.unwrap();
+    let second_chunk = request_commands(&requests[1]);
+    assert_eq!(second_chunk.len(), 1);
+    assert_eq!(
+        second_chunk[0]["args"]["project_id"],
+        json!(result.real_id(&project_id).unwrap())
+    );
+}
@@ -1,4 +1,63 @@
+// Each test binary uses its own subset of the helpers
+#![allow(dead_code)]
+
+use serde_json::{json, Value};
 use todoist_api::*;
+use wiremock::{MockServer, Request};
+
+/// Client sending both REST and Sync API requests to a mock server
+pub fn wrapper(server: &MockServer) -> TodoistWrapper {
+    TodoistWrapper::with_base_urls("test-token".to_string(), &server.uri(), &server.uri())
+}
+
+/// Decode the commands of a Sync API request
+pub fn request_commands(request: &Request) -> Vec<Value> {
+    let (_, commands) = url::form_urlencoded::parse(&request.body)
+        .find(|(key, _)| key == "commands")
+        .expect("commands field");
+    serde_json::from_str(&commands).unwrap()
+}
+
+/// REST API task in project `p1`, without a section, parent, due date or labels
+pub fn task_json(id: &str, content: &str) -> Value {
+    json!({
+        "id": id,
+        "content": content,
+        "description": "",
+        "project_id": "p1",
+        "section_id": null,
+        "parent_id": null,
+        "order": 1,
+        "priority": 1,
+        "is_completed": false,
+        "labels": [],
+        "created_at": "2024-01-01T00:00:00Z",
+        "due": null,
+        "deadline": null,
+        "duration": null,
+        "assignee_id": null,
+        "url": format!("https://todoist.com/showTask?id={id}"),
+        "comment_count": 0,
+    })
+}
+
+/// REST API top-level project
+pub fn project_json(id: &str, name: &str) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "comment_count": 0,
+        "order": 1,
+        "color": "grey",
+        "is_shared": false,
+        "is_favorite": false,
+        "is_inbox_project": false,
+        "is_team_inbox": false,
+        "view_style": "list",
+        "url": format!("https://todoist.com/showProject?id={id}"),
+        "parent_id": null,
+    })
+}
 
 /// Create a test task with minimal required fields
 pub fn create_test_task() -> Task {
@@ -95,7 +154,7 @@ pub fn create_test_task_args() -> CreateTaskArgs {
         project_id: Some("test_project_123".to_string()),
         priority: Some(3),
         labels: Some(vec!["test".to_string(), "important".to_string()]),
-        due_string: Some("tomorrow"),
+        due_string: Some("tomorrow".to_string()),
         ..Default::default()
     }
 }
@@ -285,6 +344,6 @@ mod tests {
     #[test]
     fn test_create_test_wrapper() {
         let wrapper = create_test_wrapper();
-        assert!(!wrapper.api_token.is_empty());
+        assert!(format!("{wrapper:?}").contains("TodoistWrapper"));
     }
 }
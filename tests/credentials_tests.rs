@@ -0,0 +1,138 @@
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use todoist_api::credentials::*;
+use todoist_api::*;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn mount_projects(server: &MockServer, token: &str) {
+    Mock::given(method("GET"))
+        .and(path("/projects"))
+        .and(header("Authorization", format!("Bearer {token}").as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(server)
+        .await;
+    // Any other token is rejected
+    Mock::given(method("GET"))
+        .and(path("/projects"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(server)
+        .await;
+}
+
+fn wrapper(server: &MockServer, credentials: impl CredentialProvider + 'static) -> TodoistWrapper {
+    TodoistWrapper::with_credentials_and_base_urls(credentials, &server.uri(), &server.uri())
+}
+
+/// A cached token whose source hands out `token-1`, `token-2`, ... on each fetch
+fn rotating_token(fetches: Arc<AtomicUsize>, ttl: Duration) -> CachedToken {
+    CachedToken::new(ttl, move || {
+        let count = fetches.fetch_add(1, Ordering::SeqCst) + 1;
+        async move { Ok(format!("token-{count}")) }
+    })
+}
+
+#[tokio::test]
+async fn test_static_token() {
+    let server = MockServer::start().await;
+    mount_projects(&server, "static-token").await;
+
+    let todoist = wrapper(&server, StaticToken::new("static-token"));
+    assert!(todoist.get_projects().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_env_token_is_read_on_each_request() {
+    let server = MockServer::start().await;
+    mount_projects(&server, "env-token-2").await;
+
+    let variable = "TODOIST_API_CREDENTIALS_TEST_TOKEN";
+    std::env::set_var(variable, "env-token-1");
+    let todoist = wrapper(&server, EnvToken::new(variable));
+    assert!(todoist.get_projects().await.is_err());
+
+    std::env::set_var(variable, "env-token-2");
+    assert!(todoist.get_projects().await.is_ok());
+
+    std::env::remove_var(variable);
+    let error = todoist.get_projects().await.unwrap_err();
+    assert!(error.to_string().contains(variable));
+}
+
+#[tokio::test]
+async fn test_file_token_is_trimmed() {
+    let server = MockServer::start().await;
+    mount_projects(&server, "file-token").await;
+
+    let token_path = std::env::temp_dir().join(format!("todoist-token-{}", uuid::Uuid::new_v4()));
+    std::fs::write(&token_path, "file-token\n").unwrap();
+    let todoist = wrapper(&server, FileToken::new(&token_path));
+    assert!(todoist.get_projects().await.is_ok());
+    std::fs::remove_file(token_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_cached_token_is_fetched_once() {
+    let server = MockServer::start().await;
+    mount_projects(&server, "token-1").await;
+
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let todoist = wrapper(&server, rotating_token(fetches.clone(), Duration::from_secs(60)));
+    for _ in 0..3 {
+        todoist.get_projects().await.unwrap();
+    }
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_cached_token_expires() {
+    let credentials = rotating_token(Arc::new(AtomicUsize::new(0)), Duration::ZERO);
+    assert_eq!(credentials.token().await.unwrap(), "token-1");
+    assert_eq!(credentials.token().await.unwrap(), "token-2");
+
+    let credentials = rotating_token(Arc::new(AtomicUsize::new(0)), Duration::from_secs(60));
+    assert_eq!(credentials.token().await.unwrap(), "token-1");
+    credentials.invalidate().await;
+    assert_eq!(credentials.token().await.unwrap(), "token-2");
+}
+
+#[tokio::test]
+async fn test_unauthorized_refetches_and_retries_once() {
+    let server = MockServer::start().await;
+    // The secret store has already rotated: the first fetched token is stale
+    mount_projects(&server, "token-2").await;
+
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let todoist = wrapper(&server, rotating_token(fetches.clone(), Duration::from_secs(60)));
+    assert!(todoist.get_projects().await.unwrap().is_empty());
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+    // The new token is kept for later requests
+    todoist.get_projects().await.unwrap();
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_unauthorized_without_new_token_is_not_retried() {
+    let server = MockServer::start().await;
+    mount_projects(&server, "valid-token").await;
+
+    let todoist = wrapper(&server, StaticToken::new("revoked-token"));
+    assert!(todoist.get_projects().await.is_err());
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[test]
+fn test_debug_redacts_tokens() {
+    let debug = format!(
+        "{:?} {:?} {:?}",
+        TodoistWrapper::new("secret-token".to_string()),
+        StaticToken::new("secret-token"),
+        CachedToken::new(Duration::from_secs(1), || async { Ok("secret-token".to_string()) }),
+    );
+    assert!(!debug.contains("secret-token"));
+    assert!(debug.contains("[redacted]"));
+}
@@ -0,0 +1,257 @@
+mod common;
+
+use serde_json::{json, Value};
+use todoist_api::csv_template::{export_csv, import_csv};
+use todoist_api::*;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use common::wrapper;
+
+fn project() -> Project {
+    serde_json::from_value(json!({
+        "id": "p1",
+        "name": "Client",
+        "comment_count": 0,
+        "order": 1,
+        "color": "blue",
+        "is_shared": false,
+        "is_favorite": false,
+        "is_inbox_project": false,
+        "is_team_inbox": false,
+        "view_style": "list",
+        "url": "https://todoist.com/showProject?id=p1",
+        "parent_id": null,
+    }))
+    .unwrap()
+}
+
+fn section(id: &str, name: &str, order: i32) -> Value {
+    json!({ "id": id, "name": name, "project_id": "p1", "order": order, "url": "https://todoist.com" })
+}
+
+fn task(id: &str, content: &str, section_id: Option<&str>, parent_id: Option<&str>, order: i32) -> Value {
+    json!({
+        "id": id,
+        "content": content,
+        "description": "",
+        "project_id": "p1",
+        "section_id": section_id,
+        "parent_id": parent_id,
+        "order": order,
+        "priority": 1,
+        "is_completed": false,
+        "labels": [],
+        "created_at": "2024-01-01T00:00:00Z",
+        "due": null,
+        "deadline": null,
+        "duration": null,
+        "assignee_id": null,
+        "url": "https://todoist.com",
+        "comment_count": 0,
+    })
+}
+
+fn tasks() -> Vec<Task> {
+    let mut urgent = task("t1", "Kickoff call", Some("s1"), None, 1);
+    urgent["priority"] = json!(4);
+    urgent["description"] = json!("Agenda in the doc");
+    urgent["due"] = json!({
+        "string": "every monday at 9am",
+        "date": "2024-05-06",
+        "datetime": "2024-05-06T09:00:00",
+        "is_recurring": true,
+        "timezone": "Europe/Berlin",
+    });
+    urgent["duration"] = json!({ "amount": 30, "unit": "minute" });
+    urgent["deadline"] = json!({ "date": "2024-06-01" });
+    serde_json::from_value(json!([
+        task("t4", "Send invoice", None, None, 1),
+        task("t3", "Book room", Some("s1"), Some("t1"), 2),
+        task("t2", "Send agenda", Some("s1"), Some("t1"), 1),
+        task("t5", "Confirm", Some("s1"), Some("t2"), 1),
+        urgent,
+        task("t6", "Retro", Some("s2"), None, 1),
+        task("other", "Elsewhere", None, None, 1),
+    ]))
+    .map(|mut tasks: Vec<Task>| {
+        tasks.last_mut().unwrap().project_id = "p2".to_string();
+        tasks
+    })
+    .unwrap()
+}
+
+fn sections() -> Vec<Section> {
+    serde_json::from_value(json!([section("s2", "Wrap-up", 2), section("s1", "Onboarding", 1)])).unwrap()
+}
+
+#[test]
+fn test_export_csv() {
+    let csv = export_csv(&project(), &sections(), &tasks()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,RESPONSIBLE,DATE,DATE_LANG,TIMEZONE,DURATION,DURATION_UNIT,DEADLINE,DEADLINE_LANG",
+            "task,Send invoice,,4,1,,,,,,,,,",
+            "section,Onboarding,,,,,,,,,,,,",
+            "task,Kickoff call,Agenda in the doc,1,1,,,every monday at 9am,,Europe/Berlin,30,minute,2024-06-01,",
+            "task,Send agenda,,4,2,,,,,,,,,",
+            "task,Confirm,,4,3,,,,,,,,,",
+            "task,Book room,,4,2,,,,,,,,,",
+            "section,Wrap-up,,,,,,,,,,,,",
+            "task,Retro,,4,1,,,,,,,,,",
+        ]
+    );
+}
+
+#[test]
+fn test_import_csv_preserves_structure() {
+    let csv = "\
+TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,RESPONSIBLE,DATE,DATE_LANG,TIMEZONE,DURATION,DURATION_UNIT
+meta,view_style,list,,,,,,,,,
+note,Project brief,,,,,,,,,,
+task,Send invoice,,4,1,,,,,,,
+
+section,Onboarding,,,,,,,,,,
+task,\"Kickoff call, with client\",Agenda,1,1,,,every monday,en,,30,minute
+note,Dial-in in the invite,,,,,,,,,,
+task,Send agenda,,,2,,,,,,,
+task,Confirm,,,3,,,,,,,
+task,Book room,,,2,,,,,,,
+";
+    let batch = import_csv(csv, "p9").unwrap();
+    let commands = batch.commands();
+    let types: Vec<_> = commands.iter().map(|c| c.command_type.as_str()).collect();
+    assert_eq!(
+        types,
+        vec![
+            "project_note_add",
+            "item_add",
+            "section_add",
+            "item_add",
+            "note_add",
+            "item_add",
+            "item_add",
+            "item_add"
+        ]
+    );
+    let temp_id = |index: usize| Value::from(commands[index].temp_id.clone().unwrap());
+
+    assert_eq!(commands[0].args["project_id"], "p9");
+    assert!(commands[1].args.get("section_id").is_none());
+    assert_eq!(commands[1].args["priority"], 1);
+    assert_eq!(commands[2].args, json!({ "name": "Onboarding", "project_id": "p9" }));
+
+    let kickoff = &commands[3].args;
+    assert_eq!(kickoff["content"], "Kickoff call, with client");
+    assert_eq!(kickoff["description"], "Agenda");
+    assert_eq!(kickoff["priority"], 4);
+    assert_eq!(kickoff["section_id"], temp_id(2));
+    assert_eq!(kickoff["due"], json!({ "string": "every monday", "lang": "en" }));
+    assert_eq!(kickoff["duration"], json!({ "amount": 30, "unit": "minute" }));
+    assert_eq!(commands[4].args["item_id"], temp_id(3));
+
+    assert_eq!(commands[5].args["parent_id"], temp_id(3));
+    assert_eq!(commands[6].args["parent_id"], temp_id(5));
+    assert_eq!(commands[7].args["parent_id"], temp_id(3));
+    assert_eq!(commands[7].args["section_id"], temp_id(2));
+}
+
+#[test]
+fn test_import_csv_errors() {
+    let header = "TYPE,CONTENT,PRIORITY,INDENT\n";
+    let error = import_csv(&format!("{header}task,Orphan,,2\n"), "p1").unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("line 2: INDENT 2 has no parent task at level 1"));
+
+    let error = import_csv(&format!("{header}task,Urgent,p1,1\n"), "p1").unwrap_err();
+    assert!(error.to_string().contains("invalid PRIORITY"));
+
+    let error = import_csv(&format!("{header}heading,Unknown,,\n"), "p1").unwrap_err();
+    assert!(error.to_string().contains("unknown TYPE"));
+
+    assert!(import_csv("CONTENT,INDENT\nTask,1\n", "p1").is_err());
+}
+
+#[test]
+fn test_export_import_roundtrip() {
+    let csv = export_csv(&project(), &sections(), &tasks()).unwrap();
+    let batch = import_csv(&csv, "p1").unwrap();
+    let contents: Vec<_> = batch
+        .commands()
+        .iter()
+        .map(|command| {
+            command.args["content"]
+                .as_str()
+                .or(command.args["name"].as_str())
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(
+        contents,
+        vec![
+            "Send invoice",
+            "Onboarding",
+            "Kickoff call",
+            "Send agenda",
+            "Confirm",
+            "Book room",
+            "Wrap-up",
+            "Retro"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_export_and_import_project_csv() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/projects/p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::to_value(project()).unwrap()))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/sections"))
+        .and(query_param("project_id", "p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([section("s1", "Onboarding", 1)])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/tasks"))
+        .and(query_param("project_id", "p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([task(
+            "t1",
+            "Kickoff call",
+            Some("s1"),
+            None,
+            1
+        )])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .respond_with(|request: &Request| {
+            let (_, commands) = url::form_urlencoded::parse(&request.body)
+                .find(|(key, _)| key == "commands")
+                .unwrap();
+            let commands: Vec<Value> = serde_json::from_str(&commands).unwrap();
+            let sync_status: serde_json::Map<String, Value> = commands
+                .iter()
+                .map(|command| (command["uuid"].as_str().unwrap().to_string(), json!("ok")))
+                .collect();
+            ResponseTemplate::new(200).set_body_json(json!({ "sync_status": sync_status, "temp_id_mapping": {} }))
+        })
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    let csv = todoist.export_project_csv("p1").await.unwrap();
+    assert!(csv.contains("section,Onboarding"));
+    assert!(csv.contains("task,Kickoff call,,4,1"));
+
+    let result = todoist.import_project_csv("p2", &csv).await.unwrap();
+    assert!(result.is_success());
+    assert_eq!(result.results.len(), 2);
+}
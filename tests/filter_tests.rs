@@ -0,0 +1,374 @@
+mod common;
+
+use chrono::NaiveDate;
+use todoist_api::filter::*;
+use todoist_api::*;
+
+use common::wrapper;
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn task(id: &str, content: &str, project_id: &str, priority: i32, labels: &[&str], due: Option<&str>) -> Task {
+    Task {
+        id: id.to_string(),
+        content: content.to_string(),
+        description: String::new(),
+        project_id: project_id.to_string(),
+        section_id: None,
+        parent_id: None,
+        order: 1,
+        priority,
+        is_completed: false,
+        labels: labels.iter().map(|l| l.to_string()).collect(),
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+        due: due.map(|d| Due {
+            string: d.to_string(),
+            date: d.to_string(),
+            is_recurring: false,
+            datetime: None,
+            timezone: None,
+        }),
+        deadline: None,
+        duration: None,
+        assignee_id: None,
+        url: "https://todoist.com".to_string(),
+        comment_count: 0,
+    }
+}
+
+fn project(id: &str, name: &str, parent_id: Option<&str>) -> Project {
+    Project {
+        id: id.to_string(),
+        name: name.to_string(),
+        comment_count: 0,
+        order: 1,
+        color: "blue".to_string(),
+        is_shared: false,
+        is_favorite: false,
+        is_inbox_project: false,
+        is_team_inbox: false,
+        view_style: "list".to_string(),
+        url: "https://todoist.com".to_string(),
+        parent_id: parent_id.map(|s| s.to_string()),
+    }
+}
+
+fn fixtures() -> (Vec<Task>, FilterContext) {
+    let mut tasks = vec![
+        task("1", "Write report", "work", 4, &["office"], Some("2024-05-01")),
+        task("2", "Call client", "clients", 3, &["phone"], Some("2024-04-28")),
+        task("3", "Buy milk", "home", 1, &[], None),
+        task(
+            "4",
+            "Plan Q3 & Q4",
+            "work",
+            2,
+            &["office", "planning"],
+            Some("2024-05-02"),
+        ),
+    ];
+    tasks[1].section_id = Some("s1".to_string());
+    tasks[1].assignee_id = Some("u2".to_string());
+    tasks[3].assignee_id = Some("u1".to_string());
+    tasks[3].due.as_mut().unwrap().is_recurring = true;
+
+    let mut context = FilterContext::new(date("2024-05-01"));
+    context.projects = vec![
+        project("work", "Work", None),
+        project("clients", "Client Projects", Some("work")),
+        project("home", "Home", None),
+    ];
+    context.sections = vec![Section {
+        id: "s1".to_string(),
+        name: "Follow-ups".to_string(),
+        project_id: "clients".to_string(),
+        order: 1,
+        url: "https://todoist.com".to_string(),
+    }];
+    context.users = vec![
+        User {
+            id: "u1".to_string(),
+            name: "Alice Martin".to_string(),
+            email: "alice@example.com".to_string(),
+            avatar_url: None,
+            is_premium: false,
+            is_business_account: false,
+        },
+        User {
+            id: "u2".to_string(),
+            name: "Bob Stone".to_string(),
+            email: "bob@example.com".to_string(),
+            avatar_url: None,
+            is_premium: false,
+            is_business_account: false,
+        },
+    ];
+    context.current_user_id = Some("u1".to_string());
+    (tasks, context)
+}
+
+fn ids(query: &str) -> Vec<String> {
+    let (tasks, context) = fixtures();
+    FilterQuery::parse(query)
+        .unwrap()
+        .filter(&tasks, &context)
+        .iter()
+        .map(|t| t.id.clone())
+        .collect()
+}
+
+#[test]
+fn test_parse_precedence() {
+    let query = FilterQuery::parse("today | overdue & !p1").unwrap();
+    assert_eq!(
+        query.queries,
+        vec![FilterExpr::Or(
+            Box::new(FilterExpr::Term(FilterTerm::DueOn(FilterDate::Today))),
+            Box::new(FilterExpr::And(
+                Box::new(FilterExpr::Term(FilterTerm::Overdue)),
+                Box::new(FilterExpr::Not(Box::new(FilterExpr::Term(FilterTerm::Priority(1))))),
+            )),
+        )]
+    );
+}
+
+#[test]
+fn test_parse_names_and_escapes() {
+    let query = FilterQuery::parse(r"##Client Projects & /Follow-ups & @office & #Plan Q3 \& Q4").unwrap();
+    let expected_terms = [
+        FilterTerm::Project {
+            name: "Client Projects".to_string(),
+            include_subprojects: true,
+        },
+        FilterTerm::Section("Follow-ups".to_string()),
+        FilterTerm::Label("office".to_string()),
+        FilterTerm::Project {
+            name: "Plan Q3 & Q4".to_string(),
+            include_subprojects: false,
+        },
+    ];
+
+    let mut terms = Vec::new();
+    let mut expr = &query.queries[0];
+    while let FilterExpr::And(left, right) = expr {
+        if let FilterExpr::Term(term) = right.as_ref() {
+            terms.insert(0, term.clone());
+        }
+        expr = left;
+    }
+    if let FilterExpr::Term(term) = expr {
+        terms.insert(0, term.clone());
+    }
+    assert_eq!(terms, expected_terms);
+}
+
+#[test]
+fn test_date_terms() {
+    assert_eq!(ids("today"), vec!["1"]);
+    assert_eq!(ids("tomorrow"), vec!["4"]);
+    assert_eq!(ids("overdue"), vec!["2"]);
+    assert_eq!(ids("od"), vec!["2"]);
+    assert_eq!(ids("no date"), vec!["3"]);
+    assert_eq!(ids("2024-04-28"), vec!["2"]);
+    assert_eq!(ids("due before: tomorrow"), vec!["1", "2"]);
+    assert_eq!(ids("due after: 2024-04-30"), vec!["1", "4"]);
+    assert_eq!(ids("recurring"), vec!["4"]);
+}
+
+#[test]
+fn test_priority_is_inverted() {
+    assert_eq!(ids("p1"), vec!["1"]);
+    assert_eq!(ids("p4"), vec!["3"]);
+    assert_eq!(ids("p2 | p3"), vec!["2", "4"]);
+}
+
+#[test]
+fn test_project_section_and_label_terms() {
+    assert_eq!(ids("#Work"), vec!["1", "4"]);
+    assert_eq!(ids("##Work"), vec!["1", "2", "4"]);
+    assert_eq!(ids("#client projects"), vec!["2"]);
+    assert_eq!(ids("#Cli*"), vec!["2"]);
+    assert_eq!(ids("/Follow-ups"), vec!["2"]);
+    assert_eq!(ids("@office"), vec!["1", "4"]);
+    assert_eq!(ids("@plan*"), vec!["4"]);
+    assert_eq!(ids("no labels"), vec!["3"]);
+}
+
+#[test]
+fn test_assignment_and_search_terms() {
+    assert_eq!(ids("assigned"), vec!["2", "4"]);
+    assert_eq!(ids("assigned to: me"), vec!["4"]);
+    assert_eq!(ids("assigned to: others"), vec!["2"]);
+    assert_eq!(ids("assigned to: Bob*"), vec!["2"]);
+    assert_eq!(ids("assigned to: alice@example.com"), vec!["4"]);
+    assert_eq!(ids("search: report"), vec!["1"]);
+    assert_eq!(ids(r"search: Q3 \& Q4"), vec!["4"]);
+}
+
+#[test]
+fn test_operators_and_groups() {
+    assert_eq!(ids("(today | overdue) & @office"), vec!["1"]);
+    assert_eq!(ids("!#Work & !no date"), vec!["2"]);
+    assert_eq!(ids("!(p1 | p4)"), vec!["2", "4"]);
+    assert_eq!(ids("##Work & !/Follow-ups"), vec!["1", "4"]);
+}
+
+#[test]
+fn test_multi_queries() {
+    let (tasks, context) = fixtures();
+    let query = FilterQuery::parse("today, overdue, p1").unwrap();
+    let groups: Vec<Vec<&str>> = query
+        .evaluate(&tasks, &context)
+        .iter()
+        .map(|group| group.iter().map(|t| t.id.as_str()).collect())
+        .collect();
+    assert_eq!(groups, vec![vec!["1"], vec!["2"], vec!["1"]]);
+    // Filtering flattens the groups without duplicates
+    assert_eq!(ids("today, overdue, p1"), vec!["1", "2"]);
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = |query: &str| FilterQuery::parse(query).unwrap_err();
+
+    assert_eq!(
+        error("tomorow").to_string(),
+        "unknown filter term 'tomorow' at position 0"
+    );
+    assert_eq!(error("today & p5").message, "unknown priority 'p5' (expected p1 to p4)");
+    assert_eq!(error("(today | p1").position, 0);
+    assert_eq!(error("(today | p1").message, "missing ')' to close '('");
+    assert_eq!(error("today)").message, "unexpected ')' without matching '('");
+    assert_eq!(error("today &").position, 7);
+    assert_eq!(error("today & & p1").message, "expected a filter term but found '&'");
+    assert_eq!(error("p1 & #").message, "expected a project name after '#'");
+    assert_eq!(error("due before: someday").position, 11);
+    assert_eq!(
+        error("due sometime: today").message.split(" (").next(),
+        Some("unknown filter 'due sometime:'")
+    );
+    assert_eq!(error("").message, "expected a filter term");
+}
+
+#[tokio::test]
+async fn test_store_filtering() {
+    let mut store = TodoistStore::new(TodoistWrapper::new("test-token".to_string()));
+    store.apply(
+        serde_json::from_value(serde_json::json!({
+            "sync_token": "token-1",
+            "full_sync": true,
+            "projects": [{ "id": "work", "name": "Work" }],
+            "items": [
+                { "id": "1", "project_id": "work", "content": "Report", "priority": 4, "labels": ["office"] },
+                { "id": "2", "project_id": "work", "content": "Email", "priority": 1, "labels": [] },
+            ],
+        }))
+        .unwrap(),
+    );
+
+    let context = store.filter_context(date("2024-05-01"));
+    let query = FilterQuery::parse("#Work & p1").unwrap();
+    let tasks = store.filter_tasks(&query, &context);
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].content, "Report");
+}
+
+#[test]
+fn test_builder_renders_filter_strings() {
+    let filter = Filter::due_before(date("2024-05-01"))
+        .and(Filter::label("work"))
+        .or(Filter::priority(Priority::P1))
+        .not(Filter::project("Inbox"));
+    assert_eq!(filter.to_string(), "(due before: 2024-05-01 & @work | p1) & !#Inbox");
+
+    assert_eq!(
+        Filter::project_with_subprojects("Q3 & Q4 (draft)").to_string(),
+        r"##Q3 \& Q4 \(draft\)"
+    );
+    assert_eq!(Filter::section(r"a\b, c|d").to_string(), r"/a\\b\, c\|d");
+    assert_eq!(
+        Filter::assigned_to("Bob Stone")
+            .or(Filter::assigned_to_me())
+            .to_string(),
+        "assigned to: Bob Stone | assigned to: me"
+    );
+    assert_eq!(
+        (!Filter::today().or(Filter::overdue())).to_string(),
+        "!(today | overdue)"
+    );
+    // Right-nested operators keep their grouping
+    assert_eq!(
+        Filter::no_date()
+            .and(Filter::recurring().and(Filter::no_labels()))
+            .to_string(),
+        "no date & (recurring & no labels)"
+    );
+}
+
+#[test]
+fn test_builder_round_trips_through_parser() {
+    let filters = vec![
+        Filter::today()
+            .or(Filter::tomorrow())
+            .and(!Filter::priority(Priority::P4)),
+        Filter::due_after(FilterDate::Yesterday).not(Filter::assigned()),
+        Filter::project_with_subprojects("Client Projects")
+            .and(Filter::section("Follow-ups"))
+            .and(Filter::label("office")),
+        Filter::project(r"Q3 & Q4 (plan), v2 | \ draft").or(Filter::label("a|b")),
+        Filter::search("Q3 & Q4").or(Filter::assigned_to_others()),
+        Filter::overdue().or(Filter::no_date().or(Filter::recurring())),
+        !!Filter::assigned_to("alice@example.com"),
+        Filter::due_on(date("2024-05-02")).and(Filter::no_labels()),
+    ];
+
+    for filter in filters {
+        let rendered = filter.to_string();
+        let parsed = FilterQuery::parse(&rendered).unwrap_or_else(|e| panic!("{rendered}: {e}"));
+        assert_eq!(parsed, filter.clone().into_query(), "{rendered}");
+        assert_eq!(parsed.to_string(), rendered);
+    }
+}
+
+#[test]
+fn test_parsed_queries_render_back() {
+    for query in [
+        "today | overdue & !p1",
+        "(today | overdue) & @office, ##Work & !/Follow-ups",
+        r"search: Q3 \& Q4",
+    ] {
+        let parsed = FilterQuery::parse(query).unwrap();
+        assert_eq!(FilterQuery::parse(&parsed.to_string()).unwrap(), parsed);
+    }
+}
+
+#[test]
+fn test_builder_evaluates_locally() {
+    let (tasks, context) = fixtures();
+    let query = Filter::label("office").not(Filter::recurring()).into_query();
+    let ids: Vec<&str> = query.filter(&tasks, &context).iter().map(|t| t.id.as_str()).collect();
+    assert_eq!(ids, vec!["1"]);
+}
+
+#[tokio::test]
+async fn test_get_tasks_by_filter_encodes_query() {
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/tasks"))
+        .and(query_param("filter", r"#Q3 \& Q4 & p1"))
+        .and(query_param("limit", "10"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let wrapper = wrapper(&server);
+    let mut args = TaskFilterArgs::from(Filter::project("Q3 & Q4").and(Filter::priority(Priority::P1)));
+    args.limit = Some(10);
+    assert!(wrapper.get_tasks_by_filter(&args).await.unwrap().is_empty());
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use serde_json::json;
+use todoist_api::ics_server::*;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use common::{project_json, task_json, wrapper};
+
+async fn mount_project(server: &MockServer) {
+    let mut mow = task_json("t1", "Mow the lawn");
+    mow["due"] = json!({ "string": "every saturday", "date": "2024-05-11", "is_recurring": true, "datetime": null, "timezone": null });
+    Mock::given(method("GET"))
+        .and(path("/projects/p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(project_json("p1", "Garden")))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/tasks"))
+        .and(query_param("project_id", "p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([mow])))
+        .mount(server)
+        .await;
+}
+
+/// Start a server on a random local port and return its URL
+async fn start(server: CalendarServer) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(server.serve(listener));
+    format!("http://{address}")
+}
+
+#[tokio::test]
+async fn test_serves_project_feed() {
+    let api = MockServer::start().await;
+    mount_project(&api).await;
+    let url = start(CalendarServer::new(wrapper(&api))).await;
+
+    let response = reqwest::get(format!("{url}{DEFAULT_ROUTE}/p1.ics")).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["content-type"].to_str().unwrap(),
+        "text/calendar; charset=utf-8"
+    );
+    let calendar = response.text().await.unwrap();
+    assert!(calendar.contains("X-WR-CALNAME:Garden\r\n"));
+    assert!(calendar.contains("UID:t1@todoist.com\r\n"));
+    assert!(calendar.contains("RRULE:FREQ=WEEKLY;BYDAY=SA\r\n"));
+
+    let status = |path: &str| {
+        let url = format!("{url}{path}");
+        async move { reqwest::get(url).await.unwrap().status().as_u16() }
+    };
+    assert_eq!(status("/calendars/p1.txt").await, 404);
+    assert_eq!(status("/calendars/.ics").await, 404);
+    // Unknown projects, and IDs that are not Todoist IDs
+    assert_eq!(status("/calendars/p2.ics").await, 404);
+    assert_eq!(status("/calendars/p1%3Fx=1.ics").await, 400);
+    assert_eq!(status("/calendars/..%2Fusers.ics").await, 400);
+}
+
+#[tokio::test]
+async fn test_todoist_errors_are_bad_gateway() {
+    let api = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&api)
+        .await;
+    let url = start(CalendarServer::new(wrapper(&api))).await;
+
+    let response = reqwest::get(format!("{url}{DEFAULT_ROUTE}/p1.ics")).await.unwrap();
+    assert_eq!(response.status().as_u16(), 502);
+}
+
+#[tokio::test]
+async fn test_feed_access_token() {
+    let api = MockServer::start().await;
+    mount_project(&api).await;
+    let server = CalendarServer::new(wrapper(&api))
+        .with_route("/feeds/")
+        .with_access_token("s3cret");
+    let url = start(server).await;
+
+    let status = |path: &str| {
+        let url = format!("{url}{path}");
+        async move { reqwest::get(url).await.unwrap().status().as_u16() }
+    };
+    assert_eq!(status("/feeds/p1.ics").await, 401);
+    assert_eq!(status("/feeds/p1.ics?token=wrong").await, 401);
+    assert_eq!(status("/feeds/p1.ics?token=s3cret").await, 200);
+    assert_eq!(status("/calendars/p1.ics?token=s3cret").await, 404);
+}
@@ -0,0 +1,329 @@
+mod common;
+
+use serde_json::{json, Value};
+use todoist_api::ics::{recurrence_rule, render_calendar};
+use todoist_api::*;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use common::{project_json, task_json, wrapper};
+
+fn due_task_json(id: &str, content: &str, due: Value) -> Value {
+    let mut task = task_json(id, content);
+    task["created_at"] = json!("2024-01-01T08:30:00.000000Z");
+    task["due"] = due;
+    task
+}
+
+fn task(id: &str, content: &str, due: Value) -> Task {
+    serde_json::from_value(due_task_json(id, content, due)).unwrap()
+}
+
+fn date_due(string: &str, date: &str, is_recurring: bool) -> Value {
+    json!({ "string": string, "date": date, "is_recurring": is_recurring, "datetime": null, "timezone": null })
+}
+
+/// Unfolded content lines of a calendar
+fn lines(calendar: &str) -> Vec<String> {
+    calendar
+        .replace("\r\n ", "")
+        .split("\r\n")
+        .map(str::to_string)
+        .collect()
+}
+
+fn todo_options() -> IcsOptions {
+    IcsOptions::default()
+}
+
+#[test]
+fn test_render_todos() {
+    let mut all_day = task(
+        "t1",
+        "Pay rent; utilities, etc.",
+        date_due("every month", "2024-05-01", true),
+    );
+    all_day.priority = 4;
+    all_day.labels = vec!["home".to_string(), "bills".to_string()];
+    all_day.description = "Line one\nLine two".to_string();
+    let timed = task(
+        "t2",
+        "Standup",
+        json!({
+            "string": "tomorrow at 9am",
+            "date": "2024-05-06",
+            "is_recurring": false,
+            "datetime": "2024-05-06T07:00:00.000000Z",
+            "timezone": "Europe/Berlin",
+        }),
+    );
+    let floating = task(
+        "t3",
+        "Stretch",
+        json!({
+            "string": "every day at 7pm",
+            "date": "2024-05-06",
+            "is_recurring": true,
+            "datetime": "2024-05-06T19:00:00",
+            "timezone": null,
+        }),
+    );
+    let undated = task("t4", "Someday", Value::Null);
+
+    let calendar = render_calendar(&[all_day, timed, floating, undated], &todo_options());
+    assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+    let lines = lines(&calendar);
+    let has = |line: &str| lines.iter().any(|candidate| candidate == line);
+
+    assert_eq!(lines.iter().filter(|line| *line == "BEGIN:VTODO").count(), 4);
+    assert!(has("UID:t1@todoist.com"));
+    assert!(has("DTSTAMP:20240101T083000Z"));
+    assert!(has("SUMMARY:Pay rent\\; utilities\\, etc."));
+    assert!(has("DESCRIPTION:Line one\\nLine two"));
+    // DUE is exclusive once there is a DTSTART
+    assert!(has("DTSTART;VALUE=DATE:20240501"));
+    assert!(has("DUE;VALUE=DATE:20240502"));
+    assert!(has("RRULE:FREQ=MONTHLY"));
+    assert!(has("CATEGORIES:home,bills"));
+    assert!(has("PRIORITY:1"));
+    assert!(has("STATUS:NEEDS-ACTION"));
+
+    // Times with a timezone are written in UTC, so no VTIMEZONE is needed
+    assert!(has("DUE:20240506T070000Z"));
+    assert!(!lines.iter().any(|line| line.contains("TZID")));
+    assert!(has("DTSTART:20240506T190000"));
+    assert!(!has("DUE:20240506T190000"));
+    assert!(has("RRULE:FREQ=DAILY"));
+    assert!(has("UID:t4@todoist.com"));
+}
+
+#[test]
+fn test_local_times_with_a_timezone_are_written_in_utc() {
+    let summer = task(
+        "t1",
+        "Standup",
+        json!({
+            "string": "may 6 at 9am",
+            "date": "2024-05-06",
+            "is_recurring": false,
+            "datetime": "2024-05-06T09:00:00",
+            "timezone": "Europe/Berlin",
+        }),
+    );
+    let unknown = task(
+        "t2",
+        "Call",
+        json!({
+            "string": "may 6 at 9am",
+            "date": "2024-05-06",
+            "is_recurring": false,
+            "datetime": "2024-05-06T09:00:00",
+            "timezone": "Mars/Olympus",
+        }),
+    );
+    let lines = lines(&render_calendar(&[summer, unknown], &todo_options()));
+    assert!(lines.contains(&"DUE:20240506T070000Z".to_string()));
+    // Unknown timezones leave the time floating
+    assert!(lines.contains(&"DUE:20240506T090000".to_string()));
+}
+
+#[test]
+fn test_due_is_later_than_start() {
+    let mut one_day = task("t1", "Move out", date_due("may 6", "2024-05-06", false));
+    one_day.duration = Some(Duration {
+        amount: 1,
+        unit: "day".to_string(),
+    });
+    let mut meeting = task(
+        "t2",
+        "Review",
+        json!({
+            "string": "every monday at 2pm",
+            "date": "2024-05-06",
+            "is_recurring": true,
+            "datetime": "2024-05-06T14:00:00",
+            "timezone": null,
+        }),
+    );
+    meeting.duration = Some(Duration {
+        amount: 30,
+        unit: "minute".to_string(),
+    });
+    let lines = lines(&render_calendar(&[one_day, meeting], &todo_options()));
+
+    let dates: Vec<&str> = lines
+        .iter()
+        .filter(|line| line.starts_with("DTSTART") || line.starts_with("DUE"))
+        .map(String::as_str)
+        .collect();
+    assert_eq!(
+        dates,
+        vec![
+            "DTSTART;VALUE=DATE:20240506",
+            "DUE;VALUE=DATE:20240507",
+            "DTSTART:20240506T140000",
+            "DUE:20240506T143000",
+        ]
+    );
+}
+
+#[test]
+fn test_repeating_zoned_times_stay_on_the_wall_clock() {
+    let weekly = task(
+        "t1",
+        "Team call",
+        json!({
+            "string": "every monday at 9am",
+            "date": "2024-03-25",
+            "is_recurring": true,
+            "datetime": "2024-03-25T08:00:00Z",
+            "timezone": "Europe/Berlin",
+        }),
+    );
+    let naive = task(
+        "t2",
+        "Standup",
+        json!({
+            "string": "every day at 9am",
+            "date": "2024-03-25",
+            "is_recurring": true,
+            "datetime": "2024-03-25T09:00:00",
+            "timezone": "Europe/Berlin",
+        }),
+    );
+    let lines = lines(&render_calendar(&[weekly, naive], &todo_options()));
+    let starts: Vec<&str> = lines
+        .iter()
+        .filter(|line| line.starts_with("DTSTART"))
+        .map(String::as_str)
+        .collect();
+    // Floating, so the occurrences after the switch to summer time are still at 9am
+    assert_eq!(starts, vec!["DTSTART:20240325T090000", "DTSTART:20240325T090000"]);
+    assert!(!lines.iter().any(|line| line.contains("TZID")));
+}
+
+#[test]
+fn test_render_events() {
+    let mut meeting = task(
+        "t1",
+        "Review",
+        json!({
+            "string": "may 6 at 2pm",
+            "date": "2024-05-06",
+            "is_recurring": false,
+            "datetime": "2024-05-06T14:00:00Z",
+            "timezone": null,
+        }),
+    );
+    meeting.duration = Some(Duration {
+        amount: 90,
+        unit: "minute".to_string(),
+    });
+    let mut trip = task("t2", "Trip", date_due("may 10", "2024-05-10", false));
+    trip.duration = Some(Duration {
+        amount: 3,
+        unit: "day".to_string(),
+    });
+    let mut report = task("t3", "Report", Value::Null);
+    report.deadline = Some(Deadline {
+        date: "2024-05-31".to_string(),
+    });
+    let unscheduled = task("t4", "Someday", Value::Null);
+    let mut odd = task("t5", "Odd", date_due("every 3rd friday", "2024-05-17", true));
+    odd.deadline = Some(Deadline {
+        date: "2024-06-30".to_string(),
+    });
+
+    let options = IcsOptions {
+        component: IcsComponent::Event,
+        name: Some("Work, Q2".to_string()),
+    };
+    let calendar = render_calendar(&[meeting, trip, report, unscheduled, odd], &options);
+    let lines = lines(&calendar);
+    let has = |line: &str| lines.iter().any(|candidate| candidate == line);
+
+    assert!(has("X-WR-CALNAME:Work\\, Q2"));
+    assert_eq!(lines.iter().filter(|line| *line == "BEGIN:VEVENT").count(), 4);
+    assert!(!lines.iter().any(|line| line.starts_with("STATUS:")));
+    assert!(has("DTSTART:20240506T140000Z"));
+    assert!(has("DTEND:20240506T153000Z"));
+    assert!(has("DTSTART;VALUE=DATE:20240510"));
+    assert!(has("DTEND;VALUE=DATE:20240513"));
+    assert!(has("DTSTART;VALUE=DATE:20240531"));
+    assert!(has("DTEND;VALUE=DATE:20240601"));
+    assert!(!has("UID:t4@todoist.com"));
+
+    // Unsupported recurrences keep the next occurrence and the original text
+    assert!(has("DTSTART;VALUE=DATE:20240517"));
+    assert!(has("DESCRIPTION:Repeats: every 3rd friday\\n\\nDeadline: 2024-06-30"));
+    assert!(!lines.iter().any(|line| line.starts_with("RRULE:")));
+}
+
+#[test]
+fn test_long_lines_are_folded() {
+    let content = "Ünïcödé ".repeat(20);
+    let calendar = render_calendar(&[task("t1", &content, Value::Null)], &todo_options());
+    for line in calendar.split("\r\n") {
+        assert!(line.len() <= 75, "{line:?} is longer than 75 octets");
+    }
+    let expected = format!("SUMMARY:{content}");
+    assert!(lines(&calendar).contains(&expected));
+}
+
+#[test]
+fn test_recurrence_rule() {
+    assert_eq!(recurrence_rule("every day").as_deref(), Some("FREQ=DAILY"));
+    assert_eq!(recurrence_rule("Every! week").as_deref(), Some("FREQ=WEEKLY"));
+    assert_eq!(recurrence_rule("daily at 9am").as_deref(), Some("FREQ=DAILY"));
+    assert_eq!(
+        recurrence_rule("every 3 months").as_deref(),
+        Some("FREQ=MONTHLY;INTERVAL=3")
+    );
+    assert_eq!(
+        recurrence_rule("every other year").as_deref(),
+        Some("FREQ=YEARLY;INTERVAL=2")
+    );
+    assert_eq!(
+        recurrence_rule("every mon, wednesday and fri at 17:30").as_deref(),
+        Some("FREQ=WEEKLY;BYDAY=MO,WE,FR")
+    );
+    assert_eq!(
+        recurrence_rule("every weekday").as_deref(),
+        Some("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR")
+    );
+    assert_eq!(
+        recurrence_rule("every weekend").as_deref(),
+        Some("FREQ=WEEKLY;BYDAY=SA,SU")
+    );
+    assert_eq!(recurrence_rule("every 3rd friday"), None);
+    assert_eq!(recurrence_rule("every day until june"), None);
+    assert_eq!(recurrence_rule("every 0 days"), None);
+    assert_eq!(recurrence_rule("tomorrow"), None);
+}
+
+#[tokio::test]
+async fn test_export_project_ics() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/projects/p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(project_json("p1", "Home")))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/tasks"))
+        .and(query_param("project_id", "p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([due_task_json(
+            "t1",
+            "Water plants",
+            date_due("every 2 days", "2024-05-06", true)
+        )])))
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    let calendar = todoist.export_project_ics("p1", &IcsOptions::default()).await.unwrap();
+    let lines = lines(&calendar);
+    assert!(lines.contains(&"X-WR-CALNAME:Home".to_string()));
+    assert!(lines.contains(&"RRULE:FREQ=DAILY;INTERVAL=2".to_string()));
+}
@@ -0,0 +1,151 @@
+use serde_json::json;
+use std::time::Duration;
+use todoist_api::oauth::*;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn client(server: &MockServer) -> OAuthClient {
+    OAuthClient::new("client-id", "client-secret").with_endpoints(OAuthEndpoints {
+        authorize_url: format!("{}/oauth/authorize", server.uri()),
+        token_url: format!("{}/oauth/access_token", server.uri()),
+        revoke_url: format!("{}/access_tokens/revoke", server.uri()),
+    })
+}
+
+#[test]
+fn test_authorize_url() {
+    let oauth = OAuthClient::new("client-id", "client-secret").with_redirect_uri("http://localhost:8765/");
+    let url = oauth
+        .authorize_url_with_state(&[Scope::DataRead, Scope::TaskAdd], "state 1")
+        .unwrap();
+    assert_eq!(
+        url,
+        "https://todoist.com/oauth/authorize?client_id=client-id&scope=data%3Aread%2Ctask%3Aadd\
+         &state=state+1&redirect_uri=http%3A%2F%2Flocalhost%3A8765%2F"
+    );
+
+    let first = oauth.authorize_url(&[Scope::DataReadWrite]).unwrap();
+    let second = oauth.authorize_url(&[Scope::DataReadWrite]).unwrap();
+    assert_ne!(first.state, second.state);
+    assert!(first.url.contains(&format!("state={}", first.state)));
+}
+
+#[tokio::test]
+async fn test_exchange_code() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/access_token"))
+        .and(body_string_contains("client_id=client-id"))
+        .and(body_string_contains("client_secret=client-secret"))
+        .and(body_string_contains("code=the-code"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "0123456789abcdef",
+            "token_type": "Bearer",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let token = client(&server).exchange_code("the-code").await.unwrap();
+    assert_eq!(token.access_token, "0123456789abcdef");
+    assert_eq!(token.token_type, "Bearer");
+    assert!(!format!("{token:?}").contains("0123456789abcdef"));
+}
+
+#[tokio::test]
+async fn test_exchange_code_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/access_token"))
+        .and(body_string_contains("code=expired"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "error": "bad_authorization_code" })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/access_token"))
+        .and(body_string_contains("code=broken"))
+        .respond_with(ResponseTemplate::new(400).set_body_string("Bad Request"))
+        .mount(&server)
+        .await;
+
+    let oauth = client(&server);
+    let error = oauth.exchange_code("expired").await.unwrap_err();
+    assert_eq!(error.to_string(), "token exchange failed: bad_authorization_code");
+    let error = oauth.exchange_code("broken").await.unwrap_err();
+    assert!(error.to_string().contains("400"));
+}
+
+#[tokio::test]
+async fn test_revoke() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/access_tokens/revoke"))
+        .and(body_string_contains("access_token=0123456789abcdef"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client(&server).revoke("0123456789abcdef").await.unwrap();
+    assert!(client(&server).revoke("unknown").await.is_err());
+}
+
+#[tokio::test]
+async fn test_loopback_listener_receives_code() {
+    let listener = LoopbackListener::bind(0).await.unwrap();
+    let redirect_uri = listener.redirect_uri();
+    let waiting = tokio::spawn(listener.wait_for_code("expected-state", TIMEOUT));
+
+    let http = reqwest::Client::new();
+    let favicon = http.get(format!("{redirect_uri}favicon.ico")).send().await.unwrap();
+    assert_eq!(favicon.status(), 404);
+
+    let response = http
+        .get(format!("{redirect_uri}?code=the%20code&state=expected-state"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(response.text().await.unwrap().contains("Authorization complete"));
+    assert_eq!(waiting.await.unwrap().unwrap(), "the code");
+}
+
+#[tokio::test]
+async fn test_loopback_listener_rejects_forged_state() {
+    let listener = LoopbackListener::bind(0).await.unwrap();
+    let redirect_uri = listener.redirect_uri();
+    let waiting = tokio::spawn(listener.wait_for_code("expected-state", TIMEOUT));
+
+    reqwest::get(format!("{redirect_uri}?code=the-code&state=forged"))
+        .await
+        .unwrap();
+    let error = waiting.await.unwrap().unwrap_err();
+    assert!(error.to_string().starts_with("state mismatch"));
+
+    let listener = LoopbackListener::bind(0).await.unwrap();
+    let redirect_uri = listener.redirect_uri();
+    let waiting = tokio::spawn(listener.wait_for_code("expected-state", TIMEOUT));
+    reqwest::get(format!("{redirect_uri}?error=access_denied&state=expected-state"))
+        .await
+        .unwrap();
+    let error = waiting.await.unwrap().unwrap_err();
+    assert_eq!(error.to_string(), "authorization denied: access_denied");
+}
+
+#[tokio::test]
+async fn test_loopback_listener_redirect_uri_matches_bound_address() {
+    let listener = LoopbackListener::bind(0).await.unwrap();
+    assert!(listener.redirect_uri().starts_with("http://127.0.0.1:"));
+}
+
+#[tokio::test]
+async fn test_loopback_listener_times_out() {
+    let listener = LoopbackListener::bind(0).await.unwrap();
+    let error = listener
+        .wait_for_code("expected-state", Duration::from_millis(50))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().starts_with("no OAuth redirect received"));
+}
@@ -0,0 +1,484 @@
+mod common;
+
+use serde_json::{json, Value};
+use todoist_api::offline::*;
+use todoist_api::*;
+use wiremock::matchers::{body_partial_json, body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use common::{project_json, task_json, wrapper};
+
+fn item(id: &str, content: &str) -> Value {
+    json!({
+        "id": id,
+        "project_id": "p1",
+        "content": content,
+        "priority": 1,
+        "child_order": 1,
+        "labels": [],
+        "checked": false,
+        "is_deleted": false,
+        "added_at": "2024-01-01T00:00:00Z",
+    })
+}
+
+/// Mount a Sync API answering the initial full sync and every later incremental sync
+async fn mount_sync(server: &MockServer, incremental_items: Vec<Value>) {
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("sync_token=*"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "sync_token": "token-1",
+            "full_sync": true,
+            "projects": [{ "id": "p1", "name": "Work" }],
+            "items": [item("1", "Write report"), item("2", "Call client")],
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("sync_token=token-"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "sync_token": "token-2",
+            "full_sync": false,
+            "items": incremental_items,
+        })))
+        .mount(server)
+        .await;
+}
+
+async fn refreshed_store(server: &MockServer) -> OfflineStore {
+    let mut offline = OfflineStore::open_in_memory(wrapper(server)).unwrap();
+    offline.refresh().await.unwrap();
+    offline
+}
+
+fn rename_task() -> Mutation {
+    Mutation::UpdateTask {
+        task_id: "1".to_string(),
+        args: UpdateTaskArgs {
+            content: Some("Write the report".to_string()),
+            ..Default::default()
+        },
+    }
+}
+
+#[tokio::test]
+async fn test_replica_is_persisted_to_disk() {
+    let server = MockServer::start().await;
+    mount_sync(&server, vec![]).await;
+    let db_path = std::env::temp_dir().join(format!("todoist-offline-{}.db", uuid::Uuid::new_v4()));
+
+    {
+        let mut offline = OfflineStore::open(&db_path, wrapper(&server)).unwrap();
+        offline.refresh().await.unwrap();
+        offline
+            .queue(Mutation::CompleteTask {
+                task_id: "2".to_string(),
+            })
+            .unwrap();
+    }
+
+    // Reopening works without any network access
+    let offline = OfflineStore::open(&db_path, TodoistWrapper::new("test-token".to_string())).unwrap();
+    assert_eq!(offline.store().sync_token(), "token-1");
+    assert_eq!(offline.remote().tasks().len(), 2);
+    assert_eq!(offline.store().project("p1").unwrap().name, "Work");
+    // The queued completion shows in reads
+    assert_eq!(offline.store().tasks_by_project("p1").len(), 1);
+
+    let outbox = offline.outbox().unwrap();
+    assert_eq!(outbox.len(), 1);
+    assert!(matches!(&outbox[0].mutation, Mutation::CompleteTask { task_id } if task_id == "2"));
+    assert!(matches!(&outbox[0].base, Some(Resource::Task(task)) if task.content == "Call client"));
+
+    std::fs::remove_file(db_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_outbox_is_replayed() {
+    let server = MockServer::start().await;
+    mount_sync(&server, vec![]).await;
+    Mock::given(method("POST"))
+        .and(path("/tasks/2/close"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "3",
+            "content": "New task",
+            "description": "",
+            "project_id": "p1",
+            "section_id": null,
+            "parent_id": null,
+            "order": 1,
+            "priority": 1,
+            "is_completed": false,
+            "labels": [],
+            "created_at": "2024-01-01T00:00:00Z",
+            "due": null,
+            "deadline": null,
+            "duration": null,
+            "assignee_id": null,
+            "url": "https://todoist.com",
+            "comment_count": 0,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut offline = refreshed_store(&server).await;
+    let first = offline
+        .queue(Mutation::CompleteTask {
+            task_id: "2".to_string(),
+        })
+        .unwrap();
+    let second = offline
+        .queue(Mutation::CreateTask {
+            args: CreateTaskArgs {
+                content: "New task".to_string(),
+                ..Default::default()
+            },
+        })
+        .unwrap();
+
+    let report = offline.sync_outbox().await.unwrap();
+    assert_eq!(report.applied, vec![first, second]);
+    assert!(report.conflicts.is_empty());
+    assert!(report.failed.is_none());
+    assert_eq!(report.remaining, 0);
+    assert_eq!(offline.store().sync_token(), "token-2");
+}
+
+#[tokio::test]
+async fn test_replay_uses_real_ids_of_objects_created_offline() {
+    let server = MockServer::start().await;
+    mount_sync(&server, vec![]).await;
+    Mock::given(method("POST"))
+        .and(path("/projects"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(project_json("p9", "Garden")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/tasks"))
+        .and(body_partial_json(json!({ "project_id": "p9" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(task_json("t9", "Plant tomatoes")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/tasks/t9/close"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut offline = refreshed_store(&server).await;
+    let project = offline
+        .queue(Mutation::CreateProject {
+            args: CreateProjectArgs {
+                name: "Garden".to_string(),
+                ..Default::default()
+            },
+        })
+        .unwrap();
+    let task = offline
+        .queue(Mutation::CreateTask {
+            args: CreateTaskArgs {
+                content: "Plant tomatoes".to_string(),
+                project_id: Some(format!("local-{project}")),
+                ..Default::default()
+            },
+        })
+        .unwrap();
+    let completion = offline
+        .queue(Mutation::CompleteTask {
+            task_id: format!("local-{task}"),
+        })
+        .unwrap();
+
+    let report = offline.sync_outbox().await.unwrap();
+    assert_eq!(report.applied, vec![project, task, completion]);
+    assert!(report.conflicts.is_empty());
+    assert!(report.failed.is_none());
+    assert_eq!(report.remaining, 0);
+}
+
+#[tokio::test]
+async fn test_interrupted_replay_keeps_real_ids() {
+    let server = MockServer::start().await;
+    mount_sync(&server, vec![]).await;
+    Mock::given(method("POST"))
+        .and(path("/projects"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(project_json("p9", "Garden")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let db_path = std::env::temp_dir().join(format!("todoist-offline-{}.db", uuid::Uuid::new_v4()));
+
+    let mut offline = OfflineStore::open(&db_path, wrapper(&server)).unwrap();
+    offline.refresh().await.unwrap();
+    let project = offline
+        .queue(Mutation::CreateProject {
+            args: CreateProjectArgs {
+                name: "Garden".to_string(),
+                ..Default::default()
+            },
+        })
+        .unwrap();
+    offline
+        .queue(Mutation::CreateTask {
+            args: CreateTaskArgs {
+                content: "Plant tomatoes".to_string(),
+                project_id: Some(format!("local-{project}")),
+                ..Default::default()
+            },
+        })
+        .unwrap();
+
+    // Creating the task fails, as no mock answers it yet
+    let report = offline.sync_outbox().await.unwrap();
+    assert_eq!(report.applied, vec![project]);
+    assert_eq!(report.remaining, 1);
+    drop(offline);
+
+    // The queued task shows in the created project after reopening
+    let mut offline = OfflineStore::open(&db_path, wrapper(&server)).unwrap();
+    assert_eq!(offline.store().tasks_by_project("p9").len(), 1);
+
+    Mock::given(method("POST"))
+        .and(path("/tasks"))
+        .and(body_partial_json(json!({ "project_id": "p9" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(task_json("t9", "Plant tomatoes")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let report = offline.sync_outbox().await.unwrap();
+    assert_eq!(report.remaining, 0);
+
+    std::fs::remove_file(db_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_conflict_remote_wins() {
+    let server = MockServer::start().await;
+    mount_sync(&server, vec![item("1", "Write report v2")]).await;
+    Mock::given(method("POST"))
+        .and(path("/tasks/1"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let mut offline = refreshed_store(&server).await.with_resolver(RemoteWins);
+    offline.queue(rename_task()).unwrap();
+
+    let report = offline.sync_outbox().await.unwrap();
+    assert_eq!(report.conflicts.len(), 1);
+    let (conflict, resolution) = &report.conflicts[0];
+    assert_eq!(*resolution, Resolution::KeepRemote);
+    assert!(matches!(&conflict.entry.base, Some(Resource::Task(t)) if t.content == "Write report"));
+    assert!(matches!(&conflict.remote, Some(Resource::Task(t)) if t.content == "Write report v2"));
+    assert!(report.applied.is_empty());
+    assert_eq!(report.remaining, 0);
+    assert_eq!(offline.store().task("1").unwrap().content, "Write report v2");
+}
+
+#[tokio::test]
+async fn test_conflicts_are_deferred_by_default() {
+    let server = MockServer::start().await;
+    // Task 1 was completed remotely
+    let mut completed = item("1", "Write report");
+    completed["checked"] = json!(true);
+    mount_sync(&server, vec![completed]).await;
+
+    let mut offline = refreshed_store(&server).await;
+    let entry = offline.queue(rename_task()).unwrap();
+
+    let report = offline.sync_outbox().await.unwrap();
+    assert_eq!(report.conflicts.len(), 1);
+    assert_eq!(report.conflicts[0].1, Resolution::Defer);
+    assert!(report.conflicts[0].0.remote.is_none());
+    assert_eq!(report.remaining, 1);
+
+    offline.discard(entry).unwrap();
+    assert!(offline.outbox().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_custom_resolver_applies_local_change() {
+    let server = MockServer::start().await;
+    mount_sync(&server, vec![item("1", "Write report v2")]).await;
+    Mock::given(method("POST"))
+        .and(path("/tasks/1"))
+        .and(body_string_contains("Write the report"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "1",
+            "content": "Write the report",
+            "description": "",
+            "project_id": "p1",
+            "section_id": null,
+            "parent_id": null,
+            "order": 1,
+            "priority": 1,
+            "is_completed": false,
+            "labels": [],
+            "created_at": "2024-01-01T00:00:00Z",
+            "due": null,
+            "deadline": null,
+            "duration": null,
+            "assignee_id": null,
+            "url": "https://todoist.com",
+            "comment_count": 0,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut offline = refreshed_store(&server)
+        .await
+        .with_resolver(|conflict: &Conflict| match conflict.remote {
+            Some(_) => Resolution::ApplyLocal,
+            None => Resolution::Defer,
+        });
+    let entry = offline.queue(rename_task()).unwrap();
+
+    let report = offline.sync_outbox().await.unwrap();
+    assert_eq!(report.conflicts[0].1, Resolution::ApplyLocal);
+    assert_eq!(report.applied, vec![entry]);
+    assert_eq!(report.remaining, 0);
+}
+
+#[tokio::test]
+async fn test_missing_target_is_dropped_instead_of_replayed() {
+    let server = MockServer::start().await;
+    // Task 1 was deleted remotely
+    let mut deleted = item("1", "Write report");
+    deleted["is_deleted"] = json!(true);
+    mount_sync(&server, vec![deleted]).await;
+    Mock::given(method("POST"))
+        .and(path("/tasks/1"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(0)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/tasks/2/close"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut offline = refreshed_store(&server).await.with_resolver(LocalWins);
+    offline.queue(rename_task()).unwrap();
+    let completion = offline
+        .queue(Mutation::CompleteTask {
+            task_id: "2".to_string(),
+        })
+        .unwrap();
+
+    let report = offline.sync_outbox().await.unwrap();
+    assert_eq!(report.conflicts.len(), 1);
+    assert!(report.conflicts[0].0.remote.is_none());
+    assert_eq!(report.conflicts[0].1, Resolution::KeepRemote);
+    assert!(report.failed.is_none());
+    assert_eq!(report.applied, vec![completion]);
+    assert_eq!(report.remaining, 0);
+}
+
+#[tokio::test]
+async fn test_failed_replay_keeps_outbox() {
+    let server = MockServer::start().await;
+    mount_sync(&server, vec![]).await;
+    Mock::given(method("POST"))
+        .and(path("/tasks/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("not json"))
+        .mount(&server)
+        .await;
+
+    let mut offline = refreshed_store(&server).await;
+    let entry = offline.queue(rename_task()).unwrap();
+    offline
+        .queue(Mutation::DeleteTask {
+            task_id: "2".to_string(),
+        })
+        .unwrap();
+
+    let report = offline.sync_outbox().await.unwrap();
+    assert!(report.applied.is_empty());
+    assert_eq!(report.failed.as_ref().map(|(id, _)| *id), Some(entry));
+    assert_eq!(report.remaining, 2);
+}
+
+#[tokio::test]
+async fn test_server_error_keeps_outbox_entry() {
+    let server = MockServer::start().await;
+    mount_sync(&server, vec![]).await;
+    Mock::given(method("DELETE"))
+        .and(path("/tasks/2"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut offline = refreshed_store(&server).await;
+    let entry = offline
+        .queue(Mutation::DeleteTask {
+            task_id: "2".to_string(),
+        })
+        .unwrap();
+
+    let report = offline.sync_outbox().await.unwrap();
+    assert!(report.applied.is_empty());
+    assert_eq!(report.failed.as_ref().map(|(id, _)| *id), Some(entry));
+    assert_eq!(report.remaining, 1);
+    assert_eq!(offline.outbox().unwrap()[0].id, entry);
+}
+
+#[tokio::test]
+async fn test_queued_mutations_show_in_reads() {
+    let server = MockServer::start().await;
+    mount_sync(&server, vec![]).await;
+
+    let mut offline = refreshed_store(&server).await;
+    let mut events = offline.store().subscribe();
+    offline.queue(rename_task()).unwrap();
+    let created = offline
+        .queue(Mutation::CreateTask {
+            args: CreateTaskArgs {
+                content: "New task".to_string(),
+                project_id: Some("p1".to_string()),
+                labels: Some(vec!["errand".to_string()]),
+                ..Default::default()
+            },
+        })
+        .unwrap();
+    offline
+        .queue(Mutation::CompleteTask {
+            task_id: "2".to_string(),
+        })
+        .unwrap();
+
+    let store = offline.store();
+    assert_eq!(store.task("1").unwrap().content, "Write the report");
+    assert!(store.task("2").is_none());
+    let new_task = store.task(&format!("local-{created}")).unwrap();
+    assert_eq!(new_task.content, "New task");
+    assert_eq!(store.tasks_by_label("errand").len(), 1);
+    assert!(matches!(
+        events.try_recv().unwrap(),
+        StoreEvent::Task(Change::Updated { .. })
+    ));
+
+    // The synced replica is untouched, so replay detects no conflict
+    assert_eq!(offline.remote().task("1").unwrap().content, "Write report");
+    assert_eq!(offline.remote().tasks().len(), 2);
+
+    // Dropping a queued mutation drops it from reads too
+    offline.discard(created).unwrap();
+    assert!(offline.store().task(&format!("local-{created}")).is_none());
+}
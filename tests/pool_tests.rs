@@ -0,0 +1,164 @@
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use todoist_api::*;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn task(id: &str, content: &str) -> serde_json::Value {
+    json!({
+        "id": id,
+        "content": content,
+        "description": "",
+        "project_id": "p1",
+        "section_id": null,
+        "parent_id": null,
+        "order": 1,
+        "priority": 1,
+        "is_completed": false,
+        "labels": [],
+        "created_at": "2024-01-01T00:00:00Z",
+        "due": null,
+        "deadline": null,
+        "duration": null,
+        "assignee_id": null,
+        "url": "https://todoist.com",
+        "comment_count": 0,
+    })
+}
+
+async fn mount_tasks(server: &MockServer, token: &str, response: ResponseTemplate) {
+    Mock::given(method("GET"))
+        .and(path("/tasks"))
+        .and(header("Authorization", format!("Bearer {token}").as_str()))
+        .respond_with(response)
+        .mount(server)
+        .await;
+}
+
+fn pool(server: &MockServer) -> TodoistPool {
+    TodoistPool::with_base_urls(&server.uri(), &server.uri())
+}
+
+#[tokio::test]
+async fn test_fan_out_isolates_errors() {
+    let server = MockServer::start().await;
+    mount_tasks(
+        &server,
+        "alice-token",
+        ResponseTemplate::new(200).set_body_json(json!([task("1", "Alice task")])),
+    )
+    .await;
+    mount_tasks(
+        &server,
+        "bob-token",
+        ResponseTemplate::new(200).set_body_json(json!([task("2", "Bob task"), task("3", "Another")])),
+    )
+    .await;
+    mount_tasks(&server, "carol-token", ResponseTemplate::new(500)).await;
+
+    let mut pool = pool(&server);
+    pool.add_token("alice", "alice-token".to_string());
+    pool.add_token("bob", "bob-token".to_string());
+    pool.add_token("carol", "carol-token".to_string());
+    assert_eq!(pool.accounts().collect::<Vec<_>>(), vec!["alice", "bob", "carol"]);
+
+    let results = pool.get_tasks_all().await;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results["alice"].as_ref().unwrap()[0].content, "Alice task");
+    assert_eq!(results["bob"].as_ref().unwrap().len(), 2);
+    assert!(results["carol"].is_err());
+}
+
+#[tokio::test]
+async fn test_fan_out_parallelism_is_bounded() {
+    let mut pool = TodoistPool::new().with_max_concurrency(2);
+    for i in 0..6 {
+        pool.add_token(format!("account-{i}"), format!("token-{i}"));
+    }
+
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let results = {
+        let (in_flight, max_in_flight) = (in_flight.clone(), max_in_flight.clone());
+        pool.for_each(move |_| {
+            let (in_flight, max_in_flight) = (in_flight.clone(), max_in_flight.clone());
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(current)
+            }
+        })
+        .await
+    };
+
+    assert_eq!(results.len(), 6);
+    assert!(results.values().all(|r| r.is_ok()));
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_panics_are_isolated() {
+    let mut pool = TodoistPool::new();
+    pool.add_token("ok", "ok-token".to_string());
+    pool.add_token("broken", "broken-token".to_string());
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let results = {
+        let calls = calls.clone();
+        pool.for_each(move |_| {
+            let first = calls.fetch_add(1, Ordering::SeqCst) == 0;
+            async move {
+                if first {
+                    panic!("boom");
+                }
+                Ok(())
+            }
+        })
+        .await
+    };
+    assert_eq!(results.len(), 2);
+    assert_eq!(results.values().filter(|r| r.is_err()).count(), 1);
+    let error = results.values().find_map(|r| r.as_ref().err()).unwrap();
+    assert!(error.to_string().contains("panicked"));
+}
+
+#[tokio::test]
+async fn test_rate_limit_is_per_account() {
+    let server = MockServer::start().await;
+    for token in ["alice-token", "bob-token"] {
+        mount_tasks(&server, token, ResponseTemplate::new(200).set_body_json(json!([]))).await;
+    }
+
+    let mut pool = pool(&server).with_rate_limit(RateLimit::new(2, Duration::from_millis(300)));
+    let alice = pool.add_token("alice", "alice-token".to_string()).clone();
+    let bob = pool.add_token("bob", "bob-token".to_string()).clone();
+
+    let started = Instant::now();
+    alice.get_tasks().await.unwrap();
+    alice.get_tasks().await.unwrap();
+    // Each account has its own budget
+    bob.get_tasks().await.unwrap();
+    bob.get_tasks().await.unwrap();
+    assert!(started.elapsed() < Duration::from_millis(300));
+
+    // A third request for the same account waits for the window to move
+    alice.get_tasks().await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(300));
+}
+
+#[tokio::test]
+async fn test_accounts_can_be_replaced_and_removed() {
+    let mut pool = TodoistPool::new();
+    assert!(pool.is_empty());
+    pool.add_token("alice", "old-token".to_string());
+    pool.add_token("alice", "new-token".to_string());
+    assert_eq!(pool.len(), 1);
+    assert!(pool.account("alice").is_some());
+    assert!(pool.remove_account("alice").is_some());
+    assert!(pool.account("alice").is_none());
+    assert!(pool.get_projects_all().await.is_empty());
+}
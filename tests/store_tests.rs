@@ -0,0 +1,172 @@
+mod common;
+
+use serde_json::{json, Value};
+use todoist_api::*;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use common::wrapper;
+
+fn item(id: &str, content: &str, labels: &[&str], due: Option<&str>) -> Value {
+    json!({
+        "id": id,
+        "project_id": "p1",
+        "section_id": "s1",
+        "content": content,
+        "description": "",
+        "priority": 1,
+        "child_order": id.parse::<i32>().unwrap(),
+        "labels": labels,
+        "due": due.map(|date| json!({ "date": date, "string": date, "is_recurring": false })),
+        "checked": false,
+        "is_deleted": false,
+        "added_at": "2024-01-01T00:00:00Z",
+    })
+}
+
+fn full_sync() -> Value {
+    json!({
+        "sync_token": "token-1",
+        "full_sync": true,
+        "projects": [{ "id": "p1", "name": "Work", "color": "blue", "child_order": 1, "view_style": "list" }],
+        "sections": [{ "id": "s1", "name": "Backlog", "project_id": "p1", "section_order": 1 }],
+        "labels": [{ "id": "l1", "name": "urgent", "color": "red", "item_order": 1 }],
+        "items": [
+            item("1", "Write report", &["urgent"], Some("2024-05-01")),
+            item("2", "Call client", &[], Some("2024-05-03T10:00:00")),
+            item("3", "Archive mail", &["urgent"], None),
+        ],
+        "notes": [{ "id": "n1", "item_id": "1", "content": "Draft attached", "posted_at": "2024-01-02T00:00:00Z" }],
+    })
+}
+
+fn incremental_sync() -> Value {
+    let mut updated = item("1", "Write final report", &[], Some("2024-05-02"));
+    updated["priority"] = json!(4);
+    let mut completed = item("2", "Call client", &[], Some("2024-05-03T10:00:00"));
+    completed["checked"] = json!(true);
+    let mut deleted = item("3", "Archive mail", &["urgent"], None);
+    deleted["is_deleted"] = json!(true);
+    json!({
+        "sync_token": "token-2",
+        "full_sync": false,
+        "items": [updated, completed, deleted, item("4", "Send invoice", &["urgent"], None)],
+    })
+}
+
+async fn synced_store(server: &MockServer) -> (TodoistStore, Vec<StoreEvent>) {
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("sync_token=*"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(full_sync()))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("sync_token=token-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(incremental_sync()))
+        .mount(server)
+        .await;
+
+    let mut store = TodoistStore::new(wrapper(server));
+    let events = store.refresh().await.unwrap();
+    (store, events)
+}
+
+#[tokio::test]
+async fn test_full_sync_populates_store() {
+    let server = MockServer::start().await;
+    let (store, events) = synced_store(&server).await;
+
+    assert_eq!(store.sync_token(), "token-1");
+    assert_eq!(events.len(), 7);
+    assert!(events.iter().all(|e| matches!(
+        e,
+        StoreEvent::Project(Change::Created(_))
+            | StoreEvent::Section(Change::Created(_))
+            | StoreEvent::Label(Change::Created(_))
+            | StoreEvent::Task(Change::Created(_))
+            | StoreEvent::Comment(Change::Created(_))
+    )));
+
+    assert_eq!(store.tasks().len(), 3);
+    assert_eq!(store.project("p1").unwrap().name, "Work");
+    assert_eq!(store.labels()[0].name, "urgent");
+    assert_eq!(store.comments_for_task("1")[0].content, "Draft attached");
+
+    let task = store.task("2").unwrap();
+    let due = task.due.as_ref().unwrap();
+    assert_eq!(due.date, "2024-05-03");
+    assert_eq!(due.datetime.as_deref(), Some("2024-05-03T10:00:00"));
+}
+
+#[tokio::test]
+async fn test_indexed_lookups() {
+    let server = MockServer::start().await;
+    let (store, _) = synced_store(&server).await;
+
+    let ids = |tasks: Vec<&Task>| tasks.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+    assert_eq!(ids(store.tasks_by_project("p1")), vec!["1", "2", "3"]);
+    assert_eq!(ids(store.tasks_by_section("s1")), vec!["1", "2", "3"]);
+    assert_eq!(ids(store.tasks_by_label("urgent")), vec!["1", "3"]);
+    assert_eq!(ids(store.tasks_due_on("2024-05-03")), vec!["2"]);
+    assert_eq!(ids(store.tasks_due_between("2024-05-01", "2024-05-02")), vec!["1"]);
+    assert!(store.tasks_by_project("unknown").is_empty());
+}
+
+#[tokio::test]
+async fn test_incremental_sync_emits_changes() {
+    let server = MockServer::start().await;
+    let (mut store, _) = synced_store(&server).await;
+    let mut receiver = store.subscribe();
+
+    let events = store.refresh().await.unwrap();
+    assert_eq!(store.sync_token(), "token-2");
+    assert_eq!(events.len(), 4);
+
+    match &events[0] {
+        StoreEvent::Task(Change::Updated { before, after }) => {
+            assert_eq!(before.content, "Write report");
+            assert_eq!(after.content, "Write final report");
+            assert_eq!(after.priority, 4);
+        }
+        other => panic!("unexpected event {other:?}"),
+    }
+    assert!(matches!(&events[1], StoreEvent::Task(Change::Completed(t)) if t.id == "2"));
+    assert!(matches!(&events[2], StoreEvent::Task(Change::Deleted(t)) if t.id == "3"));
+    assert!(matches!(&events[3], StoreEvent::Task(Change::Created(t)) if t.id == "4"));
+    assert_eq!(receiver.recv().await.unwrap(), events[0]);
+
+    // Indexes follow the changes
+    let ids = |tasks: Vec<&Task>| tasks.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+    assert_eq!(ids(store.tasks_by_label("urgent")), vec!["4"]);
+    assert_eq!(ids(store.tasks_due_on("2024-05-02")), vec!["1"]);
+    assert!(store.tasks_due_on("2024-05-01").is_empty());
+    assert!(store.tasks_due_on("2024-05-03").is_empty());
+    // Untouched resources are kept
+    assert_eq!(store.projects().len(), 1);
+}
+
+#[test]
+fn test_full_sync_removes_missing_resources() {
+    let mut store = TodoistStore::new(TodoistWrapper::new("test-token".to_string()));
+    store.apply(serde_json::from_value(full_sync()).unwrap());
+
+    let events = store.apply(
+        serde_json::from_value(json!({
+            "sync_token": "token-3",
+            "full_sync": true,
+            "projects": [{ "id": "p1", "name": "Work", "color": "blue", "child_order": 1, "view_style": "list" }],
+            "sections": [{ "id": "s1", "name": "Backlog", "project_id": "p1", "section_order": 1 }],
+            "labels": [{ "id": "l1", "name": "urgent", "color": "red", "item_order": 1 }],
+            "items": [item("1", "Write report", &["urgent"], Some("2024-05-01"))],
+            "notes": [{ "id": "n1", "item_id": "1", "content": "Draft attached", "posted_at": "2024-01-02T00:00:00Z" }],
+        }))
+        .unwrap(),
+    );
+
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|e| matches!(e, StoreEvent::Task(Change::Deleted(_)))));
+    assert_eq!(store.tasks().len(), 1);
+    assert!(store.tasks_by_label("urgent").iter().all(|t| t.id == "1"));
+}
@@ -0,0 +1,397 @@
+mod common;
+
+use serde_json::{json, Value};
+use todoist_api::templates::{ProjectTemplate, TaskTemplate};
+use todoist_api::*;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use common::{project_json, task_json, wrapper};
+
+const TEMPLATE: &str = r#"
+[[labels]]
+name = "client"
+color = "blue"
+
+[[projects]]
+name = "Acme"
+
+[[projects.tasks]]
+content = "Weekly report"
+due = "every friday"
+
+[[projects.sections]]
+name = "Onboarding"
+
+[[projects.sections.tasks]]
+content = "Kickoff call"
+labels = ["client"]
+priority = 4
+due_in_days = 3
+
+[[projects.sections.tasks.subtasks]]
+content = "Send agenda"
+
+[[projects.projects]]
+name = "Phase 1"
+"#;
+
+fn project(id: &str, name: &str, parent_id: Option<&str>) -> Value {
+    let mut project = project_json(id, name);
+    project["parent_id"] = json!(parent_id);
+    project
+}
+
+fn task(id: &str, content: &str, section_id: Option<&str>, parent_id: Option<&str>) -> Value {
+    let mut task = task_json(id, content);
+    task["section_id"] = json!(section_id);
+    task["parent_id"] = json!(parent_id);
+    task
+}
+
+/// Mock an account holding some of the template, and creation endpoints echoing their input
+async fn mount_account(server: &MockServer, projects: Value, sections: Value, tasks: Value, labels: Value) {
+    for (resource, body) in [
+        ("/projects", projects),
+        ("/sections", sections),
+        ("/tasks", tasks),
+        ("/labels", labels),
+    ] {
+        Mock::given(method("GET"))
+            .and(path(resource))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/completed/get_all"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "items": [], "projects": {} })))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/projects"))
+        .respond_with(|request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            let name = body["name"].as_str().unwrap();
+            ResponseTemplate::new(200).set_body_json(project(&format!("new-{name}"), name, body["parent_id"].as_str()))
+        })
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/sections"))
+        .respond_with(|request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            let name = body["name"].as_str().unwrap();
+            ResponseTemplate::new(200).set_body_json(json!({
+                "id": format!("new-{name}"),
+                "name": name,
+                "project_id": body["project_id"],
+                "order": 1,
+                "url": "https://todoist.com",
+            }))
+        })
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/tasks"))
+        .respond_with(|request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            let content = body["content"].as_str().unwrap();
+            ResponseTemplate::new(200).set_body_json(task(&format!("new-{content}"), content, None, None))
+        })
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/labels"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "new-client",
+            "name": "client",
+            "color": "blue",
+            "order": 1,
+            "is_favorite": false,
+        })))
+        .mount(server)
+        .await;
+}
+
+fn posts(requests: &[Request]) -> Vec<(String, Value)> {
+    requests
+        .iter()
+        .filter(|request| request.method.to_string() == "POST")
+        .map(|request| {
+            (
+                request.url.path().to_string(),
+                serde_json::from_slice(&request.body).unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_toml_template() {
+    let template = Template::from_toml(TEMPLATE).unwrap();
+    assert_eq!(
+        template.projects[0].sections[0].tasks[0].subtasks[0].content,
+        "Send agenda"
+    );
+
+    let error = Template::from_toml(
+        r#"
+[[projects]]
+name = "Acme"
+[[projects.tasks]]
+content = "Ambiguous"
+due = "tomorrow"
+due_in_days = 2
+"#,
+    )
+    .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Acme/Ambiguous sets both due and due_in_days"));
+
+    for days in [i64::MAX, -1_000_000_000] {
+        let error = Template::from_toml(&format!(
+            "[[projects]]\nname = \"Acme\"\n[[projects.tasks]]\ncontent = \"Far\"\ndue_in_days = {days}\n"
+        ))
+        .unwrap_err();
+        assert!(error.to_string().contains("Acme/Far: due_in_days"), "{error}");
+    }
+}
+
+const YAML_TEMPLATE: &str = r#"
+labels:
+  - name: client
+    color: blue
+projects:
+  - name: Acme
+    tasks:
+      - content: Weekly report
+        due: every friday
+    sections:
+      - name: Onboarding
+        tasks:
+          - content: Kickoff call
+            labels: [client]
+            priority: 4
+            due_in_days: 3
+            subtasks:
+              - content: Send agenda
+    projects:
+      - name: Phase 1
+"#;
+
+#[tokio::test]
+async fn test_parse_yaml_template() {
+    assert_eq!(
+        Template::from_yaml(YAML_TEMPLATE).unwrap(),
+        Template::from_toml(TEMPLATE).unwrap()
+    );
+
+    let error = Template::from_yaml(
+        "projects:\n  - name: Acme\n    tasks:\n      - content: Ambiguous\n        due: tomorrow\n        due_in_days: 2\n",
+    )
+    .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Acme/Ambiguous sets both due and due_in_days"));
+
+    let dir = std::env::temp_dir().join(format!("todoist-templates-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for (name, text) in [
+        ("client.yml", YAML_TEMPLATE),
+        ("client.yaml", YAML_TEMPLATE),
+        ("client.toml", TEMPLATE),
+    ] {
+        let path = dir.join(name);
+        std::fs::write(&path, text).unwrap();
+        assert_eq!(
+            Template::load(&path).await.unwrap(),
+            Template::from_toml(TEMPLATE).unwrap(),
+            "{name}"
+        );
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_plan_template_on_empty_account() {
+    let server = MockServer::start().await;
+    mount_account(&server, json!([]), json!([]), json!([]), json!([])).await;
+
+    let template = Template::from_toml(TEMPLATE).unwrap();
+    let plan = wrapper(&server).plan_template(&template).await.unwrap();
+    assert_eq!(
+        plan.to_string(),
+        "+ label client\n\
+         + project Acme\n\
+         + task Acme/Weekly report\n\
+         + section Acme/Onboarding\n\
+         + task Acme/Onboarding/Kickoff call\n\
+         + task Acme/Onboarding/Kickoff call/Send agenda\n\
+         + project Acme/Phase 1\n"
+    );
+    assert!(plan.steps.iter().all(|step| step.id.is_none()));
+    assert!(posts(&server.received_requests().await.unwrap()).is_empty());
+}
+
+#[tokio::test]
+async fn test_apply_template_creates_only_missing_objects() {
+    let server = MockServer::start().await;
+    mount_account(
+        &server,
+        json!([project("p1", "Acme", None), project("p9", "Phase 1", None)]),
+        json!([{ "id": "s1", "name": "Onboarding", "project_id": "p1", "order": 1, "url": "https://todoist.com" }]),
+        json!([task("t1", "Kickoff call", Some("s1"), None)]),
+        json!([{ "id": "l1", "name": "Client", "color": "blue", "order": 1, "is_favorite": false }]),
+    )
+    .await;
+
+    let template = Template::from_toml(TEMPLATE).unwrap();
+    let plan = wrapper(&server).apply_template(&template).await.unwrap();
+    let created: Vec<_> = plan.creations().map(|step| step.path.as_str()).collect();
+    // A top-level "Phase 1" is not the sub-project of the template
+    assert_eq!(
+        created,
+        vec![
+            "Acme/Weekly report",
+            "Acme/Onboarding/Kickoff call/Send agenda",
+            "Acme/Phase 1"
+        ]
+    );
+    assert_eq!(plan.steps[4].id.as_deref(), Some("t1"));
+    assert_eq!(plan.steps[5].id.as_deref(), Some("new-Send agenda"));
+
+    let posts = posts(&server.received_requests().await.unwrap());
+    assert_eq!(posts.len(), 3);
+    assert_eq!(posts[0].0, "/tasks");
+    assert_eq!(posts[0].1["due_string"], "every friday");
+    assert_eq!(posts[0].1["project_id"], "p1");
+    assert_eq!(posts[1].1["parent_id"], "t1");
+    assert_eq!(posts[1].1["section_id"], "s1");
+    assert_eq!(posts[2].0, "/projects");
+    assert_eq!(posts[2].1["parent_id"], "p1");
+}
+
+#[tokio::test]
+async fn test_apply_template_matches_completed_tasks_ignoring_case() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/completed/get_all"))
+        .and(query_param("project_id", "p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "items": [{
+                "id": "c1",
+                "task_id": "t3",
+                "content": "Weekly report",
+                "project_id": "p1",
+                "section_id": null,
+                "completed_at": "2024-05-03T10:00:00Z",
+                "note_count": 0,
+            }],
+            "projects": {},
+        })))
+        .mount(&server)
+        .await;
+    mount_account(
+        &server,
+        json!([project("p1", "ACME", None), project("p2", "phase 1", Some("p1"))]),
+        json!([{ "id": "s1", "name": "onboarding", "project_id": "p1", "order": 1, "url": "https://todoist.com" }]),
+        json!([
+            task("t1", "kickoff call", Some("s1"), None),
+            task("t2", "Send Agenda", Some("s1"), Some("t1")),
+        ]),
+        json!([{ "id": "l1", "name": "client", "color": "blue", "order": 1, "is_favorite": false }]),
+    )
+    .await;
+
+    let template = Template::from_toml(TEMPLATE).unwrap();
+    let plan = wrapper(&server).apply_template(&template).await.unwrap();
+    assert!(plan.is_up_to_date(), "{plan}");
+    assert_eq!(plan.steps[2].id.as_deref(), Some("t3"));
+    assert!(posts(&server.received_requests().await.unwrap()).is_empty());
+}
+
+#[tokio::test]
+async fn test_apply_template_error_keeps_partial_plan() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/tasks"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    mount_account(&server, json!([]), json!([]), json!([]), json!([])).await;
+
+    let template = Template::from_toml(TEMPLATE).unwrap();
+    let error = wrapper(&server).apply_template(&template).await.unwrap_err();
+    // The label and the project were created before the first task failed
+    let created: Vec<_> = error.plan.creations().map(|step| step.path.as_str()).collect();
+    assert_eq!(created, vec!["client", "Acme"]);
+    assert_eq!(error.plan.steps[1].id.as_deref(), Some("new-Acme"));
+    assert!(error.to_string().contains("failed after 2 steps"), "{error}");
+}
+
+#[tokio::test]
+async fn test_apply_template_computes_relative_due_dates() {
+    let server = MockServer::start().await;
+    mount_account(&server, json!([]), json!([]), json!([]), json!([])).await;
+
+    let template = Template::from_toml(TEMPLATE).unwrap();
+    let plan = wrapper(&server).apply_template(&template).await.unwrap();
+    assert_eq!(plan.creations().count(), 7);
+
+    let posts = posts(&server.received_requests().await.unwrap());
+    let kickoff = posts
+        .iter()
+        .find(|(_, body)| body["content"] == "Kickoff call")
+        .unwrap();
+    let expected = chrono::Local::now().date_naive() + chrono::Duration::days(3);
+    assert_eq!(kickoff.1["due_date"], expected.format("%Y-%m-%d").to_string());
+    assert_eq!(kickoff.1["section_id"], "new-Onboarding");
+    assert_eq!(kickoff.1["labels"], json!(["client"]));
+}
+
+#[tokio::test]
+async fn test_apply_template_is_idempotent() {
+    let server = MockServer::start().await;
+    mount_account(
+        &server,
+        json!([project("p1", "Acme", None), project("p2", "Phase 1", Some("p1"))]),
+        json!([{ "id": "s1", "name": "Onboarding", "project_id": "p1", "order": 1, "url": "https://todoist.com" }]),
+        json!([
+            task("t1", "Kickoff call", Some("s1"), None),
+            task("t2", "Send agenda", Some("s1"), Some("t1")),
+            task("t3", "Weekly report", None, None),
+        ]),
+        json!([{ "id": "l1", "name": "client", "color": "blue", "order": 1, "is_favorite": false }]),
+    )
+    .await;
+
+    let template = Template::from_toml(TEMPLATE).unwrap();
+    let plan = wrapper(&server).apply_template(&template).await.unwrap();
+    assert!(plan.is_up_to_date());
+    assert!(plan.to_string().lines().all(|line| line.starts_with("= ")));
+    assert!(posts(&server.received_requests().await.unwrap()).is_empty());
+}
+
+#[tokio::test]
+async fn test_apply_template_rejects_out_of_range_due_dates() {
+    let server = MockServer::start().await;
+    mount_account(&server, json!([]), json!([]), json!([]), json!([])).await;
+
+    // Built in code, so never validated
+    let template = Template {
+        projects: vec![ProjectTemplate {
+            name: "Acme".to_string(),
+            tasks: vec![TaskTemplate {
+                content: "Far".to_string(),
+                due_in_days: Some(i64::MAX),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let error = wrapper(&server).apply_template(&template).await.unwrap_err();
+    assert!(error.to_string().contains("out of range"), "{error}");
+}
@@ -0,0 +1,159 @@
+use serde_json::json;
+use std::time::Duration;
+use todoist_api::webhook_server::*;
+use todoist_api::webhooks::{self, Webhook, WebhookEvent};
+use tokio::sync::mpsc;
+
+const SECRET: &str = "0123456789abcdef";
+
+fn fixture(event_name: &str) -> Vec<u8> {
+    serde_json::to_vec(&json!({
+        "event_name": event_name,
+        "user_id": "2671355",
+        "event_data": { "id": "1", "project_id": "p1", "content": "Buy milk", "name": "Groceries" },
+        "version": "9",
+    }))
+    .unwrap()
+}
+
+/// Start a server on a random local port and return its URL
+async fn start(server: WebhookServer) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(server.serve(listener));
+    format!("http://{address}")
+}
+
+async fn post(url: &str, body: &[u8], signature: Option<&str>, delivery_id: &str) -> u16 {
+    let mut request = reqwest::Client::new()
+        .post(url)
+        .header(webhooks::DELIVERY_ID_HEADER, delivery_id)
+        .body(body.to_vec());
+    if let Some(signature) = signature {
+        request = request.header(webhooks::SIGNATURE_HEADER, signature);
+    }
+    request.send().await.unwrap().status().as_u16()
+}
+
+async fn received(receiver: &mut mpsc::UnboundedReceiver<String>) -> String {
+    tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+        .await
+        .expect("handler was not called")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_dispatches_events_to_handlers() {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let (item_sender, any_sender) = (sender.clone(), sender);
+    let server = WebhookServer::new(SECRET)
+        .on("item:added", move |webhook: Webhook| {
+            let sender = item_sender.clone();
+            async move {
+                if let WebhookEvent::ItemAdded(task) = webhook.event {
+                    sender.send(format!("added {}", task.content))?;
+                }
+                Ok(())
+            }
+        })
+        .on_any(move |webhook: Webhook| {
+            let sender = any_sender.clone();
+            async move {
+                sender.send(format!("any {}", webhook.event.name()))?;
+                Ok(())
+            }
+        });
+    let url = format!("{}{DEFAULT_ROUTE}", start(server).await);
+
+    let body = fixture("item:added");
+    assert_eq!(post(&url, &body, Some(&webhooks::sign(SECRET, &body)), "d1").await, 200);
+    assert_eq!(received(&mut receiver).await, "added Buy milk");
+    assert_eq!(received(&mut receiver).await, "any item:added");
+
+    let body = fixture("project:added");
+    assert_eq!(post(&url, &body, Some(&webhooks::sign(SECRET, &body)), "d2").await, 200);
+    assert_eq!(received(&mut receiver).await, "any project:added");
+}
+
+#[tokio::test]
+async fn test_rejects_unsigned_and_invalid_deliveries() {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let server = WebhookServer::new(SECRET).on_any(move |webhook: Webhook| {
+        let sender = sender.clone();
+        async move {
+            sender.send(webhook.delivery_id.unwrap_or_default())?;
+            Ok(())
+        }
+    });
+    let url = format!("{}{DEFAULT_ROUTE}", start(server).await);
+    let body = fixture("item:added");
+
+    assert_eq!(post(&url, &body, None, "d1").await, 401);
+    assert_eq!(
+        post(&url, &body, Some(&webhooks::sign("wrong", &body)), "d1").await,
+        401
+    );
+    assert_eq!(post(&url, b"{}", Some(&webhooks::sign(SECRET, b"{}")), "d1").await, 400);
+
+    // The delivery is still accepted once correctly signed
+    assert_eq!(post(&url, &body, Some(&webhooks::sign(SECRET, &body)), "d1").await, 200);
+    assert_eq!(received(&mut receiver).await, "d1");
+}
+
+#[tokio::test]
+async fn test_retried_deliveries_are_acknowledged_once() {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let server = WebhookServer::new(SECRET)
+        .with_route("/hooks")
+        .on("item:added", move |webhook: Webhook| {
+            let sender = sender.clone();
+            async move {
+                sender.send(webhook.delivery_id.unwrap_or_default())?;
+                Ok(())
+            }
+        });
+    let base = start(server).await;
+    let body = fixture("item:added");
+    let signature = webhooks::sign(SECRET, &body);
+
+    assert_eq!(
+        post(&format!("{base}{DEFAULT_ROUTE}"), &body, Some(&signature), "d1").await,
+        404
+    );
+    let url = format!("{base}/hooks");
+    assert_eq!(post(&url, &body, Some(&signature), "d1").await, 200);
+    assert_eq!(post(&url, &body, Some(&signature), "d1").await, 200);
+    assert_eq!(post(&url, &body, Some(&signature), "d2").await, 200);
+
+    assert_eq!(received(&mut receiver).await, "d1");
+    assert_eq!(received(&mut receiver).await, "d2");
+    assert!(receiver.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_answers_before_slow_handlers_finish() {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let (error_sender, mut errors) = mpsc::unbounded_channel();
+    let server = WebhookServer::new(SECRET)
+        .on("item:added", move |_| {
+            let sender = sender.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                sender.send("done".to_string())?;
+                anyhow::bail!("handler failed")
+            }
+        })
+        .on_error(move |webhook, error| {
+            let _ = error_sender.send(format!("{}: {error}", webhook.event.name()));
+        });
+    let url = format!("{}{DEFAULT_ROUTE}", start(server).await);
+    let body = fixture("item:added");
+
+    let started = std::time::Instant::now();
+    assert_eq!(post(&url, &body, Some(&webhooks::sign(SECRET, &body)), "d1").await, 200);
+    assert!(started.elapsed() < Duration::from_millis(300));
+    assert!(receiver.try_recv().is_err());
+
+    assert_eq!(received(&mut receiver).await, "done");
+    assert_eq!(received(&mut errors).await, "item:added: handler failed");
+}
@@ -0,0 +1,198 @@
+use serde_json::{json, Value};
+use todoist_api::webhooks::{self, Webhook, WebhookError, WebhookEvent, WebhookVerifier};
+
+const SECRET: &str = "0123456789abcdef";
+
+fn item(content: &str, checked: bool) -> Value {
+    json!({
+        "id": "2995104339",
+        "project_id": "2203306141",
+        "content": content,
+        "description": "",
+        "priority": 4,
+        "child_order": 3,
+        "labels": ["work"],
+        "checked": checked,
+        "is_deleted": false,
+        "added_at": "2024-02-10T10:33:38.000000Z",
+        "added_by_uid": "2671355",
+        "responsible_uid": null,
+        "due": { "date": "2024-02-12", "string": "Feb 12", "lang": "en", "is_recurring": false },
+    })
+}
+
+fn payload(event_name: &str, event_data: Value) -> Vec<u8> {
+    serde_json::to_vec(&json!({
+        "event_name": event_name,
+        "user_id": "2671355",
+        "event_data": event_data,
+        "initiator": {
+            "id": "2671355",
+            "email": "alice@example.com",
+            "full_name": "Alice",
+            "image_id": "ad38375bdb094286af59f1eab36d8f20",
+            "is_premium": true,
+        },
+        "version": "9",
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_signature_matches_known_value() {
+    // Reference value computed with `openssl dgst -sha256 -hmac key -binary | base64`
+    assert_eq!(
+        webhooks::sign("key", b"The quick brown fox jumps over the lazy dog"),
+        "97yD9DBThCSxMpjmqm+xQ+9NWaFJRhdZl0edvC0aPNg="
+    );
+
+    let body = payload("item:added", item("Buy milk", false));
+    let signature = webhooks::sign(SECRET, &body);
+    assert!(webhooks::verify_signature(SECRET, &body, &signature));
+    assert!(!webhooks::verify_signature("other-secret", &body, &signature));
+    assert!(!webhooks::verify_signature(SECRET, b"tampered", &signature));
+    assert!(!webhooks::verify_signature(SECRET, &body, "not base64!"));
+}
+
+#[test]
+fn test_item_events_are_typed() {
+    let webhook = Webhook::parse(&payload("item:added", item("Buy milk", false))).unwrap();
+    assert_eq!(webhook.user_id, "2671355");
+    assert_eq!(webhook.version, "9");
+    assert_eq!(webhook.initiator.unwrap().full_name, "Alice");
+    match webhook.event {
+        WebhookEvent::ItemAdded(task) => {
+            assert_eq!(task.content, "Buy milk");
+            assert_eq!(task.priority, 4);
+            assert_eq!(task.labels, vec!["work"]);
+            assert_eq!(task.due.unwrap().date, "2024-02-12");
+        }
+        other => panic!("unexpected event {other:?}"),
+    }
+
+    let webhook = Webhook::parse(&payload("item:completed", item("Buy milk", true))).unwrap();
+    assert!(matches!(webhook.event, WebhookEvent::ItemCompleted(task) if task.is_completed));
+}
+
+#[test]
+fn test_item_updated_carries_previous_version() {
+    let mut body: Value = serde_json::from_slice(&payload("item:updated", item("Buy oat milk", false))).unwrap();
+    body["event_data_extra"] = json!({ "old_item": item("Buy milk", false), "update_intent": "item_updated" });
+
+    let webhook = Webhook::parse(&serde_json::to_vec(&body).unwrap()).unwrap();
+    match webhook.event {
+        WebhookEvent::ItemUpdated { task, before } => {
+            assert_eq!(task.content, "Buy oat milk");
+            assert_eq!(before.unwrap().content, "Buy milk");
+        }
+        other => panic!("unexpected event {other:?}"),
+    }
+}
+
+#[test]
+fn test_other_resources_are_typed() {
+    let note =
+        json!({ "id": "n1", "item_id": "2995104339", "content": "Almond?", "posted_at": "2024-02-10T10:40:00Z" });
+    let webhook = Webhook::parse(&payload("note:added", note)).unwrap();
+    assert!(matches!(&webhook.event, WebhookEvent::NoteAdded(c) if c.task_id.as_deref() == Some("2995104339")));
+
+    let project = json!({ "id": "p1", "name": "Groceries", "color": "green", "is_archived": true });
+    let webhook = Webhook::parse(&payload("project:archived", project)).unwrap();
+    assert!(matches!(&webhook.event, WebhookEvent::ProjectArchived(p) if p.name == "Groceries"));
+
+    let section = json!({ "id": "s1", "name": "Dairy", "project_id": "p1", "section_order": 2 });
+    let webhook = Webhook::parse(&payload("section:updated", section)).unwrap();
+    assert!(matches!(&webhook.event, WebhookEvent::SectionUpdated(s) if s.order == 2));
+
+    let label = json!({ "id": "l1", "name": "errands", "color": "red" });
+    let webhook = Webhook::parse(&payload("label:deleted", label)).unwrap();
+    assert_eq!(webhook.event.name(), "label:deleted");
+
+    let reminder = json!({ "id": "r1", "item_id": "2995104339" });
+    let webhook = Webhook::parse(&payload("reminder:fired", reminder.clone())).unwrap();
+    assert_eq!(
+        webhook.event,
+        WebhookEvent::Other {
+            event_name: "reminder:fired".to_string(),
+            data: reminder,
+        }
+    );
+}
+
+#[test]
+fn test_invalid_payloads_are_rejected() {
+    assert!(matches!(
+        Webhook::parse(b"not json"),
+        Err(WebhookError::InvalidPayload(_))
+    ));
+
+    let error = Webhook::parse(&payload("item:added", json!({ "id": "1" }))).unwrap_err();
+    assert!(error.to_string().starts_with("invalid webhook payload: item:added:"));
+}
+
+#[test]
+fn test_verifier_checks_signatures_and_replays() {
+    let verifier = WebhookVerifier::new(SECRET);
+    let body = payload("item:added", item("Buy milk", false));
+    let signature = webhooks::sign(SECRET, &body);
+
+    assert_eq!(
+        verifier.verify(&body, None, Some("d1")).unwrap_err(),
+        WebhookError::MissingSignature
+    );
+    assert_eq!(
+        verifier
+            .verify(&body, Some(&webhooks::sign("wrong", &body)), Some("d1"))
+            .unwrap_err(),
+        WebhookError::InvalidSignature
+    );
+
+    let webhook = verifier.verify(&body, Some(&signature), Some("d1")).unwrap();
+    assert_eq!(webhook.delivery_id.as_deref(), Some("d1"));
+    assert!(verifier.is_replay("d1"));
+    assert_eq!(
+        verifier.verify(&body, Some(&signature), Some("d1")).unwrap_err(),
+        WebhookError::Replayed("d1".to_string())
+    );
+    // Deliveries without an ID cannot be deduplicated, so they are rejected unless allowed
+    assert_eq!(
+        verifier.verify(&body, Some(&signature), None).unwrap_err(),
+        WebhookError::MissingDeliveryId
+    );
+    let verifier = WebhookVerifier::new(SECRET).allow_missing_delivery_id();
+    assert!(verifier.verify(&body, Some(&signature), None).is_ok());
+    assert!(verifier.verify(&body, Some(&signature), None).is_ok());
+}
+
+#[test]
+fn test_replay_window_is_bounded() {
+    let verifier = WebhookVerifier::with_replay_window(SECRET, 2);
+    let body = payload("item:added", item("Buy milk", false));
+    let signature = webhooks::sign(SECRET, &body);
+
+    for id in ["d1", "d2", "d3"] {
+        verifier.verify(&body, Some(&signature), Some(id)).unwrap();
+    }
+    assert!(!verifier.is_replay("d1"));
+    assert!(verifier.is_replay("d2"));
+    assert!(verifier.is_replay("d3"));
+}
+
+#[test]
+fn test_failed_deliveries_can_be_retried() {
+    let verifier = WebhookVerifier::new(SECRET);
+    let body = b"{\"event_name\": \"item:added\"}";
+    let signature = webhooks::sign(SECRET, body);
+
+    assert!(matches!(
+        verifier.verify(body, Some(&signature), Some("d1")),
+        Err(WebhookError::InvalidPayload(_))
+    ));
+    assert!(!verifier.is_replay("d1"));
+}
+
+#[test]
+fn test_debug_redacts_secret() {
+    let debug = format!("{:?}", WebhookVerifier::new(SECRET));
+    assert!(!debug.contains(SECRET));
+}
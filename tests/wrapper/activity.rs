@@ -0,0 +1,232 @@
+use serde_json::{json, Value};
+use todoist_api::*;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::common::wrapper;
+
+fn event(id: usize, object_type: &str, event_type: &str, extra_data: Value) -> Value {
+    json!({
+        "id": id.to_string(),
+        "object_type": object_type,
+        "object_id": format!("o{id}"),
+        "event_type": event_type,
+        "event_date": "2024-05-01T10:00:00Z",
+        "parent_project_id": "p1",
+        "parent_item_id": if object_type == "note" { json!("t1") } else { Value::Null },
+        "initiator_id": "u1",
+        "extra_data": extra_data,
+    })
+}
+
+fn page(events: Vec<Value>) -> Value {
+    json!({ "count": events.len(), "events": events })
+}
+
+#[tokio::test]
+async fn test_get_activity_sends_filters() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/activity/get"))
+        .and(query_param("object_type", "item"))
+        .and(query_param("object_id", "t1"))
+        .and(query_param("event_type", "completed"))
+        .and(query_param("initiator_id", "u1"))
+        .and(query_param("since", "2024-05-01T00:00:00"))
+        .and(query_param("until", "2024-06-01T00:00:00"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(page(vec![event(
+            1,
+            "item",
+            "completed",
+            json!({ "content": "Write report", "client": "web" }),
+        )])))
+        .mount(&server)
+        .await;
+
+    let args = ActivityArgs {
+        object_type: Some(ActivityObjectType::Task),
+        object_id: Some("t1".to_string()),
+        event_type: Some(ActivityAction::Completed),
+        initiator_id: Some("u1".to_string()),
+        since: Some("2024-05-01T00:00:00".to_string()),
+        until: Some("2024-06-01T00:00:00".to_string()),
+        ..Default::default()
+    };
+    let events = wrapper(&server).get_activity(&args).await.unwrap();
+
+    assert_eq!(events.len(), 1);
+    let completed = &events[0];
+    assert_eq!(completed.action, ActivityAction::Completed);
+    assert_eq!(
+        completed.object,
+        ActivityObject::Task {
+            id: "o1".to_string(),
+            project_id: Some("p1".to_string())
+        }
+    );
+    assert_eq!(completed.initiator_id.as_deref(), Some("u1"));
+    assert!(completed.changes.is_empty());
+    assert_eq!(completed.extra_data["client"], "web");
+}
+
+#[tokio::test]
+async fn test_update_events_carry_field_changes() {
+    let server = MockServer::start().await;
+    let updated = event(
+        1,
+        "item",
+        "updated",
+        json!({
+            "content": "Write final report",
+            "last_content": "Write report",
+            "due_date": "2024-05-03T00:00:00Z",
+            "last_due_date": "2024-05-01T00:00:00Z",
+            "last_responsible_uid": "u2",
+            "client": "web",
+        }),
+    );
+    let renamed = event(
+        2,
+        "project",
+        "updated",
+        json!({ "name": "Work", "last_name": "Office" }),
+    );
+    let commented = event(3, "note", "added", json!({ "content": "Looks good", "client": "web" }));
+    Mock::given(method("GET"))
+        .and(path("/activity/get"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(page(vec![updated, renamed, commented])))
+        .mount(&server)
+        .await;
+
+    let events = wrapper(&server).get_activity(&ActivityArgs::default()).await.unwrap();
+
+    let task = &events[0];
+    assert_eq!(task.changes.len(), 3);
+    let content = task.change("content").unwrap();
+    assert_eq!(content.before, Some(json!("Write report")));
+    assert_eq!(content.after, Some(json!("Write final report")));
+    assert_eq!(task.change("due").unwrap().before, Some(json!("2024-05-01T00:00:00Z")));
+    let assignee = task.change("assignee_id").unwrap();
+    assert_eq!(assignee.before, Some(json!("u2")));
+    assert_eq!(assignee.after, None);
+
+    let project = &events[1];
+    assert_eq!(project.object, ActivityObject::Project { id: "o2".to_string() });
+    assert_eq!(project.change("name").unwrap().before, Some(json!("Office")));
+
+    let comment = &events[2];
+    assert_eq!(
+        comment.object,
+        ActivityObject::Comment {
+            id: "o3".to_string(),
+            task_id: Some("t1".to_string()),
+            project_id: Some("p1".to_string())
+        }
+    );
+    assert_eq!(comment.changes.len(), 1);
+    assert_eq!(comment.change("content").unwrap().before, None);
+}
+
+#[tokio::test]
+async fn test_unknown_event_types_are_kept() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/activity/get"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(page(vec![event(1, "reminder", "fired", json!(null))])))
+        .mount(&server)
+        .await;
+
+    let events = wrapper(&server).get_activity(&ActivityArgs::default()).await.unwrap();
+    assert_eq!(events[0].action, ActivityAction::Other("fired".to_string()));
+    assert_eq!(
+        events[0].object,
+        ActivityObject::Other {
+            object_type: "reminder".to_string(),
+            id: "o1".to_string()
+        }
+    );
+    assert_eq!(events[0].object.id(), "o1");
+}
+
+#[tokio::test]
+async fn test_numeric_ids_are_accepted() {
+    let server = MockServer::start().await;
+    let numeric = json!({
+        "id": 955333384,
+        "object_type": "item",
+        "object_id": 2995104339u64,
+        "event_type": "completed",
+        "event_date": "2024-05-01T10:00:00Z",
+        "parent_project_id": 2203306141u64,
+        "parent_item_id": null,
+        "initiator_id": null,
+        "extra_data": {},
+    });
+    Mock::given(method("GET"))
+        .and(path("/activity/get"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(page(vec![numeric, event(2, "project", "added", json!({}))])),
+        )
+        .mount(&server)
+        .await;
+
+    let events = wrapper(&server).get_activity(&ActivityArgs::default()).await.unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].id, "955333384");
+    assert_eq!(
+        events[0].object,
+        ActivityObject::Task {
+            id: "2995104339".to_string(),
+            project_id: Some("2203306141".to_string())
+        }
+    );
+    assert_eq!(events[0].initiator_id, None);
+}
+
+#[tokio::test]
+async fn test_get_all_activity_paginates() {
+    let server = MockServer::start().await;
+    for (offset, ids) in [("0", 0..2), ("2", 2..4), ("4", 4..4)] {
+        let events = ids.map(|id| event(id, "project", "added", json!({}))).collect();
+        Mock::given(method("GET"))
+            .and(path("/activity/get"))
+            .and(query_param("limit", "2"))
+            .and(query_param("offset", offset))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(events)))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let args = ActivityArgs {
+        limit: Some(2),
+        offset: Some(0),
+        ..Default::default()
+    };
+    let events = wrapper(&server).get_all_activity(&args).await.unwrap();
+    let ids: Vec<_> = events.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, vec!["0", "1", "2", "3"]);
+}
+
+#[tokio::test]
+async fn test_get_all_activity_clamps_limit() {
+    let server = MockServer::start().await;
+    for (offset, ids) in [("0", 0..1), ("1", 1..1)] {
+        let events = ids.map(|id| event(id, "project", "added", json!({}))).collect();
+        Mock::given(method("GET"))
+            .and(path("/activity/get"))
+            .and(query_param("limit", "1"))
+            .and(query_param("offset", offset))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(events)))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let args = ActivityArgs {
+        limit: Some(0),
+        ..Default::default()
+    };
+    let events = wrapper(&server).get_all_activity(&args).await.unwrap();
+    assert_eq!(events.len(), 1);
+}
@@ -0,0 +1,160 @@
+use serde_json::{json, Value};
+use todoist_api::*;
+use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::common::{request_commands, wrapper};
+
+/// Mock Sync API that accepts every command
+fn accept_all(request: &Request) -> ResponseTemplate {
+    let sync_status: serde_json::Map<String, Value> = request_commands(request)
+        .iter()
+        .map(|command| (command["uuid"].as_str().unwrap().to_string(), json!("ok")))
+        .collect();
+    ResponseTemplate::new(200).set_body_json(json!({ "sync_status": sync_status, "temp_id_mapping": {} }))
+}
+
+fn archived_project(id: usize) -> Value {
+    json!({ "id": format!("p{id}"), "name": format!("Client {id}"), "color": "grey", "is_archived": true })
+}
+
+fn archived_section(id: &str) -> Value {
+    json!({ "id": id, "name": format!("Section {id}"), "project_id": "p1", "section_order": 1, "is_archived": true })
+}
+
+#[tokio::test]
+async fn test_archive_and_unarchive_commands() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .respond_with(accept_all)
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    todoist.archive_project("p1").await.unwrap();
+    todoist.unarchive_project("p1").await.unwrap();
+    todoist.archive_section("s1").await.unwrap();
+    todoist.unarchive_section("s1").await.unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let commands: Vec<Value> = requests.iter().flat_map(request_commands).collect();
+    let types: Vec<_> = commands.iter().map(|c| c["type"].as_str().unwrap()).collect();
+    assert_eq!(
+        types,
+        vec![
+            "project_archive",
+            "project_unarchive",
+            "section_archive",
+            "section_unarchive"
+        ]
+    );
+    assert_eq!(commands[0]["args"], json!({ "id": "p1" }));
+    assert_eq!(commands[2]["args"], json!({ "id": "s1" }));
+}
+
+#[tokio::test]
+async fn test_archive_rejected_by_server() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .respond_with(|request: &Request| {
+            let uuid = request_commands(request)[0]["uuid"].as_str().unwrap().to_string();
+            ResponseTemplate::new(200).set_body_json(json!({
+                "sync_status": { uuid: { "error_code": 21, "error": "Project not found" } },
+                "temp_id_mapping": {},
+            }))
+        })
+        .mount(&server)
+        .await;
+
+    let error = wrapper(&server).archive_project("missing").await.unwrap_err();
+    assert!(error.to_string().contains("Project not found"));
+}
+
+#[tokio::test]
+async fn test_get_archived_projects() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/projects/get_archived"))
+        .and(query_param("limit", "2"))
+        .and(query_param("offset", "4"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([archived_project(5), archived_project(6)])))
+        .mount(&server)
+        .await;
+
+    let args = ArchivedProjectArgs {
+        limit: Some(2),
+        offset: Some(4),
+    };
+    let projects = wrapper(&server).get_archived_projects(&args).await.unwrap();
+    assert_eq!(projects.len(), 2);
+    assert_eq!(projects[0].id, "p5");
+    assert_eq!(projects[1].name, "Client 6");
+}
+
+#[tokio::test]
+async fn test_get_all_archived_projects_paginates() {
+    let server = MockServer::start().await;
+    let first: Vec<Value> = (0..500).map(archived_project).collect();
+    Mock::given(method("GET"))
+        .and(path("/projects/get_archived"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(first))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/projects/get_archived"))
+        .and(query_param("offset", "500"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([archived_project(500)])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let projects = wrapper(&server).get_all_archived_projects().await.unwrap();
+    assert_eq!(projects.len(), 501);
+    assert_eq!(projects[500].id, "p500");
+}
+
+#[tokio::test]
+async fn test_get_archived_sections_follows_cursor() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/archive/sections"))
+        .and(query_param("project_id", "p1"))
+        .and(query_param_is_missing("cursor"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "sections": [archived_section("s1"), archived_section("s2")],
+            "has_more": true,
+            "next_cursor": "page-2",
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/archive/sections"))
+        .and(query_param("project_id", "p1"))
+        .and(query_param("cursor", "page-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "sections": [archived_section("s3")],
+            "has_more": false,
+            "next_cursor": "page-3",
+        })))
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    let first = todoist
+        .get_archived_sections(&ArchivedSectionArgs {
+            project_id: "p1".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(first.sections.len(), 2);
+    assert_eq!(first.next_cursor.as_deref(), Some("page-2"));
+
+    let all = todoist.get_all_archived_sections("p1").await.unwrap();
+    let ids: Vec<_> = all.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, vec!["s1", "s2", "s3"]);
+}
@@ -0,0 +1,270 @@
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use todoist_api::*;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::common::{task_json, wrapper};
+
+fn attachment(server: &MockServer, name: &str) -> Value {
+    json!({
+        "file_name": name,
+        "file_type": "text/plain",
+        "file_url": format!("{}/files/{name}", server.uri()),
+        "resource_type": "file",
+    })
+}
+
+fn comment(id: &str, task_id: Option<&str>, attachment: Option<Value>) -> Value {
+    json!({
+        "id": id,
+        "content": "See attached",
+        "posted_at": "2024-05-01T10:00:00Z",
+        "task_id": task_id,
+        "project_id": if task_id.is_none() { json!("p1") } else { Value::Null },
+        "attachment": attachment,
+    })
+}
+
+fn task(id: &str) -> Value {
+    let mut task = task_json(id, "Task");
+    task["comment_count"] = json!(1);
+    task
+}
+
+async fn mount_file(server: &MockServer, name: &str, content: &str) {
+    Mock::given(method("GET"))
+        .and(path(format!("/files/{name}")))
+        .and(header("Authorization", "Bearer test-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(content))
+        .mount(server)
+        .await;
+}
+
+/// Mock a project with a project comment, a task comment and a comment without attachment
+async fn mount_project(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/tasks"))
+        .and(query_param("project_id", "p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([task("t1")])))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/comments"))
+        .and(query_param("project_id", "p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([comment(
+            "c1",
+            None,
+            Some(attachment(server, "brief.txt"))
+        )])))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/comments"))
+        .and(query_param("task_id", "t1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            comment("c2", Some("t1"), Some(attachment(server, "minutes/notes.txt"))),
+            comment("c3", Some("t1"), None),
+        ])))
+        .mount(server)
+        .await;
+}
+
+fn temp_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("todoist-archive-{}", uuid::Uuid::new_v4()))
+}
+
+fn file_downloads(requests: &[wiremock::Request]) -> usize {
+    requests
+        .iter()
+        .filter(|request| request.url.path().starts_with("/files/"))
+        .count()
+}
+
+#[tokio::test]
+async fn test_download_attachment_streams_with_auth() {
+    let server = MockServer::start().await;
+    mount_file(&server, "brief.txt", "project brief").await;
+
+    let attachment: Attachment = serde_json::from_value(attachment(&server, "brief.txt")).unwrap();
+    let mut contents = Vec::new();
+    let written = wrapper(&server)
+        .download_attachment(&attachment, &mut contents)
+        .await
+        .unwrap();
+    assert_eq!(written, 13);
+    assert_eq!(contents, b"project brief");
+}
+
+#[tokio::test]
+async fn test_download_attachment_keeps_credentials_to_todoist() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/files/external.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("external"))
+        .mount(&server)
+        .await;
+
+    // Same server, reached through a host name the client does not talk to
+    let port = server.address().port();
+    let attachment = Attachment {
+        file_name: "external.txt".to_string(),
+        file_type: "text/plain".to_string(),
+        file_url: format!("http://localhost:{port}/files/external.txt"),
+        resource_type: "file".to_string(),
+    };
+    let mut contents = Vec::new();
+    wrapper(&server)
+        .download_attachment(&attachment, &mut contents)
+        .await
+        .unwrap();
+    assert_eq!(contents, b"external");
+
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests[0]
+        .headers
+        .iter()
+        .all(|(name, _)| name.as_str() != "authorization"));
+}
+
+#[tokio::test]
+async fn test_archive_project_attachments() {
+    let server = MockServer::start().await;
+    mount_project(&server).await;
+    mount_file(&server, "brief.txt", "project brief").await;
+    mount_file(&server, "minutes/notes.txt", "meeting notes").await;
+
+    let dir = temp_dir();
+    let todoist = wrapper(&server);
+    let report = todoist
+        .archive_project_attachments("p1", &dir, ExistingFileCheck::Hash)
+        .await
+        .unwrap();
+    assert_eq!(report.downloaded, vec!["c1", "c2"]);
+    assert!(report.skipped.is_empty());
+    assert!(report.failed.is_empty());
+
+    let manifest = AttachmentManifest::load(&dir).await.unwrap();
+    assert_eq!(manifest.entries.len(), 2);
+    let notes = manifest.entry("c2").unwrap();
+    assert_eq!(notes.path, "c2-minutes_notes.txt");
+    assert_eq!(notes.task_id.as_deref(), Some("t1"));
+    assert_eq!(notes.size, 13);
+    assert_eq!(std::fs::read_to_string(dir.join(&notes.path)).unwrap(), "meeting notes");
+    assert_eq!(manifest.entry("c1").unwrap().project_id.as_deref(), Some("p1"));
+
+    // A second run skips everything
+    let report = todoist
+        .archive_project_attachments("p1", &dir, ExistingFileCheck::Hash)
+        .await
+        .unwrap();
+    assert!(report.downloaded.is_empty());
+    assert_eq!(report.skipped, vec!["c1", "c2"]);
+    assert_eq!(file_downloads(&server.received_requests().await.unwrap()), 2);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_archive_checks_size_or_hash() {
+    let server = MockServer::start().await;
+    mount_project(&server).await;
+    mount_file(&server, "brief.txt", "project brief").await;
+    mount_file(&server, "minutes/notes.txt", "meeting notes").await;
+
+    let dir = temp_dir();
+    let todoist = wrapper(&server);
+    todoist
+        .archive_project_attachments("p1", &dir, ExistingFileCheck::Size)
+        .await
+        .unwrap();
+
+    // Same size, different content: only the hash check notices
+    std::fs::write(dir.join("c1-brief.txt"), "PROJECT BRIEF").unwrap();
+    let report = todoist
+        .archive_project_attachments("p1", &dir, ExistingFileCheck::Size)
+        .await
+        .unwrap();
+    assert_eq!(report.skipped.len(), 2);
+
+    let report = todoist
+        .archive_project_attachments("p1", &dir, ExistingFileCheck::Hash)
+        .await
+        .unwrap();
+    assert_eq!(report.downloaded, vec!["c1"]);
+    assert_eq!(report.skipped, vec!["c2"]);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("c1-brief.txt")).unwrap(),
+        "project brief"
+    );
+
+    // A deleted file is downloaded again
+    std::fs::remove_file(dir.join("c2-minutes_notes.txt")).unwrap();
+    let report = todoist
+        .archive_project_attachments("p1", &dir, ExistingFileCheck::Size)
+        .await
+        .unwrap();
+    assert_eq!(report.downloaded, vec!["c2"]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_archive_reports_failed_downloads() {
+    let server = MockServer::start().await;
+    mount_project(&server).await;
+    mount_file(&server, "brief.txt", "project brief").await;
+
+    let dir = temp_dir();
+    let report = wrapper(&server)
+        .archive_project_attachments("p1", &dir, ExistingFileCheck::default())
+        .await
+        .unwrap();
+    assert_eq!(report.downloaded, vec!["c1"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "c2");
+
+    // Failed downloads leave no file behind and are not recorded
+    let manifest = AttachmentManifest::load(&dir).await.unwrap();
+    assert!(manifest.entry("c2").is_none());
+    assert!(!dir.join("c2-minutes_notes.txt").exists());
+    assert!(!dir.join("c2-minutes_notes.txt.part").exists());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_archive_sanitizes_comment_ids() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/tasks"))
+        .and(query_param("project_id", "p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/comments"))
+        .and(query_param("project_id", "p1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([comment(
+            "../../escape",
+            None,
+            Some(attachment(&server, "brief.txt"))
+        )])))
+        .mount(&server)
+        .await;
+    mount_file(&server, "brief.txt", "project brief").await;
+
+    let dir = temp_dir();
+    let report = wrapper(&server)
+        .archive_project_attachments("p1", &dir, ExistingFileCheck::default())
+        .await
+        .unwrap();
+    assert_eq!(report.downloaded, vec!["../../escape"]);
+
+    let manifest = AttachmentManifest::load(&dir).await.unwrap();
+    let entry = manifest.entry("../../escape").unwrap();
+    assert_eq!(entry.path, "_.._escape-brief.txt");
+    assert_eq!(std::fs::read_to_string(dir.join(&entry.path)).unwrap(), "project brief");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
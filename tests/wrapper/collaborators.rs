@@ -0,0 +1,271 @@
+use serde_json::{json, Value};
+use todoist_api::*;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::common::{request_commands, task_json, wrapper};
+
+fn task(id: &str, project_id: &str, assignee_id: Option<&str>) -> Value {
+    let mut task = task_json(id, &format!("Task {id}"));
+    task["project_id"] = json!(project_id);
+    task["assignee_id"] = json!(assignee_id);
+    task
+}
+
+async fn mount_collaborators(server: &MockServer, project_id: &str, users: Value) {
+    Mock::given(method("GET"))
+        .and(path(format!("/projects/{project_id}/collaborators")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(users))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_get_project_collaborators() {
+    let server = MockServer::start().await;
+    mount_collaborators(
+        &server,
+        "p1",
+        json!([
+            { "id": "u1", "name": "Alice", "email": "alice@example.com" },
+            { "id": "u2", "name": "Bob", "email": "bob@example.com" },
+        ]),
+    )
+    .await;
+
+    let users = wrapper(&server).get_project_collaborators("p1").await.unwrap();
+    assert_eq!(users.len(), 2);
+    assert_eq!(users[0].name, "Alice");
+    assert!(!users[1].is_premium);
+}
+
+#[tokio::test]
+async fn test_create_task_rejects_non_collaborator() {
+    let server = MockServer::start().await;
+    mount_collaborators(
+        &server,
+        "p1",
+        json!([{ "id": "u1", "name": "Alice", "email": "alice@example.com" }]),
+    )
+    .await;
+    Mock::given(method("POST"))
+        .and(path("/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(task("t1", "p1", Some("u1"))))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    let mut args = CreateTaskArgs {
+        content: "Review contract".to_string(),
+        project_id: Some("p1".to_string()),
+        assignee_id: Some("u9".to_string()),
+        ..Default::default()
+    };
+    let error = todoist.create_task_validated(&args).await.unwrap_err();
+    assert!(error.to_string().contains("not a collaborator"));
+
+    args.assignee_id = Some("u1".to_string());
+    let created = todoist.create_task_validated(&args).await.unwrap();
+    assert_eq!(created.assignee_id.as_deref(), Some("u1"));
+}
+
+#[tokio::test]
+async fn test_create_task_skips_assignee_validation() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/tasks"))
+        .and(body_partial_json(json!({ "assignee_id": "u1" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(task("t1", "p1", Some("u1"))))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let args = CreateTaskArgs {
+        content: "Review contract".to_string(),
+        assignee_id: Some("u1".to_string()),
+        ..Default::default()
+    };
+    let created = wrapper(&server).create_task(&args).await.unwrap();
+    assert_eq!(created.assignee_id.as_deref(), Some("u1"));
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_create_task_assignee_follows_section_project() {
+    let server = MockServer::start().await;
+    mount_collaborators(
+        &server,
+        "p2",
+        json!([{ "id": "u1", "name": "Alice", "email": "alice@example.com" }]),
+    )
+    .await;
+    Mock::given(method("GET"))
+        .and(path("/sections/s1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "s1",
+            "project_id": "p2",
+            "order": 1,
+            "name": "Review",
+            "url": "https://todoist.com",
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(task("t1", "p2", Some("u1"))))
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    let args = CreateTaskArgs {
+        content: "Review contract".to_string(),
+        section_id: Some("s1".to_string()),
+        assignee_id: Some("u1".to_string()),
+        ..Default::default()
+    };
+    assert!(todoist.create_task_validated(&args).await.is_ok());
+
+    let inbox_args = CreateTaskArgs {
+        content: "Review contract".to_string(),
+        assignee_id: Some("u1".to_string()),
+        ..Default::default()
+    };
+    assert!(todoist.create_task_validated(&inbox_args).await.is_err());
+}
+
+#[tokio::test]
+async fn test_update_task_validates_against_task_project() {
+    let server = MockServer::start().await;
+    mount_collaborators(
+        &server,
+        "p1",
+        json!([{ "id": "u1", "name": "Alice", "email": "alice@example.com" }]),
+    )
+    .await;
+    Mock::given(method("GET"))
+        .and(path("/tasks/t1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(task("t1", "p1", None)))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/tasks/t1"))
+        .and(body_partial_json(json!({ "assignee_id": "u1" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(task("t1", "p1", Some("u1"))))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    let rejected = UpdateTaskArgs {
+        assignee_id: Some("u2".to_string()),
+        ..Default::default()
+    };
+    assert!(todoist.update_task_validated("t1", &rejected).await.is_err());
+
+    let accepted = UpdateTaskArgs {
+        assignee_id: Some("u1".to_string()),
+        ..Default::default()
+    };
+    assert!(todoist.update_task_validated("t1", &accepted).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_update_task_validated_reports_unknown_task() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/tasks/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Task not found"))
+        .mount(&server)
+        .await;
+
+    let args = UpdateTaskArgs {
+        assignee_id: Some("u1".to_string()),
+        ..Default::default()
+    };
+    let err = wrapper(&server)
+        .update_task_validated("missing", &args)
+        .await
+        .unwrap_err();
+    let status = err.downcast_ref::<reqwest::Error>().and_then(reqwest::Error::status);
+    assert_eq!(status, Some(reqwest::StatusCode::NOT_FOUND));
+}
+
+#[tokio::test]
+async fn test_resolve_assignees() {
+    let server = MockServer::start().await;
+    mount_collaborators(
+        &server,
+        "p1",
+        json!([
+            { "id": "u1", "name": "Alice", "email": "alice@example.com" },
+            { "id": "u2", "name": "Bob", "email": "bob@example.com" },
+        ]),
+    )
+    .await;
+    mount_collaborators(
+        &server,
+        "p2",
+        json!([{ "id": "u3", "name": "Carol", "email": "carol@example.com" }]),
+    )
+    .await;
+
+    let tasks: Vec<Task> = serde_json::from_value(json!([
+        task("t1", "p1", Some("u1")),
+        task("t2", "p1", Some("u1")),
+        task("t3", "p2", Some("u3")),
+        task("t4", "p3", None),
+    ]))
+    .unwrap();
+
+    let todoist = wrapper(&server);
+    let assignees = todoist.resolve_assignees(&tasks).await.unwrap();
+    assert_eq!(assignees.len(), 2);
+    assert_eq!(assignees["u1"].name, "Alice");
+    assert_eq!(assignees["u3"].name, "Carol");
+
+    assert_eq!(
+        todoist.get_task_assignee(&tasks[2]).await.unwrap().unwrap().name,
+        "Carol"
+    );
+    assert!(todoist.get_task_assignee(&tasks[3]).await.unwrap().is_none());
+    // Projects without assigned tasks are not queried
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests.iter().all(|request| !request.url.path().contains("/p3/")));
+}
+
+#[tokio::test]
+async fn test_share_and_unshare_project() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .respond_with(|request: &Request| {
+            let command = request_commands(request).remove(0);
+            let status = if command["args"]["email"] == "bob@example.com" {
+                json!("ok")
+            } else {
+                json!({ "error_code": 20, "error": "Invalid email" })
+            };
+            ResponseTemplate::new(200).set_body_json(json!({
+                "sync_status": { command["uuid"].as_str().unwrap(): status },
+                "temp_id_mapping": {},
+            }))
+        })
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    todoist.share_project("p1", "bob@example.com").await.unwrap();
+    todoist.remove_collaborator("p1", "bob@example.com").await.unwrap();
+    let error = todoist.share_project("p1", "not-an-email").await.unwrap_err();
+    assert!(error.to_string().contains("Invalid email"));
+
+    let requests = server.received_requests().await.unwrap();
+    let commands: Vec<Value> = requests.iter().flat_map(request_commands).collect();
+    assert_eq!(commands[0]["type"], "share_project");
+    assert_eq!(
+        commands[0]["args"],
+        json!({ "project_id": "p1", "email": "bob@example.com" })
+    );
+    assert_eq!(commands[1]["type"], "delete_collaborator");
+}
@@ -0,0 +1,186 @@
+use futures::TryStreamExt;
+use serde_json::{json, Value};
+use todoist_api::*;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::common::wrapper;
+
+fn completed_item(id: usize, project_id: &str) -> Value {
+    json!({
+        "id": format!("c{id}"),
+        "task_id": id.to_string(),
+        "content": format!("Task {id}"),
+        "project_id": project_id,
+        "section_id": null,
+        "user_id": "u1",
+        "completed_at": "2024-05-01T10:00:00.000000Z",
+        "note_count": 0,
+        "item_object": {
+            "id": id.to_string(),
+            "project_id": project_id,
+            "content": format!("Task {id}"),
+            "priority": 4,
+            "labels": ["urgent"],
+            "checked": true,
+            "added_at": "2024-04-01T00:00:00Z",
+        },
+    })
+}
+
+fn page(ids: std::ops::Range<usize>) -> Value {
+    json!({
+        "items": ids.map(|id| completed_item(id, "p1")).collect::<Vec<_>>(),
+        "projects": { "p1": { "id": "p1", "name": "Work", "color": "blue" } },
+        "sections": {},
+    })
+}
+
+#[tokio::test]
+async fn test_get_completed_tasks_page() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/completed/get_all"))
+        .and(query_param("project_id", "p1"))
+        .and(query_param("since", "2024-05-01T00:00:00"))
+        .and(query_param("until", "2024-05-02T00:00:00"))
+        .and(query_param("limit", "10"))
+        .and(query_param("offset", "20"))
+        .and(query_param("annotate_items", "true"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(page(1..3)))
+        .mount(&server)
+        .await;
+
+    let args = CompletedTaskArgs {
+        project_id: Some("p1".to_string()),
+        since: Some("2024-05-01T00:00:00".to_string()),
+        until: Some("2024-05-02T00:00:00".to_string()),
+        limit: Some(10),
+        offset: Some(20),
+    };
+    let completed = wrapper(&server).get_completed_tasks(&args).await.unwrap();
+
+    assert_eq!(completed.len(), 2);
+    let first = &completed[0];
+    assert_eq!(first.id, "c1");
+    assert_eq!(first.task_id, "1");
+    assert_eq!(first.completed_at, "2024-05-01T10:00:00.000000Z");
+    let task = first.task.as_ref().unwrap();
+    assert_eq!(task.content, "Task 1");
+    assert_eq!(task.labels, vec!["urgent"]);
+    assert!(task.is_completed);
+    assert_eq!(first.project.as_ref().unwrap().name, "Work");
+}
+
+#[tokio::test]
+async fn test_completed_tasks_stream_paginates() {
+    let server = MockServer::start().await;
+    for (offset, ids) in [("0", 0..3), ("3", 3..6), ("6", 6..7)] {
+        Mock::given(method("GET"))
+            .and(path("/completed/get_all"))
+            .and(query_param("limit", "3"))
+            .and(query_param("offset", offset))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(ids)))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let todoist = wrapper(&server);
+    let args = CompletedTaskArgs {
+        limit: Some(3),
+        offset: Some(0),
+        ..Default::default()
+    };
+    let completed: Vec<CompletedTask> = todoist.completed_tasks_stream(&args).try_collect().await.unwrap();
+
+    let task_ids: Vec<_> = completed.iter().map(|c| c.task_id.as_str()).collect();
+    assert_eq!(task_ids, vec!["0", "1", "2", "3", "4", "5", "6"]);
+}
+
+#[tokio::test]
+async fn test_completed_tasks_for_project_uses_largest_pages() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/completed/get_all"))
+        .and(query_param("project_id", "p1"))
+        .and(query_param("limit", "200"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(page(0..2)))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let completed = wrapper(&server).get_completed_tasks_for_project("p1").await.unwrap();
+    assert_eq!(completed.len(), 2);
+}
+
+#[tokio::test]
+async fn test_completed_tasks_stream_clamps_limit() {
+    let server = MockServer::start().await;
+    for (offset, ids) in [("0", 0..1), ("1", 1..1)] {
+        Mock::given(method("GET"))
+            .and(path("/completed/get_all"))
+            .and(query_param("limit", "1"))
+            .and(query_param("offset", offset))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(ids)))
+            .expect(2)
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/completed/get_all"))
+        .and(query_param("limit", "200"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(page(0..2)))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    for limit in [0, -5] {
+        let args = CompletedTaskArgs {
+            limit: Some(limit),
+            ..Default::default()
+        };
+        assert_eq!(todoist.get_all_completed_tasks(&args).await.unwrap().len(), 1);
+    }
+    let args = CompletedTaskArgs {
+        limit: Some(1000),
+        ..Default::default()
+    };
+    assert_eq!(todoist.get_all_completed_tasks(&args).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_completed_tasks_between_dates() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/completed/get_all"))
+        .and(query_param("since", "2024-05-01T00:00:00"))
+        .and(query_param("until", "2024-06-01T00:00:00"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "items": [], "projects": {} })))
+        .mount(&server)
+        .await;
+
+    let completed = wrapper(&server)
+        .get_completed_tasks_between("2024-05-01T00:00:00", "2024-06-01T00:00:00")
+        .await
+        .unwrap();
+    assert!(completed.is_empty());
+}
+
+#[tokio::test]
+async fn test_completed_tasks_stream_surfaces_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/completed/get_all"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    let result: Result<Vec<CompletedTask>> = todoist
+        .completed_tasks_stream(&CompletedTaskArgs::default())
+        .try_collect()
+        .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,118 @@
+use serde_json::{json, Value};
+use todoist_api::*;
+use wiremock::matchers::{body_json, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::common::{task_json, wrapper};
+
+fn label(id: &str, name: &str, order: i32) -> Label {
+    Label {
+        id: id.to_string(),
+        name: name.to_string(),
+        color: "red".to_string(),
+        order,
+        is_favorite: false,
+    }
+}
+
+fn task(id: &str, labels: &[&str]) -> Value {
+    let mut task = task_json(id, &format!("Task {id}"));
+    task["labels"] = json!(labels);
+    task
+}
+
+#[tokio::test]
+async fn test_get_shared_labels() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/labels/shared"))
+        .and(query_param("omit_personal", "true"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!(["client", "blocked"])))
+        .mount(&server)
+        .await;
+
+    let shared = wrapper(&server).get_shared_labels(true).await.unwrap();
+    assert_eq!(shared, vec!["client", "blocked"]);
+}
+
+#[tokio::test]
+async fn test_rename_and_remove_shared_label() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/labels/shared/rename"))
+        .and(body_json(json!({ "name": "client", "new_name": "customer" })))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/labels/shared/remove"))
+        .and(body_json(json!({ "name": "blocked" })))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    todoist.rename_shared_label("client", "customer").await.unwrap();
+    todoist.remove_shared_label("blocked").await.unwrap();
+    assert!(todoist.remove_shared_label("missing").await.is_err());
+}
+
+#[test]
+fn test_merge_labels() {
+    let labels = vec![label("l2", "Waiting", 2), label("l1", "urgent", 1)];
+    let shared = vec!["client".to_string(), "Urgent".to_string()];
+    let tasks: Vec<Task> = serde_json::from_value(json!([
+        task("1", &["urgent", "client"]),
+        task("2", &["URGENT", "errand"]),
+        task("3", &[]),
+    ]))
+    .unwrap();
+
+    let usage = merge_labels(&labels, &shared, &tasks);
+    let names: Vec<_> = usage.iter().map(|u| u.name.as_str()).collect();
+    assert_eq!(names, vec!["urgent", "Waiting", "client", "errand"]);
+
+    assert_eq!(usage[0].label.as_ref().unwrap().id, "l1");
+    assert!(usage[0].is_shared);
+    assert_eq!(usage[0].task_count, 2);
+
+    assert!(!usage[1].is_shared);
+    assert_eq!(usage[1].task_count, 0);
+
+    assert!(usage[2].label.is_none());
+    assert!(usage[2].is_shared);
+    assert_eq!(usage[2].task_count, 1);
+
+    assert!(!usage[3].is_shared);
+    assert_eq!(usage[3].task_count, 1);
+}
+
+#[tokio::test]
+async fn test_get_label_usage() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/labels"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "id": "l1", "name": "urgent", "color": "red", "order": 1, "is_favorite": false },
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/labels/shared"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!(["client"])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([task("1", &["client", "urgent"])])))
+        .mount(&server)
+        .await;
+
+    let usage = wrapper(&server).get_label_usage().await.unwrap();
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[0].name, "urgent");
+    assert_eq!(usage[1].name, "client");
+    assert!(usage.iter().all(|u| u.task_count == 1));
+}
@@ -0,0 +1,11 @@
+mod activity;
+mod archive;
+mod attachments;
+mod collaborators;
+mod completed;
+mod labels;
+mod quick_add;
+mod reminders;
+mod saved_filters;
+mod uploads;
+mod user;
@@ -0,0 +1,133 @@
+use serde_json::json;
+use todoist_api::*;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::common::{project_json, wrapper};
+
+#[tokio::test]
+async fn test_quick_add_reports_interpreted_tokens() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/quick/add"))
+        .and(body_string_contains("text=Call+Bob+tomorrow+3pm+%23Work+%40phone+p2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "t1",
+            "project_id": "p7",
+            "content": "Call Bob",
+            "priority": 3,
+            "due": {
+                "date": "2024-05-02T15:00:00",
+                "string": "tomorrow 3pm",
+                "lang": "en",
+                "is_recurring": false,
+            },
+            "labels": ["phone"],
+            "child_order": 1,
+            "added_at": "2024-05-01T10:00:00Z",
+            "meta": { "project": ["p7", "Work"] },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/projects/p7"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(project_json("p7", "Work")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = wrapper(&server)
+        .quick_add("Call Bob tomorrow 3pm #Work @phone p2", &QuickAddOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(result.task.id, "t1");
+    assert_eq!(result.task.content, "Call Bob");
+    assert_eq!(result.task.project_id, "p7");
+    assert_eq!(result.project.as_deref(), Some("#Work"));
+    assert_eq!(result.labels, vec!["@phone"]);
+    assert_eq!(result.priority.as_deref(), Some("p2"));
+    assert_eq!(result.due.as_deref(), Some("tomorrow 3pm"));
+}
+
+#[tokio::test]
+async fn test_quick_add_leaves_uninterpreted_tokens() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/quick/add"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "t2",
+            "project_id": "inbox",
+            "content": "Fix #Missing p2p sync",
+            "priority": 1,
+            "due": null,
+            "labels": [],
+        })))
+        .mount(&server)
+        .await;
+
+    let result = wrapper(&server)
+        .quick_add("Fix #Missing p2p sync", &QuickAddOptions::default())
+        .await
+        .unwrap();
+    assert!(result.project.is_none());
+    assert!(result.labels.is_empty());
+    assert!(result.priority.is_none());
+    assert!(result.due.is_none());
+}
+
+#[tokio::test]
+async fn test_quick_add_checks_project_token_against_task_project() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/quick/add"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "t4",
+            "project_id": "inbox",
+            "content": "Call Bob",
+            "priority": 1,
+            "labels": [],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/projects/inbox"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(project_json("inbox", "Inbox")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = wrapper(&server)
+        .quick_add("Call Bob #Work", &QuickAddOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(result.task.project_id, "inbox");
+    assert!(result.project.is_none());
+}
+
+#[tokio::test]
+async fn test_quick_add_options() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/quick/add"))
+        .and(body_string_contains("note=Dial-in+in+the+invite"))
+        .and(body_string_contains("reminder=tomorrow+2%3A30pm"))
+        .and(body_string_contains("auto_reminder=true"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "t3",
+            "project_id": "inbox",
+            "content": "Standup",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let options = QuickAddOptions {
+        note: Some("Dial-in in the invite".to_string()),
+        reminder: Some("tomorrow 2:30pm".to_string()),
+        auto_reminder: Some(true),
+    };
+    let result = wrapper(&server).quick_add("Standup", &options).await.unwrap();
+    assert_eq!(result.task.priority, 1);
+}
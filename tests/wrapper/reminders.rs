@@ -0,0 +1,236 @@
+use serde_json::{json, Value};
+use todoist_api::*;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::common::{request_commands, wrapper};
+
+/// Mock Sync API that accepts every command and assigns real IDs to temporary ones
+fn accept_all(request: &Request) -> ResponseTemplate {
+    let commands = request_commands(request);
+    let sync_status: serde_json::Map<String, Value> = commands
+        .iter()
+        .map(|command| (command["uuid"].as_str().unwrap().to_string(), json!("ok")))
+        .collect();
+    let temp_id_mapping: serde_json::Map<String, Value> = commands
+        .iter()
+        .filter_map(|command| command["temp_id"].as_str())
+        .map(|temp_id| (temp_id.to_string(), json!("r100")))
+        .collect();
+    ResponseTemplate::new(200).set_body_json(json!({
+        "sync_status": sync_status,
+        "temp_id_mapping": temp_id_mapping,
+    }))
+}
+
+/// Mock the reminders read back after `reminder_add`
+async fn mount_created(server: &MockServer, reminder: Value) {
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("resource_types"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "sync_token": "token",
+            "full_sync": true,
+            "reminders": [reminder],
+        })))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_get_reminders() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("reminders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "sync_token": "token",
+            "full_sync": true,
+            "reminders": [
+                { "id": "r1", "item_id": "t1", "notify_uid": "u1", "type": "relative", "minute_offset": 30 },
+                {
+                    "id": "r2",
+                    "item_id": "t2",
+                    "type": "absolute",
+                    "due": { "date": "2024-05-01T09:00:00Z", "is_recurring": false },
+                },
+                {
+                    "id": "r3",
+                    "item_id": "t1",
+                    "type": "location",
+                    "name": "Office",
+                    "loc_lat": "52.52",
+                    "loc_long": "13.40",
+                    "loc_trigger": "on_leave",
+                    "radius": 100,
+                },
+                { "id": "r4", "item_id": "t1", "type": "relative", "minute_offset": 10, "is_deleted": true },
+            ],
+        })))
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    let reminders = todoist.get_reminders().await.unwrap();
+    assert_eq!(reminders.len(), 3);
+    assert_eq!(reminders[0].trigger, ReminderTrigger::Relative { minute_offset: 30 });
+    assert_eq!(reminders[0].notify_uid.as_deref(), Some("u1"));
+    assert_eq!(
+        reminders[1].trigger,
+        ReminderTrigger::Absolute {
+            datetime: "2024-05-01T09:00:00Z".to_string()
+        }
+    );
+    assert_eq!(
+        reminders[2].trigger,
+        ReminderTrigger::Location {
+            name: "Office".to_string(),
+            latitude: "52.52".to_string(),
+            longitude: "13.40".to_string(),
+            trigger: LocationTrigger::OnLeave,
+            radius: 100,
+        }
+    );
+
+    let ids: Vec<_> = todoist
+        .get_task_reminders("t1")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|reminder| reminder.id)
+        .collect();
+    assert_eq!(ids, vec!["r1", "r3"]);
+}
+
+#[tokio::test]
+async fn test_remind_before() {
+    let server = MockServer::start().await;
+    mount_created(
+        &server,
+        json!({ "id": "r100", "item_id": "t1", "notify_uid": "u1", "type": "relative", "minute_offset": 30 }),
+    )
+    .await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .respond_with(accept_all)
+        .mount(&server)
+        .await;
+
+    let reminder = wrapper(&server).remind_before("t1", 30).await.unwrap();
+    assert_eq!(reminder.id, "r100");
+    assert_eq!(reminder.task_id, "t1");
+    assert_eq!(reminder.trigger, ReminderTrigger::Relative { minute_offset: 30 });
+    // Filled in by Todoist and read back
+    assert_eq!(reminder.notify_uid.as_deref(), Some("u1"));
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let command = &request_commands(&requests[0])[0];
+    assert_eq!(command["type"], "reminder_add");
+    assert_eq!(
+        command["args"],
+        json!({ "item_id": "t1", "type": "relative", "minute_offset": 30 })
+    );
+}
+
+#[tokio::test]
+async fn test_reminder_commands() {
+    let server = MockServer::start().await;
+    mount_created(
+        &server,
+        json!({
+            "id": "r100",
+            "item_id": "t1",
+            "notify_uid": "u2",
+            "type": "location",
+            "name": "Office",
+            "loc_lat": "52.52",
+            "loc_long": "13.40",
+            "loc_trigger": "on_enter",
+            "radius": 50,
+        }),
+    )
+    .await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .respond_with(accept_all)
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    todoist
+        .create_reminder(&CreateReminderArgs {
+            task_id: "t1".to_string(),
+            trigger: ReminderTrigger::Location {
+                name: "Office".to_string(),
+                latitude: "52.52".to_string(),
+                longitude: "13.40".to_string(),
+                trigger: LocationTrigger::OnEnter,
+                radius: 50,
+            },
+            notify_uid: Some("u2".to_string()),
+        })
+        .await
+        .unwrap();
+    todoist
+        .update_reminder(
+            "r1",
+            &UpdateReminderArgs {
+                trigger: Some(ReminderTrigger::Absolute {
+                    datetime: "2024-05-02T08:00:00Z".to_string(),
+                }),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    todoist.delete_reminder("r1").await.unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let commands: Vec<Value> = requests
+        .iter()
+        .filter(|request| !String::from_utf8_lossy(&request.body).contains("resource_types"))
+        .flat_map(request_commands)
+        .collect();
+    assert_eq!(
+        commands[0]["args"],
+        json!({
+            "item_id": "t1",
+            "notify_uid": "u2",
+            "type": "location",
+            "name": "Office",
+            "loc_lat": "52.52",
+            "loc_long": "13.40",
+            "loc_trigger": "on_enter",
+            "radius": 50,
+        })
+    );
+    assert_eq!(commands[1]["type"], "reminder_update");
+    assert_eq!(
+        commands[1]["args"],
+        json!({ "id": "r1", "type": "absolute", "due": { "date": "2024-05-02T08:00:00Z" } })
+    );
+    assert_eq!(commands[2]["type"], "reminder_delete");
+    assert_eq!(commands[2]["args"], json!({ "id": "r1" }));
+}
+
+#[tokio::test]
+async fn test_reminder_rejected_by_server() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .respond_with(|request: &Request| {
+            let uuid = request_commands(request)[0]["uuid"].as_str().unwrap().to_string();
+            ResponseTemplate::new(200).set_body_json(json!({
+                "sync_status": { uuid: { "error_code": 90, "error": "Task has no due date" } },
+                "temp_id_mapping": {},
+            }))
+        })
+        .mount(&server)
+        .await;
+
+    let error = wrapper(&server).remind_before("t1", 30).await.unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("reminder_add rejected: Task has no due date"));
+}
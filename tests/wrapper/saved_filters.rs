@@ -0,0 +1,173 @@
+use serde_json::{json, Value};
+use todoist_api::*;
+use wiremock::matchers::{body_string_contains, method, path, query_param};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::common::{request_commands, wrapper};
+
+/// Mock Sync API that accepts every command and assigns real IDs to temporary ones
+fn accept_all(request: &Request) -> ResponseTemplate {
+    let commands = request_commands(request);
+    let sync_status: serde_json::Map<String, Value> = commands
+        .iter()
+        .map(|command| (command["uuid"].as_str().unwrap().to_string(), json!("ok")))
+        .collect();
+    let temp_id_mapping: serde_json::Map<String, Value> = commands
+        .iter()
+        .filter_map(|command| command["temp_id"].as_str())
+        .map(|temp_id| (temp_id.to_string(), json!("f100")))
+        .collect();
+    ResponseTemplate::new(200).set_body_json(json!({
+        "sync_status": sync_status,
+        "temp_id_mapping": temp_id_mapping,
+    }))
+}
+
+async fn mount_filters(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("resource_types"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "sync_token": "token",
+            "full_sync": true,
+            "filters": [
+                { "id": "f2", "name": "On call", "query": "#Ops & @pager", "color": "red", "item_order": 2 },
+                { "id": "f1", "name": "Today", "query": "today | overdue", "color": "blue", "item_order": 1, "is_favorite": true },
+                { "id": "f3", "name": "Old", "query": "no date", "item_order": 3, "is_deleted": true },
+            ],
+        })))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_get_saved_filters() {
+    let server = MockServer::start().await;
+    mount_filters(&server).await;
+
+    let todoist = wrapper(&server);
+    let filters = todoist.get_saved_filters().await.unwrap();
+    let names: Vec<_> = filters.iter().map(|filter| filter.name.as_str()).collect();
+    assert_eq!(names, vec!["Today", "On call"]);
+    assert!(filters[0].is_favorite);
+    assert_eq!(filters[1].query, "#Ops & @pager");
+
+    let filter = todoist.get_saved_filter("on CALL").await.unwrap().unwrap();
+    assert_eq!(filter.id, "f2");
+    assert!(todoist.get_saved_filter("Old").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_run_saved_filter() {
+    let server = MockServer::start().await;
+    mount_filters(&server).await;
+    Mock::given(method("GET"))
+        .and(path("/tasks"))
+        .and(query_param("filter", "#Ops & @pager"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+            "id": "t1",
+            "content": "Rotate certificates",
+            "description": "",
+            "project_id": "p1",
+            "section_id": null,
+            "parent_id": null,
+            "order": 1,
+            "priority": 4,
+            "is_completed": false,
+            "labels": ["pager"],
+            "created_at": "2024-01-01T00:00:00Z",
+            "due": null,
+            "deadline": null,
+            "duration": null,
+            "assignee_id": null,
+            "url": "https://todoist.com",
+            "comment_count": 0,
+        }])))
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    let tasks = todoist.run_saved_filter("On call").await.unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].content, "Rotate certificates");
+
+    let error = todoist.run_saved_filter("Missing").await.unwrap_err();
+    assert!(error.to_string().contains("no saved filter named Missing"));
+}
+
+#[tokio::test]
+async fn test_saved_filter_commands() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("resource_types"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "sync_token": "token",
+            "full_sync": true,
+            "filters": [
+                { "id": "f1", "name": "Today", "query": "today | overdue", "color": "blue", "item_order": 1 },
+                { "id": "f100", "name": "Reviews", "query": "@review", "color": "green", "item_order": 4 },
+            ],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .respond_with(accept_all)
+        .mount(&server)
+        .await;
+
+    let todoist = wrapper(&server);
+    let filter = todoist
+        .create_saved_filter(&CreateSavedFilterArgs {
+            name: "Reviews".to_string(),
+            query: "@review".to_string(),
+            color: Some("green".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(filter.id, "f100");
+    assert_eq!(filter.color, "green");
+    // Assigned by Todoist and read back
+    assert_eq!(filter.order, 4);
+
+    todoist
+        .update_saved_filter(
+            "f100",
+            &UpdateSavedFilterArgs {
+                query: Some("@review & !assigned to: others".to_string()),
+                is_favorite: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    todoist.reorder_saved_filters(&["f2", "f100", "f1"]).await.unwrap();
+    todoist.delete_saved_filter("f100").await.unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let commands: Vec<Value> = requests
+        .iter()
+        .filter(|request| !String::from_utf8_lossy(&request.body).contains("resource_types"))
+        .flat_map(request_commands)
+        .collect();
+    let types: Vec<_> = commands.iter().map(|c| c["type"].as_str().unwrap()).collect();
+    assert_eq!(
+        types,
+        vec!["filter_add", "filter_update", "filter_update_orders", "filter_delete"]
+    );
+    assert_eq!(
+        commands[0]["args"],
+        json!({ "name": "Reviews", "query": "@review", "color": "green" })
+    );
+    assert_eq!(
+        commands[1]["args"],
+        json!({ "id": "f100", "query": "@review & !assigned to: others", "is_favorite": true })
+    );
+    assert_eq!(
+        commands[2]["args"],
+        json!({ "id_order_mapping": { "f2": 1, "f100": 2, "f1": 3 } })
+    );
+    assert_eq!(commands[3]["args"], json!({ "id": "f100" }));
+}
@@ -0,0 +1,157 @@
+use serde_json::json;
+use todoist_api::uploads::sniff_mime;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use crate::common::wrapper;
+
+const PNG_HEADER: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+fn upload_response(file_name: &str, file_type: &str) -> serde_json::Value {
+    json!({
+        "file_name": file_name,
+        "file_size": 16,
+        "file_type": file_type,
+        "file_url": format!("https://files.todoist.com/{file_name}"),
+        "resource_type": "file",
+        "upload_state": "completed",
+    })
+}
+
+async fn mount_upload(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/uploads/add"))
+        .respond_with(|request: &Request| {
+            let body = String::from_utf8_lossy(&request.body);
+            let file_name = body
+                .split("name=\"file_name\"\r\n\r\n")
+                .nth(1)
+                .and_then(|rest| rest.split("\r\n").next())
+                .unwrap_or_default()
+                .to_string();
+            let file_type = body
+                .split("Content-Type: ")
+                .nth(1)
+                .and_then(|rest| rest.split("\r\n").next())
+                .unwrap_or_default()
+                .to_string();
+            ResponseTemplate::new(200).set_body_json(upload_response(&file_name, &file_type))
+        })
+        .mount(server)
+        .await;
+}
+
+fn temp_file(name: &str, content: &[u8]) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("todoist-upload-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_sniff_mime() {
+    // Content wins over the file name
+    assert_eq!(sniff_mime(PNG_HEADER, "image.txt"), "image/png");
+    assert_eq!(sniff_mime(b"plain text", "notes.txt"), "text/plain");
+    assert_eq!(sniff_mime(b"{}", "data.json"), "application/json");
+    assert_eq!(sniff_mime(b"\0\x01", "blob"), "application/octet-stream");
+}
+
+#[tokio::test]
+async fn test_upload_file_from_path_streams_multipart() {
+    let server = MockServer::start().await;
+    mount_upload(&server).await;
+
+    let content = [PNG_HEADER, &[0u8; 1024]].concat();
+    let file = temp_file("diagram.png", &content);
+    let attachment = wrapper(&server)
+        .upload_file(file.as_path(), "diagram.png", None)
+        .await
+        .unwrap();
+
+    assert_eq!(attachment.file_name, "diagram.png");
+    assert_eq!(attachment.file_type, "image/png");
+    assert_eq!(attachment.file_url, "https://files.todoist.com/diagram.png");
+
+    let requests = server.received_requests().await.unwrap();
+    let request = &requests[0];
+    let header = |name: &str| {
+        request
+            .headers
+            .iter()
+            .find(|(header, _)| header.as_str() == name)
+            .map(|(_, values)| values.last().as_str().to_string())
+    };
+    assert!(header("content-type")
+        .unwrap()
+        .starts_with("multipart/form-data; boundary="));
+    assert_eq!(header("authorization").unwrap(), "Bearer test-token");
+    // The whole file is sent, including the bytes read for sniffing
+    let body = &request.body;
+    assert!(body.windows(content.len()).any(|window| window == content.as_slice()));
+    assert!(String::from_utf8_lossy(body).contains("filename=\"diagram.png\""));
+    std::fs::remove_dir_all(file.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn test_upload_file_from_bytes_with_explicit_mime() {
+    let server = MockServer::start().await;
+    mount_upload(&server).await;
+
+    let attachment = wrapper(&server)
+        .upload_file(b"a,b\n1,2\n".to_vec(), "report.csv", Some("text/csv"))
+        .await
+        .unwrap();
+    assert_eq!(attachment.file_type, "text/csv");
+}
+
+#[tokio::test]
+async fn test_upload_size_limit() {
+    let server = MockServer::start().await;
+    mount_upload(&server).await;
+
+    let todoist = wrapper(&server).with_max_upload_size(8);
+    let error = todoist.upload_file(vec![0u8; 9], "large.bin", None).await.unwrap_err();
+    assert!(error.to_string().contains("upload limit"));
+
+    let file = temp_file("large.bin", &[0u8; 9]);
+    assert!(todoist.upload_file(file.as_path(), "large.bin", None).await.is_err());
+    assert!(todoist.upload_file(vec![0u8; 8], "small.bin", None).await.is_ok());
+
+    // Oversized files never reach the server
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    std::fs::remove_dir_all(file.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn test_comment_with_file() {
+    let server = MockServer::start().await;
+    mount_upload(&server).await;
+    Mock::given(method("POST"))
+        .and(path("/comments"))
+        .and(body_partial_json(json!({
+            "task_id": "t1",
+            "content": "Signed contract",
+            "attachment": { "file_name": "contract.pdf", "file_type": "application/pdf" },
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "c1",
+            "content": "Signed contract",
+            "task_id": "t1",
+            "project_id": null,
+            "posted_at": "2024-05-01T10:00:00Z",
+            "attachment": upload_response("contract.pdf", "application/pdf"),
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let file = temp_file("contract.pdf", b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n");
+    let comment = wrapper(&server)
+        .comment_with_file("t1", "Signed contract", &file)
+        .await
+        .unwrap();
+    assert_eq!(comment.attachment.unwrap().file_name, "contract.pdf");
+    std::fs::remove_dir_all(file.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,112 @@
+use serde_json::json;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::common::wrapper;
+
+#[tokio::test]
+async fn test_get_current_user() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .and(body_string_contains("user"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "sync_token": "token",
+            "full_sync": true,
+            "user": {
+                "id": "u1",
+                "full_name": "Ada Lovelace",
+                "email": "ada@example.com",
+                "avatar_big": "https://avatars.example.com/u1.png",
+                "is_premium": true,
+                "business_account_id": null,
+                "tz_info": {
+                    "timezone": "Europe/Berlin",
+                    "gmt_string": "+02:00",
+                    "hours": 2,
+                    "minutes": 0,
+                    "is_dst": 1,
+                },
+                "start_day": 7,
+                "lang": "de",
+                "daily_goal": 5,
+                "weekly_goal": 25,
+                "karma": 12345.0,
+                "theme_id": "11",
+            },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let profile = wrapper(&server).get_current_user().await.unwrap();
+    assert_eq!(profile.user.id, "u1");
+    assert_eq!(profile.user.name, "Ada Lovelace");
+    assert!(profile.user.is_premium);
+    assert!(!profile.user.is_business_account);
+    assert_eq!(profile.timezone, "Europe/Berlin");
+    assert_eq!(profile.utc_offset, "+02:00");
+    assert_eq!(profile.start_day, 7);
+    assert_eq!(profile.lang, "de");
+    assert_eq!(profile.daily_goal, 5);
+    assert_eq!(profile.karma, 12345.0);
+}
+
+#[tokio::test]
+async fn test_get_current_user_missing_from_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/sync"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "sync_token": "token" })))
+        .mount(&server)
+        .await;
+
+    assert!(wrapper(&server).get_current_user().await.is_err());
+}
+
+#[tokio::test]
+async fn test_get_productivity_stats() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/completed/get_stats"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "karma": 8702.0,
+            "karma_trend": "up",
+            "karma_last_update": 5.0,
+            "completed_count": 2451,
+            "days_items": [
+                { "date": "2024-05-01", "total_completed": 4, "items": [{ "id": "p1", "completed": 4 }] },
+                { "date": "2024-04-30", "total_completed": 0, "items": [] },
+            ],
+            "week_items": [
+                { "from": "2024-04-29", "to": "2024-05-05", "total_completed": 4, "items": [] },
+            ],
+            "goals": {
+                "daily_goal": 5,
+                "weekly_goal": 30,
+                "karma_disabled": 0,
+                "vacation_mode": 0,
+                "current_daily_streak": { "count": 3, "start": "2024-04-29", "end": "2024-05-01" },
+                "max_daily_streak": { "count": 21, "start": "2024-01-02", "end": "2024-01-22" },
+                "current_weekly_streak": { "count": 0, "start": "", "end": "" },
+                "max_weekly_streak": { "count": 4 },
+            },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let stats = wrapper(&server).get_productivity_stats().await.unwrap();
+    assert_eq!(stats.karma, 8702.0);
+    assert_eq!(stats.karma_trend, "up");
+    assert_eq!(stats.completed_count, 2451);
+    assert_eq!(stats.daily_goal, 5);
+    assert_eq!(stats.weekly_goal, 30);
+    assert_eq!(stats.current_daily_streak.count, 3);
+    assert_eq!(stats.max_daily_streak.start, "2024-01-02");
+    assert_eq!(stats.max_weekly_streak.count, 4);
+    assert_eq!(stats.days.len(), 2);
+    assert_eq!(stats.days[0].date, "2024-05-01");
+    assert_eq!(stats.days[0].total_completed, 4);
+    assert_eq!(stats.weeks[0].from, "2024-04-29");
+}
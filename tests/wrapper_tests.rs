@@ -1,3 +1,6 @@
+mod common;
+mod wrapper;
+
 use todoist_api::*;
 
 #[test]